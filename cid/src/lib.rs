@@ -0,0 +1,125 @@
+//! Conversion between [`ContentRef`] and CID strings.
+//!
+//! Bare multihashes are shown as CIDv1 with the `raw` codec. CIDv0 strings (`Qm...`) are read
+//! as the CIDv1 `dag-pb` they stand for.
+
+use std::fmt;
+
+use cid::{multihash::Multihash, Cid, Version};
+use pallet_reputation::content::{ContentRef, DAG_PB, RAW};
+
+#[derive(Debug)]
+pub enum Error {
+    Cid(cid::Error),
+    /// A well-formed CID with a codec or hash function the pallet does not accept.
+    Unsupported,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Cid(e) => write!(f, "invalid CID: {e}"),
+            Error::Unsupported => write!(f, "codec or hash function not accepted on chain"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<cid::Error> for Error {
+    fn from(e: cid::Error) -> Self {
+        Error::Cid(e)
+    }
+}
+
+/// The CIDv1 a reference stands for.
+pub fn to_cid(content: &ContentRef) -> Cid {
+    let parts = content.parts();
+    let multihash = Multihash::wrap(parts.hash_function, parts.digest)
+        .expect("accepted digests are at most 64 bytes; qed");
+    Cid::new_v1(parts.codec.unwrap_or(RAW), multihash)
+}
+
+/// Base32 CIDv1 string, as IPFS gateways show it.
+pub fn to_cid_string(content: &ContentRef) -> String {
+    to_cid(content).to_string()
+}
+
+/// Parse a CID string into a reference the chain accepts.
+pub fn from_cid_string(s: &str) -> Result<ContentRef, Error> {
+    let cid = Cid::try_from(s)?;
+    let cid = match cid.version() {
+        Version::V0 => Cid::new_v1(DAG_PB, *cid.hash()),
+        Version::V1 => cid,
+    };
+    ContentRef::new(cid.to_bytes()).ok_or(Error::Unsupported)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pallet_reputation::content::{BLAKE2B_256, DAG_CBOR, DAG_JSON, SHA2_256, SHA2_512};
+
+    fn multihash(code: u64, digest: &[u8]) -> Multihash {
+        Multihash::wrap(code, digest).unwrap()
+    }
+
+    #[test]
+    fn cid_v0_reads_as_dag_pb() {
+        let v0 = Cid::new_v0(multihash(SHA2_256, &[0xab; 32])).unwrap().to_string();
+        assert!(v0.starts_with("Qm"));
+
+        let content = from_cid_string(&v0).unwrap();
+        let parts = content.parts();
+        assert_eq!((parts.codec, parts.hash_function, parts.digest), (Some(DAG_PB), SHA2_256, &[0xab; 32][..]));
+        assert_eq!(to_cid(&content), Cid::new_v1(DAG_PB, multihash(SHA2_256, &[0xab; 32])));
+        assert_eq!(from_cid_string(&to_cid_string(&content)).unwrap(), content);
+    }
+
+    #[test]
+    fn cid_v1_round_trips() {
+        for (codec, hash_function, len) in
+            [(RAW, SHA2_256, 32), (DAG_PB, SHA2_512, 64), (DAG_CBOR, BLAKE2B_256, 32), (DAG_JSON, SHA2_256, 32)]
+        {
+            let cid = Cid::new_v1(codec, multihash(hash_function, &vec![0xcd; len]));
+            let content = from_cid_string(&cid.to_string()).unwrap();
+
+            assert_eq!(content.as_bytes(), cid.to_bytes());
+            assert_eq!(to_cid(&content), cid);
+            assert_eq!(to_cid_string(&content), cid.to_string());
+        }
+    }
+
+    #[test]
+    fn bare_multihash_shows_as_raw_cid() {
+        let content = ContentRef::blake2_256([0xef; 32]);
+        let cid = to_cid(&content);
+        assert_eq!(cid, Cid::new_v1(RAW, multihash(BLAKE2B_256, &[0xef; 32])));
+
+        // Read back as the CID it was shown as, naming the same digest.
+        let read = from_cid_string(&to_cid_string(&content)).unwrap();
+        assert_eq!(read.parts().codec, Some(RAW));
+        assert_eq!(read.digest(), content.digest());
+        assert_eq!(to_cid(&read), cid);
+    }
+
+    #[test]
+    fn unsupported_cids_are_rejected() {
+        // git-raw codec, sha3-256 hash function and a short sha2-256 digest.
+        let unsupported = [
+            Cid::new_v1(0x78, multihash(SHA2_256, &[0xab; 32])),
+            Cid::new_v1(RAW, multihash(0x16, &[0xab; 32])),
+            Cid::new_v1(RAW, multihash(SHA2_256, &[0xab; 20])),
+        ];
+        for cid in unsupported {
+            assert!(matches!(from_cid_string(&cid.to_string()), Err(Error::Unsupported)));
+        }
+    }
+
+    #[test]
+    fn malformed_strings_are_rejected() {
+        for s in ["", "Qm", "bafy", "not a cid"] {
+            assert!(matches!(from_cid_string(s), Err(Error::Cid(_))));
+        }
+    }
+}
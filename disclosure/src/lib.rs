@@ -0,0 +1,353 @@
+//! Prover and off-chain verifier for private reputation disclosure.
+//!
+//! A holder opts in on chain with [`disclosure_key`] of a secret, waits for an epoch to be
+//! committed, reads their `DisclosureWitness` through `ReputationApi::disclosure_witness` at that
+//! epoch's block and calls [`prove`] with a threshold and a challenge from whoever asked. The
+//! proof shows their average is at least the threshold over at least `min_reviews` reviews under
+//! the epoch root, and nothing else.
+//!
+//! The verifier checks it with [`verify`] against the epoch root, or on chain through
+//! `ReputationApi::verify_score_proof`. Both run the same check as the runtime.
+
+use std::{cmp::Ordering, fmt};
+
+use ark_bn254::{Bn254, Fr};
+use ark_crypto_primitives::prf::blake2s::constraints::evaluate_blake2s;
+use ark_ff::PrimeField;
+use ark_groth16::{Groth16, ProvingKey};
+use ark_r1cs_std::{fields::fp::FpVar, prelude::*};
+use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError};
+use ark_serialize::{CanonicalSerialize, SerializationError};
+use ark_snark::{CircuitSpecificSetupSNARK, SNARK};
+use ark_std::rand::{CryptoRng, RngCore};
+use pallet_reputation::disclosure::{self as chain, DisclosureWitness, DISCLOSURE_TREE_DEPTH};
+
+pub use pallet_reputation::disclosure::disclosure_key;
+
+#[derive(Debug)]
+pub enum Error {
+    /// The witness does not meet the threshold or review count, so no valid proof exists.
+    ThresholdNotMet,
+    /// The witness path is not `DISCLOSURE_TREE_DEPTH` long.
+    MalformedWitness,
+    Synthesis(SynthesisError),
+    Serialization(SerializationError),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::ThresholdNotMet => write!(f, "profile does not meet the threshold"),
+            Error::MalformedWitness => write!(f, "witness path has the wrong length"),
+            Error::Synthesis(e) => write!(f, "constraint synthesis failed: {e}"),
+            Error::Serialization(e) => write!(f, "serialization failed: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<SynthesisError> for Error {
+    fn from(e: SynthesisError) -> Self {
+        Error::Synthesis(e)
+    }
+}
+
+impl From<SerializationError> for Error {
+    fn from(e: SerializationError) -> Self {
+        Error::Serialization(e)
+    }
+}
+
+/// "The leaf of a key I hold the secret of, under `root`, averages at least `threshold`
+/// hundredths over at least `min_reviews` reviews."
+#[derive(Clone)]
+pub struct ScoreThresholdCircuit {
+    // Private.
+    pub secret: [u8; 32],
+    pub index: u32,
+    pub total_score: u32,
+    pub review_count: u32,
+    pub siblings: Vec<[u8; 32]>,
+    // Public.
+    pub root: [u8; 32],
+    pub threshold: u32,
+    pub min_reviews: u32,
+    pub challenge: [u8; 32],
+}
+
+impl ScoreThresholdCircuit {
+    /// A circuit of the right shape for key generation.
+    pub fn blank() -> Self {
+        Self {
+            secret: [0u8; 32],
+            index: 0,
+            total_score: 0,
+            review_count: 0,
+            siblings: vec![[0u8; 32]; DISCLOSURE_TREE_DEPTH as usize],
+            root: [0u8; 32],
+            threshold: 0,
+            min_reviews: 0,
+            challenge: [0u8; 32],
+        }
+    }
+}
+
+/// In-circuit `disclosure::hash_pair` over little-endian bits.
+fn hash_pair(left: &[Boolean<Fr>], right: &[Boolean<Fr>]) -> Result<Vec<Boolean<Fr>>, SynthesisError> {
+    let input: Vec<_> = left.iter().chain(right).cloned().collect();
+    let digest: Vec<_> = evaluate_blake2s(&input)?.iter().flat_map(|word| word.to_bits_le()).collect();
+    // Same as `to_field_bytes`: the top three bits are cleared.
+    Ok(digest[..253].iter().cloned().chain([Boolean::FALSE; 3]).collect())
+}
+
+fn witness_bits(cs: &ConstraintSystemRef<Fr>, bytes: &[u8; 32]) -> Result<Vec<Boolean<Fr>>, SynthesisError> {
+    UInt8::new_witness_vec(cs.clone(), &bytes[..])?.to_bits_le()
+}
+
+fn public_input(cs: &ConstraintSystemRef<Fr>, input: &[u8; 32]) -> Result<FpVar<Fr>, SynthesisError> {
+    FpVar::new_input(cs.clone(), || Ok(Fr::from_le_bytes_mod_order(input)))
+}
+
+impl ConstraintSynthesizer<Fr> for ScoreThresholdCircuit {
+    fn generate_constraints(self, cs: ConstraintSystemRef<Fr>) -> Result<(), SynthesisError> {
+        if self.siblings.len() != DISCLOSURE_TREE_DEPTH as usize {
+            return Err(SynthesisError::Unsatisfiable)
+        }
+
+        // Allocated in `disclosure::public_inputs` order.
+        let [root, threshold, min_reviews, challenge] =
+            chain::public_inputs(&self.root, self.threshold, self.min_reviews, &self.challenge);
+        let root = public_input(&cs, &root)?;
+        let threshold = public_input(&cs, &threshold)?;
+        let min_reviews = public_input(&cs, &min_reviews)?;
+        let challenge = public_input(&cs, &challenge)?;
+        // Ties the challenge into the proof.
+        challenge.square()?;
+
+        let secret = witness_bits(&cs, &self.secret)?;
+        let key = hash_pair(&secret, &[Boolean::FALSE; 256])?;
+
+        let total_score = UInt32::new_witness(cs.clone(), || Ok(self.total_score))?.to_bits_le();
+        let review_count = UInt32::new_witness(cs.clone(), || Ok(self.review_count))?.to_bits_le();
+        let aggregates: Vec<_> =
+            total_score.iter().chain(&review_count).cloned().chain([Boolean::FALSE; 192]).collect();
+
+        let mut node = hash_pair(&key, &aggregates)?;
+        for (level, sibling) in self.siblings.iter().enumerate() {
+            let sibling = witness_bits(&cs, sibling)?;
+            let is_right = Boolean::new_witness(cs.clone(), || Ok(self.index >> level & 1 == 1))?;
+            let mut left = Vec::with_capacity(256);
+            let mut right = Vec::with_capacity(256);
+            for (node_bit, sibling_bit) in node.iter().zip(&sibling) {
+                left.push(is_right.select(sibling_bit, node_bit)?);
+                right.push(is_right.select(node_bit, sibling_bit)?);
+            }
+            node = hash_pair(&left, &right)?;
+        }
+        Boolean::le_bits_to_fp_var(&node)?.enforce_equal(&root)?;
+
+        // total_score / review_count >= threshold / 100, without division.
+        let total_score = Boolean::le_bits_to_fp_var(&total_score)?;
+        let review_count = Boolean::le_bits_to_fp_var(&review_count)?;
+        (total_score * Fr::from(100u64)).enforce_cmp(&(threshold * &review_count), Ordering::Greater, true)?;
+        review_count.enforce_cmp(&min_reviews, Ordering::Greater, true)?;
+
+        Ok(())
+    }
+}
+
+/// Generate keys for the circuit. The verifying key comes back compressed, ready for
+/// `set_disclosure_verifying_key`.
+pub fn setup<R: RngCore + CryptoRng>(rng: &mut R) -> Result<(ProvingKey<Bn254>, Vec<u8>), Error> {
+    let (proving_key, verifying_key) =
+        Groth16::<Bn254>::circuit_specific_setup(ScoreThresholdCircuit::blank(), rng)?;
+    let mut encoded = Vec::new();
+    verifying_key.serialize_compressed(&mut encoded)?;
+    Ok((proving_key, encoded))
+}
+
+/// Prove `witness` meets `threshold` hundredths over at least `min_reviews` reviews, for the
+/// verifier's `challenge`. The proof is compressed, as the runtime expects.
+pub fn prove<R: RngCore + CryptoRng>(
+    proving_key: &ProvingKey<Bn254>,
+    secret: &[u8; 32],
+    witness: &DisclosureWitness,
+    threshold: u32,
+    min_reviews: u32,
+    challenge: &[u8; 32],
+    rng: &mut R,
+) -> Result<Vec<u8>, Error> {
+    if witness.siblings.len() != DISCLOSURE_TREE_DEPTH as usize {
+        return Err(Error::MalformedWitness)
+    }
+    let meets_threshold =
+        witness.total_score as u64 * 100 >= threshold as u64 * witness.review_count as u64;
+    if !meets_threshold || witness.review_count < min_reviews {
+        return Err(Error::ThresholdNotMet)
+    }
+
+    let circuit = ScoreThresholdCircuit {
+        secret: *secret,
+        index: witness.index,
+        total_score: witness.total_score,
+        review_count: witness.review_count,
+        siblings: witness.siblings.clone(),
+        root: witness.root,
+        threshold,
+        min_reviews,
+        challenge: *challenge,
+    };
+    let proof = Groth16::<Bn254>::prove(proving_key, circuit, rng)?;
+
+    let mut encoded = Vec::new();
+    proof.serialize_compressed(&mut encoded)?;
+    Ok(encoded)
+}
+
+/// Check a proof off chain against an epoch root read from the chain.
+pub fn verify(
+    verifying_key: &[u8],
+    root: &[u8; 32],
+    threshold: u32,
+    min_reviews: u32,
+    challenge: &[u8; 32],
+    proof: &[u8],
+) -> bool {
+    chain::verify_score_proof_against(verifying_key, root, threshold, min_reviews, challenge, proof)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_relations::r1cs::ConstraintSystem;
+    use ark_std::rand::{rngs::StdRng, SeedableRng};
+    use std::sync::OnceLock;
+
+    const SECRET: [u8; 32] = [7u8; 32];
+    const CHALLENGE: [u8; 32] = [9u8; 32];
+
+    fn rng() -> StdRng {
+        StdRng::seed_from_u64(42)
+    }
+
+    /// Keys are slow to generate, so every test shares one pair.
+    fn keys() -> &'static (ProvingKey<Bn254>, Vec<u8>) {
+        static KEYS: OnceLock<(ProvingKey<Bn254>, Vec<u8>)> = OnceLock::new();
+        KEYS.get_or_init(|| setup(&mut rng()).unwrap())
+    }
+
+    /// Witness for `secret`'s leaf at `index` in an otherwise empty tree, built with the
+    /// runtime's hashing.
+    fn witness(secret: &[u8; 32], index: u32, total_score: u32, review_count: u32) -> DisclosureWitness {
+        let zeros = chain::zero_hashes();
+        let siblings: Vec<_> = zeros[..DISCLOSURE_TREE_DEPTH as usize].to_vec();
+        let mut node = chain::leaf(&disclosure_key(secret), total_score, review_count);
+        for (level, sibling) in siblings.iter().enumerate() {
+            node = if index >> level & 1 == 1 {
+                chain::hash_pair(sibling, &node)
+            } else {
+                chain::hash_pair(&node, sibling)
+            };
+        }
+        DisclosureWitness { index, total_score, review_count, siblings, root: node }
+    }
+
+    fn circuit(
+        secret: &[u8; 32],
+        witness: &DisclosureWitness,
+        threshold: u32,
+        min_reviews: u32,
+    ) -> ScoreThresholdCircuit {
+        ScoreThresholdCircuit {
+            secret: *secret,
+            index: witness.index,
+            total_score: witness.total_score,
+            review_count: witness.review_count,
+            siblings: witness.siblings.clone(),
+            root: witness.root,
+            threshold,
+            min_reviews,
+            challenge: CHALLENGE,
+        }
+    }
+
+    fn is_satisfied(circuit: ScoreThresholdCircuit) -> bool {
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        circuit.generate_constraints(cs.clone()).unwrap();
+        cs.is_satisfied().unwrap()
+    }
+
+    fn bits_to_bytes(bits: &[Boolean<Fr>]) -> [u8; 32] {
+        let mut bytes = [0u8; 32];
+        for (i, bit) in bits.iter().enumerate() {
+            bytes[i / 8] |= (bit.value().unwrap() as u8) << (i % 8);
+        }
+        bytes
+    }
+
+    #[test]
+    fn circuit_hash_matches_runtime_hash() {
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        let (left, right) = ([0xa5u8; 32], [0xffu8; 32]);
+        let digest = hash_pair(&witness_bits(&cs, &left).unwrap(), &witness_bits(&cs, &right).unwrap()).unwrap();
+
+        assert_eq!(bits_to_bytes(&digest), chain::hash_pair(&left, &right));
+        assert!(cs.is_satisfied().unwrap());
+    }
+
+    #[test]
+    fn circuit_accepts_runtime_tree() {
+        let witness = witness(&SECRET, 5, 45, 10);
+        assert!(is_satisfied(circuit(&SECRET, &witness, 450, 10)));
+    }
+
+    #[test]
+    fn circuit_rejects_wrong_witness() {
+        let witness = witness(&SECRET, 5, 45, 10);
+
+        assert!(!is_satisfied(circuit(&[8u8; 32], &witness, 450, 10)));
+        assert!(!is_satisfied(ScoreThresholdCircuit { index: 4, ..circuit(&SECRET, &witness, 450, 10) }));
+        assert!(!is_satisfied(ScoreThresholdCircuit { total_score: 50, ..circuit(&SECRET, &witness, 450, 10) }));
+        assert!(!is_satisfied(circuit(&SECRET, &witness, 451, 10)));
+        assert!(!is_satisfied(circuit(&SECRET, &witness, 450, 11)));
+    }
+
+    #[test]
+    fn prove_verify_round_trip() {
+        let (proving_key, verifying_key) = keys();
+        let witness = witness(&SECRET, 3, 47, 10);
+
+        let proof = prove(proving_key, &SECRET, &witness, 450, 10, &CHALLENGE, &mut rng()).unwrap();
+
+        assert!(verify(verifying_key, &witness.root, 450, 10, &CHALLENGE, &proof));
+        assert!(!verify(verifying_key, &witness.root, 450, 10, &[1u8; 32], &proof));
+        assert!(!verify(verifying_key, &witness.root, 460, 10, &CHALLENGE, &proof));
+        assert!(!verify(verifying_key, &[0u8; 32], 450, 10, &CHALLENGE, &proof));
+    }
+
+    #[test]
+    fn prove_rejects_wrong_witness() {
+        let (proving_key, verifying_key) = keys();
+        let witness = witness(&SECRET, 3, 47, 10);
+
+        assert!(matches!(
+            prove(proving_key, &SECRET, &witness, 480, 10, &CHALLENGE, &mut rng()),
+            Err(Error::ThresholdNotMet)
+        ));
+        assert!(matches!(
+            prove(proving_key, &SECRET, &witness, 450, 11, &CHALLENGE, &mut rng()),
+            Err(Error::ThresholdNotMet)
+        ));
+        let short = DisclosureWitness { siblings: witness.siblings[1..].to_vec(), ..witness.clone() };
+        assert!(matches!(
+            prove(proving_key, &SECRET, &short, 450, 10, &CHALLENGE, &mut rng()),
+            Err(Error::MalformedWitness)
+        ));
+
+        // A proof only speaks for the tree it was made against.
+        let proof = prove(proving_key, &SECRET, &witness, 450, 10, &CHALLENGE, &mut rng()).unwrap();
+        let other = self::witness(&[8u8; 32], 3, 47, 10);
+        assert!(!verify(verifying_key, &other.root, 450, 10, &CHALLENGE, &proof));
+    }
+}
@@ -166,6 +166,9 @@ export default function Dashboard({ userAddress }: DashboardProps) {
                     <p className="text-3xl font-bold text-gray-900 mt-2">
                       {stats.stakedAmount}
                     </p>
+                    <p className="text-gray-500 text-sm mt-1">
+                      Backed by {stats.backedAmount}
+                    </p>
                   </div>
                   <TrendingUp className="w-12 h-12 text-purple-400" />
                 </div>
@@ -1,5 +1,9 @@
 import { ApiPromise, WsProvider } from '@polkadot/api';
 import { web3Accounts, web3Enable, web3FromAddress } from '@polkadot/extension-dapp';
+import { hexToU8a, u8aToHex } from '@polkadot/util';
+import { blake2AsHex } from '@polkadot/util-crypto';
+
+export type ProfileStatus = 'Active' | 'Paused' | 'UnderReview' | 'Suspended' | 'Banned';
 
 export interface ReputationProfile {
   totalScore: number;
@@ -10,6 +14,7 @@ export interface ReputationProfile {
     quality: number;
     professionalism: number;
   };
+  status: ProfileStatus;
   active: boolean;
   stakedAmount: string;
   owner: string;
@@ -27,8 +32,15 @@ export interface Rating {
   };
   timestamp: number;
   reviewHash: string;
+  tip: string;
 }
 
+// A `reviewHash` is a `ContentRef`: the binary multihash or CIDv1 of the review, hex encoded.
+// Raw digests need the multihash prefix of the function that made them.
+export const sha256ContentRef = (digest: string) => '0x1220' + digest.replace(/^0x/, '');
+export const blake2ContentRef = (digest: string) => '0xa0e40220' + digest.replace(/^0x/, '');
+export const NO_REVIEW = sha256ContentRef('0'.repeat(64));
+
 export interface UserStats {
   address: string;
   averageScore: number;
@@ -40,6 +52,7 @@ export interface UserStats {
     professionalism: number;
   };
   stakedAmount: string;
+  backedAmount: string;
   isActive: boolean;
 }
 
@@ -90,7 +103,7 @@ export async function submitRating(
   reliability: number,
   quality: number,
   professionalism: number,
-  reviewHash: string = '0x' + '0'.repeat(64)
+  reviewHash: string = NO_REVIEW
 ) {
   const api = await getApi();
   const injector = await web3FromAddress(fromAddress);
@@ -109,6 +122,41 @@ export async function submitRating(
   return hash;
 }
 
+export async function submitRatingWithTip(
+  fromAddress: string,
+  toAddress: string,
+  score: number,
+  communication: number,
+  reliability: number,
+  quality: number,
+  professionalism: number,
+  tip: string,
+  reviewHash: string = NO_REVIEW
+) {
+  const api = await getApi();
+  const injector = await web3FromAddress(fromAddress);
+
+  const tx = api.tx.reputation.submitRatingWithTip(
+    toAddress,
+    score,
+    communication,
+    reliability,
+    quality,
+    professionalism,
+    reviewHash,
+    tip
+  );
+
+  const hash = await tx.signAndSend(fromAddress, { signer: injector.signer });
+  return hash;
+}
+
+export async function getTipsReceived(address: string): Promise<string> {
+  const api = await getApi();
+  const tips = await api.query.reputation.tipsReceived(address);
+  return tips.toString();
+}
+
 export async function stakeReputation(address: string, amount: string) {
   const api = await getApi();
   const injector = await web3FromAddress(address);
@@ -125,11 +173,13 @@ export async function getUserReputation(address: string): Promise<ReputationProf
   
   if (!profile.isEmpty) {
     const data = profile.toJSON() as any;
+    const status = (profile as any).unwrap().status.type as ProfileStatus;
     return {
       totalScore: data.totalScore,
       reviewCount: data.reviewCount,
       categoryScores: data.categoryScores,
-      active: data.active,
+      status,
+      active: status === 'Active',
       stakedAmount: data.stakedAmount.toString(),
       owner: data.owner,
     };
@@ -138,6 +188,12 @@ export async function getUserReputation(address: string): Promise<ReputationProf
   return null;
 }
 
+export async function getBackedAmount(address: string): Promise<string> {
+  const api = await getApi();
+  const backed = await api.query.reputation.backedBy(address);
+  return backed.toString();
+}
+
 export async function getUserStats(address: string): Promise<UserStats> {
   const profile = await getUserReputation(address);
   
@@ -153,6 +209,7 @@ export async function getUserStats(address: string): Promise<UserStats> {
         professionalism: 0,
       },
       stakedAmount: '0',
+      backedAmount: '0',
       isActive: false,
     };
   }
@@ -171,6 +228,7 @@ export async function getUserStats(address: string): Promise<UserStats> {
     totalReviews: profile.reviewCount,
     categoryAverages,
     stakedAmount: profile.stakedAmount,
+    backedAmount: await getBackedAmount(address),
     isActive: profile.active,
   };
 }
@@ -190,3 +248,225 @@ export async function deactivateProfile(address: string) {
   
   return hash;
 }
+
+export async function reactivateProfile(address: string) {
+  const api = await getApi();
+  const injector = await web3FromAddress(address);
+
+  const tx = api.tx.reputation.reactivateProfile();
+  const hash = await tx.signAndSend(address, { signer: injector.signer });
+
+  return hash;
+}
+
+export async function vouch(fromAddress: string, targetAddress: string, amount: string) {
+  const api = await getApi();
+  const injector = await web3FromAddress(fromAddress);
+
+  const tx = api.tx.reputation.vouch(targetAddress, amount);
+  const hash = await tx.signAndSend(fromAddress, { signer: injector.signer });
+
+  return hash;
+}
+
+export async function unvouch(fromAddress: string, targetAddress: string, amount: string) {
+  const api = await getApi();
+  const injector = await web3FromAddress(fromAddress);
+
+  const tx = api.tx.reputation.unvouch(targetAddress, amount);
+  const hash = await tx.signAndSend(fromAddress, { signer: injector.signer });
+
+  return hash;
+}
+
+export async function voteRating(voterAddress: string, to: string, from: string, helpful: boolean) {
+  const api = await getApi();
+  const injector = await web3FromAddress(voterAddress);
+
+  const tx = helpful
+    ? api.tx.reputation.upvoteRating(to, from)
+    : api.tx.reputation.downvoteRating(to, from);
+  const hash = await tx.signAndSend(voterAddress, { signer: injector.signer });
+
+  return hash;
+}
+
+export async function getHelpfulness(to: string, from: string): Promise<number> {
+  const api = await getApi();
+  const net = await api.query.reputation.helpfulness(to, from);
+  return Number(net.toString());
+}
+
+export type FlagReason = 'Spam' | 'Abusive' | 'Fraudulent' | 'ConflictOfInterest' | 'Other';
+
+export async function flagRating(flaggerAddress: string, to: string, from: string, reason: FlagReason) {
+  const api = await getApi();
+  const injector = await web3FromAddress(flaggerAddress);
+
+  const tx = api.tx.reputation.flagRating(to, from, reason);
+  const hash = await tx.signAndSend(flaggerAddress, { signer: injector.signer });
+
+  return hash;
+}
+
+export async function isRatingHidden(to: string, from: string): Promise<boolean> {
+  const api = await getApi();
+  const caseId = await api.query.reputation.hiddenRatings(to, from);
+  return (caseId as any).isSome;
+}
+
+export async function getReputationParams() {
+  const api = await getApi();
+  const params = await api.query.reputation.reputationParams();
+  return params.toJSON();
+}
+
+export async function appealModeration(address: string, caseId: number, evidenceHash: string) {
+  const api = await getApi();
+  const injector = await web3FromAddress(address);
+
+  const tx = api.tx.reputation.appeal(caseId, evidenceHash);
+  const hash = await tx.signAndSend(address, { signer: injector.signer });
+
+  return hash;
+}
+
+export interface SealedRating {
+  score: number;
+  communication: number;
+  reliability: number;
+  quality: number;
+  professionalism: number;
+  reviewHash: string;
+  salt: string;
+}
+
+// Matches `T::Hashing::hash_of(&(score, [communication, reliability, quality, professionalism], review_hash, salt))`.
+export async function ratingCommitment(r: SealedRating): Promise<string> {
+  const api = await getApi();
+  const preimage = api.createType('(u8, [u8; 4], Bytes, [u8; 32])', [
+    r.score,
+    [r.communication, r.reliability, r.quality, r.professionalism],
+    r.reviewHash,
+    r.salt,
+  ]);
+  return blake2AsHex(preimage.toU8a());
+}
+
+export async function commitRating(fromAddress: string, targetAddress: string, rating: SealedRating) {
+  const api = await getApi();
+  const injector = await web3FromAddress(fromAddress);
+
+  const tx = api.tx.reputation.commitRating(targetAddress, await ratingCommitment(rating));
+  const hash = await tx.signAndSend(fromAddress, { signer: injector.signer });
+
+  return hash;
+}
+
+export async function revealRating(fromAddress: string, targetAddress: string, r: SealedRating) {
+  const api = await getApi();
+  const injector = await web3FromAddress(fromAddress);
+
+  const tx = api.tx.reputation.revealRating(
+    targetAddress,
+    r.score,
+    r.communication,
+    r.reliability,
+    r.quality,
+    r.professionalism,
+    r.reviewHash,
+    r.salt,
+  );
+  const hash = await tx.signAndSend(fromAddress, { signer: injector.signer });
+
+  return hash;
+}
+
+// Reserves the session deposit from `address` until the session is published.
+export async function openSession(address: string, counterparty: string) {
+  const api = await getApi();
+  const injector = await web3FromAddress(address);
+
+  const tx = api.tx.reputation.openSession(counterparty);
+  const hash = await tx.signAndSend(address, { signer: injector.signer });
+
+  return hash;
+}
+
+export async function submitSessionRating(
+  address: string,
+  sessionId: number,
+  score: number,
+  categories: { communication: number; reliability: number; quality: number; professionalism: number },
+  reviewHash: string,
+) {
+  const api = await getApi();
+  const injector = await web3FromAddress(address);
+
+  const tx = api.tx.reputation.submitSessionRating(
+    sessionId,
+    score,
+    categories.communication,
+    categories.reliability,
+    categories.quality,
+    categories.professionalism,
+    reviewHash,
+  );
+  const hash = await tx.signAndSend(address, { signer: injector.signer });
+
+  return hash;
+}
+
+// Stores a review body on chain; rate with the returned reference as `reviewHash`.
+export async function storeReviewContent(address: string, text: string) {
+  const api = await getApi();
+  const injector = await web3FromAddress(address);
+
+  const body = new TextEncoder().encode(text);
+  const tx = api.tx.reputation.storeReviewContent(body);
+  await tx.signAndSend(address, { signer: injector.signer });
+
+  return blake2ContentRef(blake2AsHex(body));
+}
+
+// Stores a review body on the connected node instead of on chain; rate with the returned
+// reference as `reviewHash`. Nodes evict unpinned bodies when their store fills up.
+// Needs a node serving unsafe RPC methods, e.g. one listening on localhost.
+export async function putNodeReview(text: string) {
+  const api = await getApi();
+  const hash: string = await api.provider.send('reviews_put', [u8aToHex(new TextEncoder().encode(text))]);
+  return blake2ContentRef(hash);
+}
+
+// `null` if the connected node does not hold the body.
+export async function getNodeReview(reviewHash: string): Promise<string | null> {
+  const prefix = blake2ContentRef('');
+  if (!reviewHash.startsWith(prefix)) {
+    return null;
+  }
+  const api = await getApi();
+  const body: string | null = await api.provider.send('reviews_get', ['0x' + reviewHash.slice(prefix.length)]);
+  return body === null ? null : new TextDecoder().decode(hexToU8a(body));
+}
+
+// Ratings submitted in the last `days` days across all profiles, newest first, with a null
+// `from` for anonymous ones. Needs a node run with offchain indexing enabled.
+export async function getRecentRatings(days: number, limit = 100) {
+  const api = await getApi();
+  const until = Date.now();
+  return api.provider.send('ratings_inRange', [until - days * 24 * 60 * 60 * 1000, until, limit]);
+}
+
+// `null` if the body was never stored on chain or has been forgotten.
+export async function getReviewContent(reviewHash: string): Promise<string | null> {
+  const prefix = blake2ContentRef('');
+  if (!reviewHash.startsWith(prefix)) {
+    return null;
+  }
+  const api = await getApi();
+  const content = (await api.query.reputation.reviewContent('0x' + reviewHash.slice(prefix.length))) as any;
+  if (content.isNone || content.unwrap().body.isNone) {
+    return null;
+  }
+  return new TextDecoder().decode(content.unwrap().body.unwrap().toU8a(true));
+}
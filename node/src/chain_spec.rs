@@ -0,0 +1,199 @@
+use sp_core::{sr25519, Get, Pair, Public};
+use sp_runtime::traits::{IdentifyAccount, Verify};
+use reputechain_runtime::{AccountId, Signature};
+
+/// Specialized `ChainSpec`. This is a specialization of the general Substrate ChainSpec type.
+pub type ChainSpec = sc_service::GenericChainSpec<reputechain_runtime::GenesisConfig>;
+
+/// Generate a crypto pair from seed.
+pub fn get_from_seed<TPublic: Public>(seed: &str) -> <TPublic::Pair as Pair>::Public {
+	TPublic::Pair::from_string(&format!("//{}", seed), None)
+		.expect("static values are valid; qed")
+		.public()
+}
+
+type AccountPublic = <Signature as Verify>::Signer;
+
+/// Generate an account ID from seed.
+pub fn get_account_id_from_seed<TPublic: Public>(seed: &str) -> AccountId
+where
+	AccountPublic: From<<TPublic::Pair as Pair>::Public>,
+{
+	AccountPublic::from(get_from_seed::<TPublic>(seed)).into_account()
+}
+
+/// Generate an Aura authority key.
+pub fn authority_keys_from_seed(s: &str) -> (sp_consensus_aura::sr25519::AuthorityId, sp_consensus_grandpa::AuthorityId) {
+	(
+		get_from_seed::<sp_consensus_aura::sr25519::AuthorityId>(s),
+		get_from_seed::<sp_consensus_grandpa::AuthorityId>(s),
+	)
+}
+
+/// A small reputation graph between Alice and Ferdie so demos don't start from zero.
+pub fn demo_reputation() -> reputechain_runtime::ReputationConfig {
+	let [alice, bob, charlie, dave, eve, ferdie] =
+		["Alice", "Bob", "Charlie", "Dave", "Eve", "Ferdie"]
+			.map(get_account_id_from_seed::<sr25519::Public>);
+	let params = reputechain_runtime::DefaultReputationParams::get();
+	let token = params.min_stake_amount;
+
+	reputechain_runtime::ReputationConfig {
+		params,
+		profiles: vec![
+			alice.clone(),
+			bob.clone(),
+			charlie.clone(),
+			dave.clone(),
+			eve.clone(),
+			ferdie.clone(),
+		],
+		stakes: vec![(alice.clone(), 10 * token), (bob.clone(), 5 * token), (charlie.clone(), token)],
+		ratings: vec![
+			(bob.clone(), alice.clone(), 5, [5, 5, 5, 4]),
+			(charlie.clone(), alice.clone(), 4, [4, 5, 4, 4]),
+			(dave.clone(), alice.clone(), 5, [5, 4, 5, 5]),
+			(alice.clone(), bob.clone(), 4, [4, 4, 3, 4]),
+			(eve.clone(), bob.clone(), 3, [3, 2, 4, 3]),
+			(alice.clone(), charlie.clone(), 5, [5, 5, 5, 5]),
+			(ferdie.clone(), dave.clone(), 2, [2, 1, 3, 2]),
+			(bob.clone(), eve.clone(), 4, [4, 4, 4, 5]),
+			(charlie.clone(), ferdie.clone(), 1, [1, 1, 2, 1]),
+		],
+		content_reporters: vec![alice],
+	}
+}
+
+pub fn development_config() -> Result<ChainSpec, String> {
+	let wasm_binary = reputechain_runtime::WASM_BINARY
+		.ok_or_else(|| "Development wasm not available".to_string())?;
+
+	Ok(ChainSpec::from_genesis(
+		// Name
+		"Development",
+		// ID
+		"dev",
+		sc_service::ChainType::Development,
+		move || {
+			testnet_genesis(
+				wasm_binary,
+				// Initial PoA authorities
+				vec![authority_keys_from_seed("Alice")],
+				// Sudo account
+				get_account_id_from_seed::<sr25519::Public>("Alice"),
+				// Pre-funded accounts
+				vec![
+					get_account_id_from_seed::<sr25519::Public>("Alice"),
+					get_account_id_from_seed::<sr25519::Public>("Bob"),
+					get_account_id_from_seed::<sr25519::Public>("Alice//stash"),
+					get_account_id_from_seed::<sr25519::Public>("Bob//stash"),
+				],
+				demo_reputation(),
+				true,
+			)
+		},
+		// Bootnodes
+		vec![],
+		// Telemetry
+		None,
+		// Protocol ID
+		None,
+		None,
+		// Properties
+		None,
+		// Extensions
+		None,
+	))
+}
+
+pub fn local_testnet_config() -> Result<ChainSpec, String> {
+	let wasm_binary = reputechain_runtime::WASM_BINARY
+		.ok_or_else(|| "Development wasm not available".to_string())?;
+
+	Ok(ChainSpec::from_genesis(
+		// Name
+		"Local Testnet",
+		// ID
+		"local_testnet",
+		sc_service::ChainType::Local,
+		move || {
+			testnet_genesis(
+				wasm_binary,
+				// Initial PoA authorities
+				vec![authority_keys_from_seed("Alice"), authority_keys_from_seed("Bob")],
+				// Sudo account
+				get_account_id_from_seed::<sr25519::Public>("Alice"),
+				// Pre-funded accounts
+				vec![
+					get_account_id_from_seed::<sr25519::Public>("Alice"),
+					get_account_id_from_seed::<sr25519::Public>("Bob"),
+					get_account_id_from_seed::<sr25519::Public>("Charlie"),
+					get_account_id_from_seed::<sr25519::Public>("Dave"),
+					get_account_id_from_seed::<sr25519::Public>("Eve"),
+					get_account_id_from_seed::<sr25519::Public>("Ferdie"),
+					get_account_id_from_seed::<sr25519::Public>("Alice//stash"),
+					get_account_id_from_seed::<sr25519::Public>("Bob//stash"),
+					get_account_id_from_seed::<sr25519::Public>("Charlie//stash"),
+					get_account_id_from_seed::<sr25519::Public>("Dave//stash"),
+					get_account_id_from_seed::<sr25519::Public>("Eve//stash"),
+					get_account_id_from_seed::<sr25519::Public>("Ferdie//stash"),
+				],
+				demo_reputation(),
+				true,
+			)
+		},
+		// Bootnodes
+		vec![],
+		// Telemetry
+		None,
+		// Protocol ID
+		None,
+		None,
+		// Properties
+		None,
+		// Extensions
+		None,
+	))
+}
+
+/// Configure initial storage state for FRAME modules.
+fn testnet_genesis(
+	wasm_binary: &[u8],
+	initial_authorities: Vec<(sp_consensus_aura::sr25519::AuthorityId, sp_consensus_grandpa::AuthorityId)>,
+	root_key: AccountId,
+	endowed_accounts: Vec<AccountId>,
+	reputation: reputechain_runtime::ReputationConfig,
+	_enable_println: bool,
+) -> reputechain_runtime::GenesisConfig {
+	reputechain_runtime::GenesisConfig {
+		system: reputechain_runtime::SystemConfig {
+			// Add Wasm runtime to storage.
+			code: wasm_binary.to_vec(),
+		},
+		balances: reputechain_runtime::BalancesConfig {
+			// Configure endowed accounts with initial balance of 1 << 60.
+			balances: endowed_accounts.iter().cloned().map(|k| (k, 1 << 60)).collect(),
+		},
+		aura: reputechain_runtime::AuraConfig {
+			authorities: initial_authorities.iter().map(|x| (x.0.clone())).collect(),
+		},
+		grandpa: reputechain_runtime::GrandpaConfig {
+			authorities: initial_authorities.iter().map(|x| (x.1.clone(), 1)).collect(),
+		},
+		democracy: Default::default(),
+		council: reputechain_runtime::CouncilConfig {
+			members: ["Alice", "Bob", "Charlie"].map(get_account_id_from_seed::<sr25519::Public>).to_vec(),
+			phantom: Default::default(),
+		},
+		technical_committee: reputechain_runtime::TechnicalCommitteeConfig {
+			members: ["Alice", "Bob"].map(get_account_id_from_seed::<sr25519::Public>).to_vec(),
+			phantom: Default::default(),
+		},
+		sudo: reputechain_runtime::SudoConfig {
+			// Assign network admin rights until governance takes over, see `SudoSunset`.
+			key: Some(root_key),
+		},
+		transaction_payment: Default::default(),
+		reputation,
+	}
+}
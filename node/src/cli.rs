@@ -0,0 +1,83 @@
+/// Parse command line arguments into service configuration.
+#[derive(Debug, clap::Parser)]
+pub struct Cli {
+	#[command(subcommand)]
+	pub subcommand: Option<Subcommand>,
+
+	#[clap(flatten)]
+	pub run: sc_cli::RunCmd,
+
+	/// Replace Aura/GRANDPA with manual seal. Intended for dev chains only.
+	#[arg(long, value_enum, ignore_case = true)]
+	pub sealing: Option<Sealing>,
+
+	#[clap(flatten)]
+	pub reviews: ReviewStoreParams,
+}
+
+/// Limits of the review content store served by `reviews_put` and `reviews_get`.
+#[derive(Debug, Clone, clap::Args)]
+pub struct ReviewStoreParams {
+	/// Largest review body accepted, in bytes.
+	#[arg(long, default_value_t = 64 * 1024)]
+	pub review_max_size: usize,
+
+	/// Space the review store may use, in MiB. Past it the oldest unpinned bodies are evicted.
+	#[arg(long, default_value_t = 256)]
+	pub review_store_size: u64,
+
+	/// Pin the bodies that finalized ratings refer to, so they are never evicted.
+	#[arg(long)]
+	pub review_pin: bool,
+}
+
+/// How blocks are authored when `--sealing` is set.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, clap::ValueEnum)]
+pub enum Sealing {
+	/// Seal and finalize a block as soon as a transaction enters the pool.
+	Instant,
+	/// Seal blocks only when `engine_createBlock` is called.
+	Manual,
+}
+
+/// Possible subcommands of the main binary.
+#[derive(Debug, clap::Subcommand)]
+pub enum Subcommand {
+	/// Key management cli utilities
+	#[command(subcommand)]
+	Key(sc_cli::KeySubcommand),
+
+	/// Build a chain specification.
+	BuildSpec(sc_cli::BuildSpecCmd),
+
+	/// Validate blocks.
+	CheckBlock(sc_cli::CheckBlockCmd),
+
+	/// Export blocks.
+	ExportBlocks(sc_cli::ExportBlocksCmd),
+
+	/// Export the state of a given block into a chain spec.
+	ExportState(sc_cli::ExportStateCmd),
+
+	/// Import blocks.
+	ImportBlocks(sc_cli::ImportBlocksCmd),
+
+	/// Remove the whole chain.
+	PurgeChain(sc_cli::PurgeChainCmd),
+
+	/// Revert the chain to a previous state.
+	Revert(sc_cli::RevertCmd),
+
+	/// Sub-commands concerned with benchmarking.
+	#[command(subcommand)]
+	Benchmark(frame_benchmarking_cli::BenchmarkCmd),
+
+	/// Try some command against a specified runtime state.
+	#[cfg(feature = "try-runtime")]
+	TryRuntime(try_runtime_cli::TryRuntimeCmd),
+
+	/// Try some command against a specified runtime state. Note: `try-runtime` feature must be
+	/// enabled.
+	#[cfg(not(feature = "try-runtime"))]
+	TryRuntime,
+}
@@ -1,3 +1,14 @@
-fn main() {
-    println!("ReputeChain node - placeholder for now");
-}
\ No newline at end of file
+//! ReputeChain node CLI.
+#![warn(missing_docs)]
+
+mod chain_spec;
+mod cli;
+mod command;
+mod rating_index;
+mod reviews;
+mod rpc;
+mod service;
+
+fn main() -> sc_cli::Result<()> {
+	command::run()
+}
@@ -0,0 +1,536 @@
+//! Rating query tables built from the runtime's offchain index.
+//!
+//! With `--enable-offchain-indexing true` the runtime writes a record of every rating change to
+//! the offchain database (see `pallet_reputation::indexing`). This module follows the best chain,
+//! reads each block's records and keeps in-memory tables of visible ratings by time and by rater,
+//! served through the `ratings_*` RPC methods. Anonymous ratings are listed by time, without a
+//! rater.
+//!
+//! What every unfinalized block changed is kept until it is finalized, so a reorg reverts the
+//! retracted blocks before applying the enacted ones and the tables always match the best
+//! block. A block's records are found by walking back from the `RatingChangesHead` in its
+//! state, so reading a block needs its state; an index starting from scratch on a pruned node
+//! leaves out the blocks whose state is gone. Offchain indexing does nothing while genesis is
+//! built, so ratings set at genesis are read from the genesis state instead.
+//!
+//! The tables are checkpointed to the offchain database every `CHECKPOINT_INTERVAL` blocks and
+//! resumed from the checkpoint on start. Records are read without holding the lock queries
+//! take, and applied `SYNC_BATCH` blocks at a time.
+
+use std::{
+	collections::{BTreeMap, BTreeSet, HashMap},
+	sync::{Arc, RwLock},
+};
+
+use codec::{Decode, Encode};
+use frame_support::storage::StoragePrefixedMap;
+use futures::StreamExt;
+use jsonrpsee::{core::RpcResult, proc_macros::rpc};
+use pallet_reputation::indexing::{record_key, IndexEntry, Rater, RatingChange, RatingRecord};
+use reputechain_runtime::{AccountId, BlockNumber, Hash, Runtime};
+use sc_client_api::{Backend, BlockchainEvents, StorageKey, StorageProvider};
+use sc_service::{Configuration, TaskManager};
+use serde::{Deserialize, Serialize};
+use sp_blockchain::HeaderBackend;
+use sp_core::offchain::{OffchainStorage, STORAGE_PREFIX};
+use sp_runtime::traits::Header as _;
+
+use crate::service::{FullBackend, FullClient};
+
+const LOG_TARGET: &str = "rating-index";
+
+/// Results returned when the caller sets no limit.
+const DEFAULT_LIMIT: u32 = 100;
+/// Most results a single query returns.
+const MAX_LIMIT: u32 = 1_000;
+
+/// Blocks between checkpoints. Well under the default state pruning window, so the blocks since
+/// the last checkpoint can still be read on restart.
+const CHECKPOINT_INTERVAL: BlockNumber = 100;
+/// Offchain database key of the checkpoint.
+const CHECKPOINT_KEY: &[u8] = b"rating-index::checkpoint";
+/// Blocks read before the tables are locked to apply them.
+const SYNC_BATCH: usize = 256;
+
+/// A visible rating as of the best block.
+#[derive(Clone, Debug, Serialize, Encode, Decode)]
+#[serde(rename_all = "camelCase")]
+pub struct IndexedRating {
+	pub to: AccountId,
+	/// `None` for anonymous ratings.
+	pub from: Option<AccountId>,
+	pub score: u8,
+	pub communication: u32,
+	pub reliability: u32,
+	pub quality: u32,
+	pub professionalism: u32,
+	/// Time of the block the rating was submitted in, in milliseconds since the Unix epoch.
+	pub submitted_at: u64,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Category {
+	Communication,
+	Reliability,
+	Quality,
+	Professionalism,
+}
+
+impl Category {
+	fn score(self, rating: &IndexedRating) -> u32 {
+		match self {
+			Category::Communication => rating.communication,
+			Category::Reliability => rating.reliability,
+			Category::Quality => rating.quality,
+			Category::Professionalism => rating.professionalism,
+		}
+	}
+}
+
+#[derive(Clone, Encode, Decode)]
+struct Entry {
+	rating: IndexedRating,
+	hidden: bool,
+}
+
+type RatingKey = (AccountId, Rater<AccountId>);
+
+#[derive(Default)]
+struct Tables {
+	/// Every indexed rating by `(to, from)`, hidden ones included.
+	ratings: HashMap<RatingKey, Entry>,
+	/// Visible ratings by `(submitted_at, to, from)`.
+	by_time: BTreeSet<(u64, AccountId, Rater<AccountId>)>,
+	/// Visible ratings by rater, then `(submitted_at, to)`.
+	by_rater: HashMap<AccountId, BTreeSet<(u64, AccountId)>>,
+	/// What each unfinalized block replaced, to revert it on a reorg.
+	undo: BTreeMap<(BlockNumber, Hash), Vec<(RatingKey, Option<Entry>)>>,
+	/// Last block applied; `None` before the first sync.
+	head: Option<Hash>,
+}
+
+/// What a checkpoint keeps of [`Tables`]; the lookup tables are rebuilt from `ratings`.
+#[derive(Encode, Decode)]
+struct Checkpoint {
+	head: Hash,
+	number: BlockNumber,
+	ratings: Vec<(RatingKey, Entry)>,
+	undo: BTreeMap<(BlockNumber, Hash), Vec<(RatingKey, Option<Entry>)>>,
+}
+
+impl Tables {
+	/// Replace the entry under `key`, keeping the lookup tables in step. Returns the old entry.
+	fn set(&mut self, key: &RatingKey, entry: Option<Entry>) -> Option<Entry> {
+		let (to, from) = key;
+		let old = match &entry {
+			Some(entry) => self.ratings.insert(key.clone(), entry.clone()),
+			None => self.ratings.remove(key),
+		};
+
+		if let Some(old) = old.as_ref().filter(|old| !old.hidden) {
+			let at = old.rating.submitted_at;
+			self.by_time.remove(&(at, to.clone(), from.clone()));
+			if let Rater::Account(from) = from {
+				if let Some(rated) = self.by_rater.get_mut(from) {
+					rated.remove(&(at, to.clone()));
+					if rated.is_empty() {
+						self.by_rater.remove(from);
+					}
+				}
+			}
+		}
+		if let Some(new) = entry.filter(|new| !new.hidden) {
+			let at = new.rating.submitted_at;
+			self.by_time.insert((at, to.clone(), from.clone()));
+			if let Rater::Account(from) = from {
+				self.by_rater.entry(from.clone()).or_default().insert((at, to.clone()));
+			}
+		}
+
+		old
+	}
+
+	fn apply(&mut self, number: BlockNumber, hash: Hash, records: Vec<RatingRecord<AccountId>>) {
+		let mut undo = Vec::with_capacity(records.len());
+		for RatingRecord { to, from, change, at } in records {
+			let key = (to, from);
+			let entry = match change {
+				RatingChange::Submitted { score, category_ratings } => Some(Entry {
+					rating: IndexedRating {
+						to: key.0.clone(),
+						from: match &key.1 {
+							Rater::Account(from) => Some(from.clone()),
+							Rater::Anonymous(_) => None,
+						},
+						score,
+						communication: category_ratings.communication,
+						reliability: category_ratings.reliability,
+						quality: category_ratings.quality,
+						professionalism: category_ratings.professionalism,
+						submitted_at: at,
+					},
+					hidden: false,
+				}),
+				RatingChange::Hidden | RatingChange::Unhidden => {
+					let Some(entry) = self.ratings.get(&key) else { continue };
+					Some(Entry { hidden: matches!(change, RatingChange::Hidden), ..entry.clone() })
+				},
+			};
+			let old = self.set(&key, entry);
+			undo.push((key, old));
+		}
+		self.undo.insert((number, hash), undo);
+	}
+
+	fn revert(&mut self, number: BlockNumber, hash: Hash) {
+		let Some(undo) = self.undo.remove(&(number, hash)) else {
+			log::warn!(target: LOG_TARGET, "cannot revert #{} ({}): it is not in the undo log", number, hash);
+			return
+		};
+		for (key, old) in undo.into_iter().rev() {
+			self.set(&key, old);
+		}
+	}
+
+	/// Visible ratings submitted between `since` and `until`, newest first.
+	fn in_range(&self, since: u64, until: u64) -> impl Iterator<Item = &(u64, AccountId, Rater<AccountId>)> {
+		let (first, last) = (AccountId::new([0; 32]), AccountId::new([0xff; 32]));
+		// Account raters order before anonymous ones.
+		let (first_rater, last_rater) = (Rater::Account(first.clone()), Rater::Anonymous([0xff; 32]));
+		let range = (since <= until).then(|| {
+			self.by_time.range((since, first, first_rater)..=(until, last, last_rater)).rev()
+		});
+		range.into_iter().flatten()
+	}
+
+	fn checkpoint(&self, number: BlockNumber) -> Option<Checkpoint> {
+		Some(Checkpoint {
+			head: self.head?,
+			number,
+			ratings: self.ratings.iter().map(|(key, entry)| (key.clone(), entry.clone())).collect(),
+			undo: self.undo.clone(),
+		})
+	}
+
+	fn restore(checkpoint: Checkpoint) -> Self {
+		let mut tables = Tables { undo: checkpoint.undo, head: Some(checkpoint.head), ..Default::default() };
+		for (key, entry) in checkpoint.ratings {
+			tables.set(&key, Some(entry));
+		}
+		tables
+	}
+
+	/// Forget the undo log of blocks that can no longer be reverted.
+	fn prune(&mut self, finalized: BlockNumber) {
+		self.undo = self.undo.split_off(&(finalized.saturating_add(1), Hash::zero()));
+	}
+}
+
+/// Query tables following the best chain.
+pub struct RatingIndex {
+	tables: RwLock<Tables>,
+}
+
+impl RatingIndex {
+	/// Tables as of the last checkpoint, or the genesis ratings to build on.
+	fn resume(client: &FullClient, storage: &impl OffchainStorage) -> Self {
+		let checkpoint = storage
+			.get(STORAGE_PREFIX, CHECKPOINT_KEY)
+			.and_then(|checkpoint| Checkpoint::decode(&mut &checkpoint[..]).ok())
+			// A checkpoint of a block this node no longer has, e.g. after a database reset.
+			.filter(|checkpoint| matches!(client.header(checkpoint.head), Ok(Some(_))));
+
+		let tables = match checkpoint {
+			Some(checkpoint) => {
+				log::info!(target: LOG_TARGET, "resuming rating index from #{}", checkpoint.number);
+				Tables::restore(checkpoint)
+			},
+			None => {
+				log::info!(target: LOG_TARGET, "building rating index from genesis");
+				let genesis = client.info().genesis_hash;
+				let mut tables = Tables::default();
+				match read_ratings(client, genesis) {
+					Ok(records) => tables.apply(0, genesis, records),
+					Err(e) => log::warn!(target: LOG_TARGET, "cannot read the genesis ratings: {}", e),
+				}
+				tables.head = Some(genesis);
+				tables
+			},
+		};
+		Self { tables: RwLock::new(tables) }
+	}
+
+	/// Save the tables if `CHECKPOINT_INTERVAL` blocks went by since `last`. Returns the number
+	/// of the last checkpoint.
+	fn checkpoint(&self, client: &FullClient, storage: &mut impl OffchainStorage, last: BlockNumber) -> BlockNumber {
+		let tables = self.tables.read().expect("no sync panics while holding the lock; qed");
+		let Some(Ok(Some(number))) = tables.head.map(|head| client.number(head)) else { return last };
+		if number < last.saturating_add(CHECKPOINT_INTERVAL) {
+			return last
+		}
+		let Some(checkpoint) = tables.checkpoint(number) else { return last };
+		let encoded = checkpoint.encode();
+		drop(tables);
+
+		storage.set(STORAGE_PREFIX, CHECKPOINT_KEY, &encoded);
+		number
+	}
+
+	/// Bring the tables from their head to `best`, reverting whatever is no longer on its chain.
+	fn sync(
+		&self,
+		client: &FullClient,
+		storage: &impl OffchainStorage,
+		best: Hash,
+	) -> sp_blockchain::Result<()> {
+		// Only `follow` writes, so the head cannot move while the lock is released.
+		let head = self.tables.read().expect("no sync panics while holding the lock; qed").head;
+		let route = sp_blockchain::tree_route(client, head.unwrap_or_else(|| client.info().genesis_hash), best)?;
+
+		{
+			let mut tables = self.tables.write().expect("no sync panics while holding the lock; qed");
+			for block in route.retracted() {
+				tables.revert(block.number, block.hash);
+			}
+			tables.head = Some(route.common_block().hash);
+		}
+
+		for batch in route.enacted().chunks(SYNC_BATCH) {
+			let mut blocks = Vec::with_capacity(batch.len());
+			for block in batch {
+				let records = read_records(client, storage, block.hash).unwrap_or_else(|e| {
+					log::warn!(target: LOG_TARGET, "cannot read the records of #{} ({}): {}", block.number, block.hash, e);
+					Vec::new()
+				});
+				blocks.push((block.number, block.hash, records));
+			}
+
+			let mut tables = self.tables.write().expect("no sync panics while holding the lock; qed");
+			for (number, hash, records) in blocks {
+				tables.apply(number, hash, records);
+				tables.head = Some(hash);
+			}
+		}
+
+		self.tables.write().expect("no sync panics while holding the lock; qed").prune(client.info().finalized_number);
+		Ok(())
+	}
+
+	fn query(
+		&self,
+		limit: Option<u32>,
+		select: impl FnOnce(&Tables, &mut dyn FnMut(&RatingKey) -> bool),
+	) -> Vec<IndexedRating> {
+		let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+		if limit == 0 {
+			return Vec::new()
+		}
+		let tables = self.tables.read().expect("no sync panics while holding the lock; qed");
+		let mut found = Vec::new();
+		select(&*tables, &mut |key| {
+			if let Some(entry) = tables.ratings.get(key) {
+				found.push(entry.rating.clone());
+			}
+			found.len() < limit
+		});
+		found
+	}
+}
+
+/// Storage key of the pallet's `RatingChangesHead`, under whatever name the runtime gives it.
+fn head_key() -> StorageKey {
+	StorageKey(pallet_reputation::RatingChangesHead::<Runtime>::hashed_key().to_vec())
+}
+
+/// Every rating in `block`'s state as a submission, followed by the hiding of those hidden.
+/// For blocks whose changes never reached the offchain index, i.e. genesis.
+fn read_ratings(client: &FullClient, block: Hash) -> sp_blockchain::Result<Vec<RatingRecord<AccountId>>> {
+	let prefix = StorageKey(pallet_reputation::Ratings::<Runtime>::final_prefix().to_vec());
+	let mut records = Vec::new();
+	let mut hidden = Vec::new();
+	for (key, value) in client.storage_pairs(block, Some(&prefix), None)? {
+		let Ok(rating) = pallet_reputation::RatingOf::<Runtime>::decode(&mut &value.0[..]) else {
+			log::warn!(target: LOG_TARGET, "skipping undecodable rating at {}", hex::encode(&key.0));
+			continue
+		};
+		let hidden_key = pallet_reputation::HiddenRatings::<Runtime>::hashed_key_for(&rating.to, &rating.from);
+		if client.storage(block, &StorageKey(hidden_key))?.is_some() {
+			hidden.push(RatingRecord {
+				to: rating.to.clone(),
+				from: Rater::Account(rating.from.clone()),
+				change: RatingChange::Hidden,
+				at: rating.timestamp,
+			});
+		}
+		records.push(RatingRecord {
+			to: rating.to,
+			from: Rater::Account(rating.from),
+			change: RatingChange::Submitted { score: rating.score, category_ratings: rating.category_ratings },
+			at: rating.timestamp,
+		});
+	}
+	records.extend(hidden);
+	Ok(records)
+}
+
+/// Records `block` wrote, oldest first.
+fn read_records(
+	client: &FullClient,
+	storage: &impl OffchainStorage,
+	block: Hash,
+) -> Result<Vec<RatingRecord<AccountId>>, Box<dyn std::error::Error>> {
+	let header = client.header(block)?.ok_or("unknown block")?;
+	let Some(head) = client.storage(block, &head_key())? else { return Ok(Vec::new()) };
+	let mut link = Hash::decode(&mut &head.0[..])?;
+
+	let mut records = Vec::new();
+	while link != *header.parent_hash() {
+		let entry = storage.get(STORAGE_PREFIX, &record_key(&link)).ok_or("record missing from the offchain index")?;
+		let IndexEntry { previous, record } = IndexEntry::<AccountId, Hash>::decode(&mut &entry[..])?;
+		records.push(record);
+		link = previous;
+	}
+	records.reverse();
+	Ok(records)
+}
+
+/// Start following the chain if offchain indexing is enabled. Without it there is nothing to
+/// read and the `ratings_*` methods are not served.
+pub fn start(
+	config: &Configuration,
+	client: Arc<FullClient>,
+	backend: Arc<FullBackend>,
+	task_manager: &TaskManager,
+) -> Option<Arc<RatingIndex>> {
+	if !config.offchain_worker.indexing_enabled {
+		log::info!(target: LOG_TARGET, "offchain indexing is disabled; ratings_* RPC methods are unavailable");
+		return None
+	}
+	let Some(storage) = backend.offchain_storage() else {
+		log::warn!(target: LOG_TARGET, "backend has no offchain storage; ratings_* RPC methods are unavailable");
+		return None
+	};
+
+	let index = Arc::new(RatingIndex::resume(&client, &storage));
+	task_manager.spawn_handle().spawn("rating-index", None, follow(client, storage, index.clone()));
+	Some(index)
+}
+
+async fn follow(client: Arc<FullClient>, mut storage: impl OffchainStorage, index: Arc<RatingIndex>) {
+	// Subscribed before catching up, so no block is missed in between.
+	let mut imported = client.import_notification_stream();
+	let mut checkpointed = 0;
+
+	if let Err(e) = index.sync(&client, &storage, client.info().best_hash) {
+		log::error!(target: LOG_TARGET, "failed to build rating index: {}", e);
+	}
+	checkpointed = index.checkpoint(&client, &mut storage, checkpointed);
+
+	while let Some(notification) = imported.next().await {
+		if !notification.is_new_best {
+			continue
+		}
+		if let Err(e) = index.sync(&client, &storage, notification.hash) {
+			log::warn!(target: LOG_TARGET, "failed to index {}: {}", notification.hash, e);
+		}
+		checkpointed = index.checkpoint(&client, &mut storage, checkpointed);
+	}
+}
+
+/// Rating queries over the offchain index. Times are milliseconds since the Unix epoch and
+/// ranges are inclusive; results are newest first and hidden ratings are left out.
+#[rpc(server)]
+pub trait RatingIndexApi {
+	/// Ratings submitted between `since` and `until`, across all profiles.
+	#[method(name = "ratings_inRange")]
+	fn in_range(&self, since: u64, until: u64, limit: Option<u32>) -> RpcResult<Vec<IndexedRating>>;
+
+	/// Ratings `rater` gave, optionally only those submitted between `since` and `until`.
+	#[method(name = "ratings_byRater")]
+	fn by_rater(
+		&self,
+		rater: AccountId,
+		since: Option<u64>,
+		until: Option<u64>,
+		limit: Option<u32>,
+	) -> RpcResult<Vec<IndexedRating>>;
+
+	/// Ratings submitted between `since` and `until` whose `category` score is between `min`
+	/// and `max`.
+	#[method(name = "ratings_byCategory")]
+	fn by_category(
+		&self,
+		category: Category,
+		min: u32,
+		max: u32,
+		since: u64,
+		until: u64,
+		limit: Option<u32>,
+	) -> RpcResult<Vec<IndexedRating>>;
+}
+
+/// Serves [`RatingIndexApi`] from a [`RatingIndex`].
+pub struct RatingIndexRpc {
+	index: Arc<RatingIndex>,
+}
+
+impl RatingIndexRpc {
+	pub fn new(index: Arc<RatingIndex>) -> Self {
+		Self { index }
+	}
+}
+
+impl RatingIndexApiServer for RatingIndexRpc {
+	fn in_range(&self, since: u64, until: u64, limit: Option<u32>) -> RpcResult<Vec<IndexedRating>> {
+		Ok(self.index.query(limit, |tables, visit| {
+			for (_, to, from) in tables.in_range(since, until) {
+				if !visit(&(to.clone(), from.clone())) {
+					break
+				}
+			}
+		}))
+	}
+
+	fn by_rater(
+		&self,
+		rater: AccountId,
+		since: Option<u64>,
+		until: Option<u64>,
+		limit: Option<u32>,
+	) -> RpcResult<Vec<IndexedRating>> {
+		let (since, until) = (since.unwrap_or(0), until.unwrap_or(u64::MAX));
+		Ok(self.index.query(limit, |tables, visit| {
+			let Some(rated) = tables.by_rater.get(&rater).filter(|_| since <= until) else { return };
+			let (first, last) = (AccountId::new([0; 32]), AccountId::new([0xff; 32]));
+			for (_, to) in rated.range((since, first)..=(until, last)).rev() {
+				if !visit(&(to.clone(), Rater::Account(rater.clone()))) {
+					break
+				}
+			}
+		}))
+	}
+
+	fn by_category(
+		&self,
+		category: Category,
+		min: u32,
+		max: u32,
+		since: u64,
+		until: u64,
+		limit: Option<u32>,
+	) -> RpcResult<Vec<IndexedRating>> {
+		Ok(self.index.query(limit, |tables, visit| {
+			for (_, to, from) in tables.in_range(since, until) {
+				let key = (to.clone(), from.clone());
+				let matches = tables
+					.ratings
+					.get(&key)
+					.map_or(false, |entry| (min..=max).contains(&category.score(&entry.rating)));
+				if matches && !visit(&key) {
+					break
+				}
+			}
+		}))
+	}
+}
@@ -0,0 +1,266 @@
+//! Node-local review content store.
+//!
+//! Review bodies are kept as files named by the hex Blake2-256 of their content, under the
+//! chain's directory in the base path, and served through `reviews_put` and `reviews_get`. A
+//! rating's `review_hash` is `ContentRef::blake2_256` of the hash `reviews_put` returns, the same
+//! reference the on-chain content store uses, so clients need no separate IPFS node.
+//!
+//! Bodies over `--review-max-size` are refused. When a body would take the store past
+//! `--review-store-size`, the oldest unpinned bodies are evicted to make room. With
+//! `--review-pin` the node pins every body a rating in a finalized block refers to, anonymous
+//! ratings included. `reviews_put` writes to the node's disk and is only served to callers
+//! allowed unsafe RPC methods.
+
+use std::{
+	fmt, fs, io,
+	path::PathBuf,
+	sync::{Arc, Mutex},
+	time::SystemTime,
+};
+
+use futures::StreamExt;
+use jsonrpsee::{
+	core::RpcResult,
+	proc_macros::rpc,
+	types::error::{CallError, ErrorObject},
+};
+use pallet_reputation::content::{BLAKE2B_256, RAW};
+use pallet_reputation_runtime_api::ReputationApi;
+use reputechain_runtime::Hash;
+use sc_client_api::BlockchainEvents;
+use sc_rpc_api::DenyUnsafe;
+use sc_service::{error::Error as ServiceError, Configuration, TaskManager};
+use sp_api::ProvideRuntimeApi;
+use sp_core::{hashing::blake2_256, Bytes, H256};
+
+use crate::{cli::ReviewStoreParams, service::FullClient};
+
+const LOG_TARGET: &str = "reviews";
+
+/// Subdirectory holding an empty marker file per pinned hash.
+const PINS_DIR: &str = "pins";
+
+#[derive(Debug)]
+pub enum Error {
+	/// The body is over `--review-max-size`.
+	TooLarge { len: usize, max: usize },
+	/// Pinned bodies leave no room for this one.
+	StoreFull,
+	Io(io::Error),
+}
+
+impl fmt::Display for Error {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Error::TooLarge { len, max } => write!(f, "review is {len} bytes, at most {max} are accepted"),
+			Error::StoreFull => write!(f, "review store is full"),
+			Error::Io(e) => write!(f, "review store I/O failed: {e}"),
+		}
+	}
+}
+
+impl std::error::Error for Error {}
+
+impl From<io::Error> for Error {
+	fn from(e: io::Error) -> Self {
+		Error::Io(e)
+	}
+}
+
+/// Content-addressed review bodies in a directory.
+pub struct ReviewStore {
+	dir: PathBuf,
+	max_size: usize,
+	/// In bytes.
+	capacity: u64,
+	// Writes are serialised so eviction sees the directory as it is.
+	write_lock: Mutex<()>,
+}
+
+impl ReviewStore {
+	/// Open the store in `dir`, creating it if needed.
+	pub fn open(dir: PathBuf, max_size: usize, capacity: u64) -> io::Result<Self> {
+		fs::create_dir_all(dir.join(PINS_DIR))?;
+		Ok(Self { dir, max_size, capacity, write_lock: Mutex::new(()) })
+	}
+
+	fn path(&self, hash: &[u8; 32]) -> PathBuf {
+		self.dir.join(hex::encode(hash))
+	}
+
+	fn pin_path(&self, hash: &[u8; 32]) -> PathBuf {
+		self.dir.join(PINS_DIR).join(hex::encode(hash))
+	}
+
+	/// Store `body` and return its Blake2-256 hash. Storing a body twice is a no-op.
+	pub fn put(&self, body: &[u8]) -> Result<[u8; 32], Error> {
+		if body.len() > self.max_size {
+			return Err(Error::TooLarge { len: body.len(), max: self.max_size })
+		}
+		let hash = blake2_256(body);
+		let path = self.path(&hash);
+
+		let _guard = self.write_lock.lock().expect("no write panics while holding the lock; qed");
+		if path.exists() {
+			return Ok(hash)
+		}
+		self.make_room(body.len() as u64)?;
+
+		// Written aside and renamed, so a crash never leaves a body that does not match its name.
+		let staged = path.with_extension("tmp");
+		fs::write(&staged, body)?;
+		fs::rename(&staged, &path)?;
+		Ok(hash)
+	}
+
+	/// Body stored under `hash`, if any.
+	pub fn get(&self, hash: &[u8; 32]) -> io::Result<Option<Vec<u8>>> {
+		match fs::read(self.path(hash)) {
+			Ok(body) => Ok(Some(body)),
+			Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+			Err(e) => Err(e),
+		}
+	}
+
+	/// Keep `hash` through eviction, whether or not its body is stored yet.
+	pub fn pin(&self, hash: &[u8; 32]) -> io::Result<()> {
+		fs::File::create(self.pin_path(hash)).map(|_| ())
+	}
+
+	pub fn is_pinned(&self, hash: &[u8; 32]) -> bool {
+		self.pin_path(hash).exists()
+	}
+
+	/// Evict unpinned bodies, least recently written first, until `len` more bytes fit. Nothing
+	/// is evicted if that would not be enough.
+	fn make_room(&self, len: u64) -> Result<(), Error> {
+		let mut used = 0;
+		let mut evictable = Vec::new();
+		for entry in fs::read_dir(&self.dir)? {
+			let entry = entry?;
+			let metadata = entry.metadata()?;
+			let hash = entry
+				.file_name()
+				.to_str()
+				.and_then(|name| hex::decode(name).ok())
+				.and_then(|hash| <[u8; 32]>::try_from(hash).ok());
+			// Pins, staged writes and anything else that is not a body.
+			let (true, Some(hash)) = (metadata.is_file(), hash) else { continue };
+
+			used += metadata.len();
+			if !self.is_pinned(&hash) {
+				let modified = metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH);
+				evictable.push((modified, metadata.len(), entry.path()));
+			}
+		}
+
+		let freeable: u64 = evictable.iter().map(|(_, size, _)| size).sum();
+		if used + len > self.capacity.saturating_add(freeable) {
+			return Err(Error::StoreFull)
+		}
+
+		evictable.sort_by_key(|(modified, ..)| *modified);
+		for (_, size, path) in evictable {
+			if used + len <= self.capacity {
+				break
+			}
+			fs::remove_file(path)?;
+			used -= size;
+		}
+		Ok(())
+	}
+}
+
+/// Open the store for the chain being run and start pinning if `--review-pin` is set.
+pub fn start(
+	config: &Configuration,
+	params: &ReviewStoreParams,
+	client: Arc<FullClient>,
+	task_manager: &TaskManager,
+) -> Result<Arc<ReviewStore>, ServiceError> {
+	let dir = config.base_path.config_dir(config.chain_spec.id()).join("reviews");
+	let store = Arc::new(ReviewStore::open(dir, params.review_max_size, params.review_store_size * 1024 * 1024)?);
+
+	if params.review_pin {
+		task_manager.spawn_handle().spawn("review-pinning", None, pin_referenced(client, store.clone()));
+	}
+
+	Ok(store)
+}
+
+/// Pin the bodies ratings refer to as their blocks are finalized. Finalized blocks cannot be
+/// reverted, so pins never need undoing.
+async fn pin_referenced(client: Arc<FullClient>, store: Arc<ReviewStore>) {
+	let mut finalized = client.finality_notification_stream();
+
+	while let Some(notification) = finalized.next().await {
+		// Blocks finalized along with the notified one come first.
+		for block in notification.tree_route.iter().chain([&notification.hash]) {
+			if let Err(e) = pin_block(&client, &store, *block) {
+				log::warn!(target: LOG_TARGET, "failed to pin reviews rated in {}: {}", block, e);
+			}
+		}
+	}
+}
+
+/// Pin what ratings submitted in `block` refer to. The runtime that built the block lists
+/// them, so events of runtimes this node was not compiled with are no obstacle.
+fn pin_block(client: &FullClient, store: &ReviewStore, block: Hash) -> Result<(), Box<dyn std::error::Error>> {
+	for review_hash in client.runtime_api().reviews_submitted(block)? {
+		// Only references to the raw body's Blake2-256 name something this store can hold.
+		let parts = review_hash.parts();
+		if parts.hash_function == BLAKE2B_256 && parts.codec.map_or(true, |codec| codec == RAW) {
+			store.pin(&<[u8; 32]>::try_from(parts.digest)?)?;
+		}
+	}
+
+	Ok(())
+}
+
+/// Review content RPC methods.
+#[rpc(server)]
+pub trait ReviewsApi {
+	/// Store a review body and return its Blake2-256 hash. Rate with the `blake2b-256`
+	/// multihash of it as `review_hash`. Unsafe: it takes the node's disk space.
+	#[method(name = "reviews_put")]
+	fn put(&self, body: Bytes) -> RpcResult<H256>;
+
+	/// Body stored under `hash`, if this node has it.
+	#[method(name = "reviews_get")]
+	fn get(&self, hash: H256) -> RpcResult<Option<Bytes>>;
+}
+
+/// Serves [`ReviewsApi`] from a [`ReviewStore`].
+pub struct Reviews {
+	store: Arc<ReviewStore>,
+	deny_unsafe: DenyUnsafe,
+}
+
+impl Reviews {
+	pub fn new(store: Arc<ReviewStore>, deny_unsafe: DenyUnsafe) -> Self {
+		Self { store, deny_unsafe }
+	}
+}
+
+fn rpc_error(e: Error) -> jsonrpsee::core::Error {
+	let code = match e {
+		Error::TooLarge { .. } => 1,
+		Error::StoreFull => 2,
+		Error::Io(_) => 3,
+	};
+	CallError::Custom(ErrorObject::owned(code, e.to_string(), None::<()>)).into()
+}
+
+impl ReviewsApiServer for Reviews {
+	fn put(&self, body: Bytes) -> RpcResult<H256> {
+		self.deny_unsafe.check_if_safe()?;
+		self.store.put(&body).map(H256).map_err(rpc_error)
+	}
+
+	fn get(&self, hash: H256) -> RpcResult<Option<Bytes>> {
+		self.store
+			.get(hash.as_fixed_bytes())
+			.map(|body| body.map(Into::into))
+			.map_err(|e| rpc_error(e.into()))
+	}
+}
@@ -0,0 +1,81 @@
+//! A collection of node-specific RPC methods.
+//!
+//! Substrate provides the `sc-rpc` crate, which defines the core RPC layer used by Substrate
+//! nodes. This file extends those RPC definitions with capabilities that are specific to this
+//! project's runtime configuration.
+
+#![warn(missing_docs)]
+
+use std::sync::Arc;
+
+use futures::channel::mpsc;
+use jsonrpsee::RpcModule;
+use reputechain_runtime::{opaque::Block, AccountId, Balance, Hash, Nonce};
+use sc_consensus_manual_seal::EngineCommand;
+use sc_transaction_pool_api::TransactionPool;
+use sp_api::ProvideRuntimeApi;
+use sp_block_builder::BlockBuilder;
+use sp_blockchain::{Error as BlockChainError, HeaderBackend, HeaderMetadata};
+
+use crate::{
+	rating_index::{RatingIndex, RatingIndexApiServer, RatingIndexRpc},
+	reviews::{ReviewStore, Reviews, ReviewsApiServer},
+};
+
+pub use sc_rpc_api::DenyUnsafe;
+
+/// Full client dependencies.
+pub struct FullDeps<C, P> {
+	/// The client instance to use.
+	pub client: Arc<C>,
+	/// Transaction pool instance.
+	pub pool: Arc<P>,
+	/// Whether to deny unsafe calls
+	pub deny_unsafe: DenyUnsafe,
+	/// Sink for `engine_*` commands when the node runs with manual seal.
+	pub command_sink: Option<mpsc::Sender<EngineCommand<Hash>>>,
+	/// Store behind `reviews_put` and `reviews_get`.
+	pub reviews: Arc<ReviewStore>,
+	/// Tables behind the `ratings_*` methods, if offchain indexing is enabled.
+	pub rating_index: Option<Arc<RatingIndex>>,
+}
+
+/// Instantiate all full RPC extensions.
+pub fn create_full<C, P>(
+	deps: FullDeps<C, P>,
+) -> Result<RpcModule<()>, Box<dyn std::error::Error + Send + Sync>>
+where
+	C: ProvideRuntimeApi<Block>,
+	C: HeaderBackend<Block> + HeaderMetadata<Block, Error = BlockChainError> + 'static,
+	C: Send + Sync + 'static,
+	C::Api: substrate_frame_rpc_system::AccountNonceApi<Block, AccountId, Nonce>,
+	C::Api: pallet_transaction_payment_rpc::TransactionPaymentRuntimeApi<Block, Balance>,
+	C::Api: BlockBuilder<Block>,
+	P: TransactionPool + 'static,
+{
+	use pallet_transaction_payment_rpc::{TransactionPayment, TransactionPaymentApiServer};
+	use sc_consensus_manual_seal::rpc::{ManualSeal, ManualSealApiServer};
+	use substrate_frame_rpc_system::{System, SystemApiServer};
+
+	let mut module = RpcModule::new(());
+	let FullDeps { client, pool, deny_unsafe, command_sink, reviews, rating_index } = deps;
+
+	module.merge(System::new(client.clone(), pool, deny_unsafe).into_rpc())?;
+	module.merge(TransactionPayment::new(client).into_rpc())?;
+	module.merge(Reviews::new(reviews, deny_unsafe).into_rpc())?;
+	if let Some(rating_index) = rating_index {
+		module.merge(RatingIndexRpc::new(rating_index).into_rpc())?;
+	}
+
+	if let Some(command_sink) = command_sink {
+		// `engine_createBlock` and `engine_finalizeBlock`.
+		module.merge(ManualSeal::new(command_sink).into_rpc())?;
+	}
+
+	// Extend this RPC with a custom API by using the following syntax.
+	// `YourRpcStruct` should have a reference to a client, which is needed
+	// to call into the runtime.
+	// `module.merge(YourRpcTrait::into_rpc(YourRpcStruct::new(ReferenceToClient, ...)))?;`
+
+	Ok(module)
+}
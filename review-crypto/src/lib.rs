@@ -0,0 +1,228 @@
+//! Encrypted reviews.
+//!
+//! [`seal`] encrypts review content under a fresh content key with ChaCha20-Poly1305 and wraps
+//! that key for the rated user, the rater and optionally the moderator key, giving the
+//! ciphertext to store off chain, its `review_hash` and the `ReviewEnvelope` for
+//! `submit_encrypted_rating`. [`open`] reverses it with any of those readers' secrets.
+//!
+//! A wrapping key is Blake2-256 of the x25519 shared secret between the envelope's ephemeral key
+//! and the reader, bound to both public keys. Every key encrypts exactly one message, so the
+//! nonce is always zero.
+
+use std::fmt;
+
+use chacha20poly1305::{
+    aead::{Aead, KeyInit},
+    ChaCha20Poly1305, Key, Nonce,
+};
+use pallet_reputation::{content::ContentRef, ReviewEnvelope, WrappedKey};
+use rand_core::{CryptoRng, RngCore};
+use sp_core::hashing::blake2_256;
+use x25519_dalek::PublicKey;
+
+pub use x25519_dalek::StaticSecret;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Error {
+    /// The envelope is not wrapped for this secret's public key.
+    NotARecipient,
+    /// Wrong key, or the ciphertext or envelope was tampered with.
+    Decryption,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::NotARecipient => write!(f, "envelope is not wrapped for this key"),
+            Error::Decryption => write!(f, "decryption failed"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// A sealed review, ready to publish.
+pub struct SealedReview {
+    /// Goes wherever review content goes; only the envelope's readers can decrypt it.
+    pub ciphertext: Vec<u8>,
+    /// Blake2-256 of `ciphertext`.
+    pub review_hash: ContentRef,
+    pub envelope: ReviewEnvelope,
+}
+
+/// New x25519 key pair. Publish the public half with `set_encryption_key`.
+pub fn generate_keypair<R: RngCore + CryptoRng>(rng: &mut R) -> (StaticSecret, [u8; 32]) {
+    let secret = StaticSecret::random_from_rng(rng);
+    let public = PublicKey::from(&secret).to_bytes();
+    (secret, public)
+}
+
+fn wrapping_key(shared: &[u8; 32], ephemeral: &[u8; 32], recipient: &[u8; 32]) -> [u8; 32] {
+    blake2_256(&[&b"reputation/review-key"[..], shared, ephemeral, recipient].concat())
+}
+
+fn encrypt(key: &[u8; 32], plaintext: &[u8]) -> Vec<u8> {
+    ChaCha20Poly1305::new(Key::from_slice(key))
+        .encrypt(Nonce::from_slice(&[0u8; 12]), plaintext)
+        .expect("encryption only fails for messages over 256 GiB; qed")
+}
+
+fn decrypt(key: &[u8; 32], ciphertext: &[u8]) -> Result<Vec<u8>, Error> {
+    ChaCha20Poly1305::new(Key::from_slice(key))
+        .decrypt(Nonce::from_slice(&[0u8; 12]), ciphertext)
+        .map_err(|_| Error::Decryption)
+}
+
+fn wrap(ephemeral: &StaticSecret, content_key: &[u8; 32], recipient: [u8; 32]) -> WrappedKey {
+    let shared = ephemeral.diffie_hellman(&PublicKey::from(recipient));
+    let key = wrapping_key(shared.as_bytes(), PublicKey::from(ephemeral).as_bytes(), &recipient);
+
+    let mut ciphertext = [0u8; 48];
+    ciphertext.copy_from_slice(&encrypt(&key, content_key));
+    WrappedKey { recipient, ciphertext }
+}
+
+/// Encrypt `content` for the rated user `to`, the rater `from` and, if governance holds one,
+/// the `moderator` key. All are x25519 public keys as published on chain.
+pub fn seal<R: RngCore + CryptoRng>(
+    content: &[u8],
+    to: [u8; 32],
+    from: [u8; 32],
+    moderator: Option<[u8; 32]>,
+    rng: &mut R,
+) -> SealedReview {
+    let mut content_key = [0u8; 32];
+    rng.fill_bytes(&mut content_key);
+    let ephemeral = StaticSecret::random_from_rng(rng);
+
+    let ciphertext = encrypt(&content_key, content);
+    let envelope = ReviewEnvelope {
+        ephemeral: PublicKey::from(&ephemeral).to_bytes(),
+        to: wrap(&ephemeral, &content_key, to),
+        from: wrap(&ephemeral, &content_key, from),
+        moderator: moderator.map(|key| wrap(&ephemeral, &content_key, key)),
+    };
+
+    SealedReview { review_hash: ContentRef::blake2_256(blake2_256(&ciphertext)), ciphertext, envelope }
+}
+
+/// Decrypt a review with the secret of any reader the envelope is wrapped for.
+pub fn open(ciphertext: &[u8], envelope: &ReviewEnvelope, secret: &StaticSecret) -> Result<Vec<u8>, Error> {
+    let public = PublicKey::from(secret).to_bytes();
+    let wrapped = [Some(&envelope.to), Some(&envelope.from), envelope.moderator.as_ref()]
+        .into_iter()
+        .flatten()
+        .find(|wrapped| wrapped.recipient == public)
+        .ok_or(Error::NotARecipient)?;
+
+    let shared = secret.diffie_hellman(&PublicKey::from(envelope.ephemeral));
+    let key = wrapping_key(shared.as_bytes(), &envelope.ephemeral, &public);
+    let content_key: [u8; 32] =
+        decrypt(&key, &wrapped.ciphertext)?.try_into().map_err(|_| Error::Decryption)?;
+
+    decrypt(&content_key, ciphertext)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chacha20poly1305::aead::OsRng;
+    use codec::{Decode, Encode};
+
+    const CONTENT: &[u8] = b"Paid on time and kept me posted throughout.";
+
+    struct Parties {
+        to: StaticSecret,
+        from: StaticSecret,
+        moderator: StaticSecret,
+        sealed: SealedReview,
+    }
+
+    fn sealed() -> Parties {
+        let (to, to_public) = generate_keypair(&mut OsRng);
+        let (from, from_public) = generate_keypair(&mut OsRng);
+        let (moderator, moderator_public) = generate_keypair(&mut OsRng);
+        let sealed = seal(CONTENT, to_public, from_public, Some(moderator_public), &mut OsRng);
+        Parties { to, from, moderator, sealed }
+    }
+
+    #[test]
+    fn every_reader_opens_sealed_review() {
+        let Parties { to, from, moderator, sealed } = sealed();
+
+        for secret in [&to, &from, &moderator] {
+            assert_eq!(open(&sealed.ciphertext, &sealed.envelope, secret).unwrap(), CONTENT);
+        }
+        assert_eq!(sealed.review_hash, ContentRef::blake2_256(blake2_256(&sealed.ciphertext)));
+    }
+
+    #[test]
+    fn moderator_is_optional() {
+        let (to, to_public) = generate_keypair(&mut OsRng);
+        let (_, from_public) = generate_keypair(&mut OsRng);
+        let sealed = seal(CONTENT, to_public, from_public, None, &mut OsRng);
+
+        assert!(sealed.envelope.moderator.is_none());
+        assert_eq!(open(&sealed.ciphertext, &sealed.envelope, &to).unwrap(), CONTENT);
+    }
+
+    #[test]
+    fn other_key_is_not_a_recipient() {
+        let Parties { sealed, .. } = sealed();
+        let (outsider, _) = generate_keypair(&mut OsRng);
+
+        assert_eq!(open(&sealed.ciphertext, &sealed.envelope, &outsider), Err(Error::NotARecipient));
+    }
+
+    #[test]
+    fn wrapped_key_for_another_recipient_fails() {
+        let Parties { to, sealed, .. } = sealed();
+        let (outsider, outsider_public) = generate_keypair(&mut OsRng);
+
+        // Claims to be for the outsider but was wrapped for `to`.
+        let mut envelope = sealed.envelope.clone();
+        envelope.to.recipient = outsider_public;
+        assert_eq!(open(&sealed.ciphertext, &envelope, &outsider), Err(Error::Decryption));
+        assert_eq!(open(&sealed.ciphertext, &envelope, &to), Err(Error::NotARecipient));
+    }
+
+    #[test]
+    fn tampered_ciphertext_fails() {
+        let Parties { to, sealed, .. } = sealed();
+
+        for i in [0, sealed.ciphertext.len() / 2, sealed.ciphertext.len() - 1] {
+            let mut ciphertext = sealed.ciphertext.clone();
+            ciphertext[i] ^= 1;
+            assert_eq!(open(&ciphertext, &sealed.envelope, &to), Err(Error::Decryption));
+        }
+    }
+
+    #[test]
+    fn tampered_envelope_fails() {
+        let Parties { to, sealed, .. } = sealed();
+
+        let mut envelope = sealed.envelope.clone();
+        envelope.ephemeral[0] ^= 1;
+        assert_eq!(open(&sealed.ciphertext, &envelope, &to), Err(Error::Decryption));
+
+        let mut envelope = sealed.envelope.clone();
+        envelope.to.ciphertext[0] ^= 1;
+        assert_eq!(open(&sealed.ciphertext, &envelope, &to), Err(Error::Decryption));
+    }
+
+    #[test]
+    fn truncated_input_fails() {
+        let Parties { to, sealed, .. } = sealed();
+
+        // Shorter than the Poly1305 tag, and missing the end of the tag.
+        assert_eq!(open(&sealed.ciphertext[..8], &sealed.envelope, &to), Err(Error::Decryption));
+        let cut = &sealed.ciphertext[..sealed.ciphertext.len() - 1];
+        assert_eq!(open(cut, &sealed.envelope, &to), Err(Error::Decryption));
+
+        let encoded = sealed.envelope.encode();
+        assert_eq!(ReviewEnvelope::decode(&mut &encoded[..]).unwrap(), sealed.envelope);
+        for len in [0, 32, encoded.len() - 1] {
+            assert!(ReviewEnvelope::decode(&mut &encoded[..len]).is_err());
+        }
+    }
+}
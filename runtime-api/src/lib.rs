@@ -0,0 +1,51 @@
+//! Runtime API for the reputation pallet.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use codec::Codec;
+use sp_std::vec::Vec;
+
+pub use pallet_reputation::{
+    content::ContentRef,
+    disclosure::{DisclosureEpoch, DisclosureWitness},
+};
+
+sp_api::decl_runtime_apis! {
+    #[api_version(3)]
+    pub trait ReputationApi<AccountId, BlockNumber>
+    where
+        AccountId: Codec,
+        BlockNumber: Codec,
+    {
+        /// Last committed disclosure epoch.
+        fn current_epoch() -> u32;
+
+        /// Root committed for `epoch` and the block it was committed at.
+        fn disclosure_epoch(epoch: u32) -> Option<DisclosureEpoch<BlockNumber>>;
+
+        /// Path from `who`'s disclosure leaf to the root. Call it at an epoch's block to prove
+        /// against that epoch.
+        fn disclosure_witness(who: AccountId) -> Option<DisclosureWitness>;
+
+        /// Check a score proof against the root committed for `epoch`.
+        fn verify_score_proof(
+            epoch: u32,
+            threshold: u32,
+            min_reviews: u32,
+            challenge: [u8; 32],
+            proof: Vec<u8>,
+        ) -> bool;
+
+        /// Review body stored on chain under `hash`. `None` if it was never stored or has been
+        /// forgotten.
+        fn review_content(hash: [u8; 32]) -> Option<Vec<u8>>;
+
+        /// `review_hash` of the rating `from` gave `to`.
+        #[api_version(2)]
+        fn review_hash(to: AccountId, from: AccountId) -> Option<ContentRef>;
+
+        /// `review_hash` of every rating submitted in the block, anonymous ones included.
+        #[api_version(3)]
+        fn reviews_submitted() -> Vec<ContentRef>;
+    }
+}
@@ -13,12 +13,13 @@ use sp_version::RuntimeVersion;
 
 use frame_support::{
     construct_runtime, parameter_types,
-    traits::{ConstBool, ConstU128, ConstU32, ConstU64, ConstU8},
+    traits::{ConstBool, ConstU128, ConstU32, ConstU64, ConstU8, EitherOfDiverse, EqualPrivilegeOnly},
     weights::{
         constants::WEIGHT_REF_TIME_PER_SECOND, IdentityFee, Weight,
     },
+    PalletId,
 };
-use frame_system::EnsureRoot;
+use frame_system::{EnsureRoot, EnsureSigned};
 use pallet_grandpa::{AuthorityId as GrandpaId, fg_primitives};
 use sp_consensus_grandpa as fg_primitives;
 
@@ -50,7 +51,7 @@ pub const VERSION: RuntimeVersion = RuntimeVersion {
     spec_name: create_runtime_str!("reputechain"),
     impl_name: create_runtime_str!("reputechain"),
     authoring_version: 1,
-    spec_version: 100,
+    spec_version: 116,
     impl_version: 1,
     apis: RUNTIME_API_VERSIONS,
     transaction_version: 1,
@@ -155,22 +156,268 @@ impl pallet_transaction_payment::Config for Runtime {
     type FeeMultiplierUpdate = ();
 }
 
+impl pallet_insecure_randomness_collective_flip::Config for Runtime {}
+
 impl pallet_sudo::Config for Runtime {
     type RuntimeEvent = RuntimeEvent;
     type RuntimeCall = RuntimeCall;
     type WeightInfo = ();
 }
 
+// Governance: a council and a technical committee (both `pallet_collective`) on top of
+// `pallet_democracy`. Referenda dispatch as Root; the council proposes external referenda and
+// holds the reputation origins below; the technical committee can fast-track and veto.
+pub type CouncilCollective = pallet_collective::Instance1;
+pub type TechnicalCollective = pallet_collective::Instance2;
+
+parameter_types! {
+    pub MaxCollectivesProposalWeight: Weight = Perbill::from_percent(50) * BlockWeights::get().max_block;
+    pub const CouncilMotionDuration: BlockNumber = 3 * DAYS;
+    pub const TechnicalMotionDuration: BlockNumber = 3 * DAYS;
+}
+
+impl pallet_collective::Config<CouncilCollective> for Runtime {
+    type RuntimeOrigin = RuntimeOrigin;
+    type Proposal = RuntimeCall;
+    type RuntimeEvent = RuntimeEvent;
+    type MotionDuration = CouncilMotionDuration;
+    type MaxProposals = ConstU32<100>;
+    type MaxMembers = ConstU32<100>;
+    type DefaultVote = pallet_collective::PrimeDefaultVote;
+    type WeightInfo = ();
+    type SetMembersOrigin = EnsureRoot<AccountId>;
+    type MaxProposalWeight = MaxCollectivesProposalWeight;
+}
+
+impl pallet_collective::Config<TechnicalCollective> for Runtime {
+    type RuntimeOrigin = RuntimeOrigin;
+    type Proposal = RuntimeCall;
+    type RuntimeEvent = RuntimeEvent;
+    type MotionDuration = TechnicalMotionDuration;
+    type MaxProposals = ConstU32<100>;
+    type MaxMembers = ConstU32<100>;
+    type DefaultVote = pallet_collective::PrimeDefaultVote;
+    type WeightInfo = ();
+    type SetMembersOrigin = EnsureRoot<AccountId>;
+    type MaxProposalWeight = MaxCollectivesProposalWeight;
+}
+
+/// Root, or at least `N / D` of the council.
+pub type RootOrCouncil<const N: u32, const D: u32> = EitherOfDiverse<
+    EnsureRoot<AccountId>,
+    pallet_collective::EnsureProportionAtLeast<AccountId, CouncilCollective, N, D>,
+>;
+
+/// Root, or at least `N / D` of the technical committee.
+pub type RootOrTechnical<const N: u32, const D: u32> = EitherOfDiverse<
+    EnsureRoot<AccountId>,
+    pallet_collective::EnsureProportionAtLeast<AccountId, TechnicalCollective, N, D>,
+>;
+
+parameter_types! {
+    pub MaximumSchedulerWeight: Weight = Perbill::from_percent(80) * BlockWeights::get().max_block;
+    pub const PreimageBaseDeposit: Balance = 1_000_000_000_000;
+    pub const PreimageByteDeposit: Balance = 1_000_000;
+}
+
+impl pallet_preimage::Config for Runtime {
+    type RuntimeEvent = RuntimeEvent;
+    type WeightInfo = ();
+    type Currency = Balances;
+    type ManagerOrigin = EnsureRoot<AccountId>;
+    type BaseDeposit = PreimageBaseDeposit;
+    type ByteDeposit = PreimageByteDeposit;
+}
+
+impl pallet_scheduler::Config for Runtime {
+    type RuntimeEvent = RuntimeEvent;
+    type RuntimeOrigin = RuntimeOrigin;
+    type PalletsOrigin = OriginCaller;
+    type RuntimeCall = RuntimeCall;
+    type MaximumWeight = MaximumSchedulerWeight;
+    type ScheduleOrigin = EnsureRoot<AccountId>;
+    type MaxScheduledPerBlock = ConstU32<50>;
+    type WeightInfo = ();
+    type OriginPrivilegeCmp = EqualPrivilegeOnly;
+    type Preimages = Preimage;
+}
+
+parameter_types! {
+    pub const LaunchPeriod: BlockNumber = 7 * DAYS;
+    pub const VotingPeriod: BlockNumber = 7 * DAYS;
+    pub const FastTrackVotingPeriod: BlockNumber = 3 * HOURS;
+    pub const EnactmentPeriod: BlockNumber = DAYS;
+    pub const CooloffPeriod: BlockNumber = 7 * DAYS;
+    pub const MinimumDeposit: Balance = 100_000_000_000_000;
+}
+
+impl pallet_democracy::Config for Runtime {
+    type RuntimeEvent = RuntimeEvent;
+    type Currency = Balances;
+    type EnactmentPeriod = EnactmentPeriod;
+    type LaunchPeriod = LaunchPeriod;
+    type VotingPeriod = VotingPeriod;
+    type VoteLockingPeriod = EnactmentPeriod;
+    type MinimumDeposit = MinimumDeposit;
+    // A simple majority of the council picks the next external referendum; three quarters can
+    // make it majority-carries, unanimity default-carries.
+    type ExternalOrigin = pallet_collective::EnsureProportionAtLeast<AccountId, CouncilCollective, 1, 2>;
+    type ExternalMajorityOrigin = pallet_collective::EnsureProportionAtLeast<AccountId, CouncilCollective, 3, 4>;
+    type ExternalDefaultOrigin = pallet_collective::EnsureProportionAtLeast<AccountId, CouncilCollective, 1, 1>;
+    type SubmitOrigin = EnsureSigned<AccountId>;
+    // Two thirds of the technical committee can fast-track an external proposal, all of it can
+    // enact one instantly.
+    type FastTrackOrigin = pallet_collective::EnsureProportionAtLeast<AccountId, TechnicalCollective, 2, 3>;
+    type InstantOrigin = pallet_collective::EnsureProportionAtLeast<AccountId, TechnicalCollective, 1, 1>;
+    type InstantAllowed = ConstBool<true>;
+    type FastTrackVotingPeriod = FastTrackVotingPeriod;
+    type CancellationOrigin = pallet_collective::EnsureProportionAtLeast<AccountId, CouncilCollective, 2, 3>;
+    type CancelProposalOrigin = RootOrTechnical<1, 1>;
+    type BlacklistOrigin = EnsureRoot<AccountId>;
+    // Any single technical committee member can veto an external proposal once per cool-off.
+    type VetoOrigin = pallet_collective::EnsureMember<AccountId, TechnicalCollective>;
+    type CooloffPeriod = CooloffPeriod;
+    type Slash = ();
+    type Scheduler = Scheduler;
+    type PalletsOrigin = OriginCaller;
+    type MaxVotes = ConstU32<100>;
+    type WeightInfo = ();
+    type MaxProposals = ConstU32<100>;
+    type Preimages = Preimage;
+    type MaxDeposits = ConstU32<100>;
+    type MaxBlacklisted = ConstU32<100>;
+}
+
+parameter_types! {
+    pub const SudoPalletName: &'static str = "Sudo";
+}
+
+/// Clears `Sudo`'s storage.
+///
+/// Sudo stays in `construct_runtime!` while the council finds its feet. The runtime upgrade
+/// that removes it (passed by referendum, not by the sudo key) drops the `Sudo` entry and
+/// adds this to `Migrations`.
+pub type SudoSunset =
+    frame_support::migrations::RemovePallet<SudoPalletName, <Runtime as frame_system::Config>::DbWeight>;
+
 // Configure the reputation pallet
 parameter_types! {
-    pub const MinStakeAmount: u128 = 1_000_000_000_000; // 1 token (12 decimals)
-    pub const MaxReviewsPerUser: u32 = 10_000;
+    /// Genesis and upgrade defaults for `ReputationParams`.
+    pub DefaultReputationParams: pallet_reputation::ParamsOf<Runtime> = pallet_reputation::ReputationParameters {
+        min_stake_amount: 1_000_000_000_000, // 1 token (12 decimals)
+        max_reviews_per_user: 10_000,
+        rating_deposit: 100_000_000_000, // 0.1 token
+        rating_cooldown: MINUTES,
+        decay_half_life: 180 * 24 * 60 * 60 * 1_000, // 180 days, in milliseconds
+    };
+    pub const ReputationPalletId: PalletId = PalletId(*b"py/reput");
+    pub const MinVouchAmount: Balance = 1_000_000_000_000;
+    pub const MaxVouchersPerProfile: u32 = 100;
+    pub const MaxUnbondingChunks: u32 = 16;
+    pub const VouchUnbondingPeriod: BlockNumber = 7 * DAYS;
+    pub const MinHelpfulnessVoterReviews: u32 = 1;
+    pub const FlagDeposit: Balance = 1_000_000_000_000;
+    pub const FlagThreshold: u32 = 3;
+    pub const JurorBond: Balance = 10_000_000_000_000;
+    pub const MinJurorScore: u32 = 4;
+    pub const MinJurorReviews: u32 = 5;
+    pub const MaxJurors: u32 = 1_000;
+    pub const PanelSize: u32 = 5;
+    pub const JuryDrawDelay: BlockNumber = 10;
+    pub const CommitPeriod: BlockNumber = DAYS;
+    pub const RevealPeriod: BlockNumber = DAYS;
+    pub const NoShowSlash: Perbill = Perbill::from_percent(10);
+    pub const MaxSuspensionsPerBlock: u32 = 64;
+    pub const AppealBond: Balance = 10_000_000_000_000;
+    pub const AppealPeriod: BlockNumber = 14 * DAYS;
+    pub const AppealDecisionPeriod: BlockNumber = 14 * DAYS;
+    pub const MaxAppeals: u32 = 256;
+    pub const RatingCommitDeposit: Balance = 1_000_000_000_000;
+    pub const RatingRevealWindow: BlockNumber = 2 * DAYS;
+    pub const UnrevealedSlash: Perbill = Perbill::from_percent(50);
+    pub const MaxCommitsPerBlock: u32 = 256;
+    pub const SessionDuration: BlockNumber = 7 * DAYS;
+    pub const MaxSessionsPerBlock: u32 = 256;
+    pub const SessionDeposit: Balance = 1_000_000_000_000;
+    pub const EpochLength: BlockNumber = DAYS;
+    pub const ContentDepositBase: Balance = 100_000_000_000;
+    pub const ContentDepositPerByte: Balance = 1_000_000_000;
+    pub const MaxContentLen: u32 = 4 * 1024;
+    pub const MaxContentChecks: u32 = 8;
+    pub const ContentRecheckPeriod: BlockNumber = 7 * DAYS;
 }
 
 impl pallet_reputation::Config for Runtime {
     type RuntimeEvent = RuntimeEvent;
-    type MinStakeAmount = MinStakeAmount;
-    type MaxReviewsPerUser = MaxReviewsPerUser;
+    type ParamsOrigin = RootOrCouncil<3, 4>;
+    type ModerationOrigin = RootOrCouncil<1, 2>;
+    // Appeals are heard by the technical committee rather than the council that moderates.
+    type AppealOrigin = RootOrTechnical<1, 2>;
+    type OnRatingSubmitted = ();
+    type OnProfileChanged = ();
+    type RatingsMigration = pallet_reputation::migrations::v4::MigrateRatingsToV4<Runtime>;
+    type Currency = Balances;
+    type SlashOrigin = RootOrCouncil<2, 3>;
+    type Slash = ();
+    type PalletId = ReputationPalletId;
+    type MinVouchAmount = MinVouchAmount;
+    type MaxVouchersPerProfile = MaxVouchersPerProfile;
+    type MaxUnbondingChunks = MaxUnbondingChunks;
+    type VouchUnbondingPeriod = VouchUnbondingPeriod;
+    type MinHelpfulnessVoterReviews = MinHelpfulnessVoterReviews;
+    // Biasable by block authors, see `pallet_reputation::Config::Randomness`. Aura has no VRF
+    // to draw from; switch to BABE's randomness if the chain moves to BABE.
+    type Randomness = RandomnessCollectiveFlip;
+    type FlagDeposit = FlagDeposit;
+    type FlagThreshold = FlagThreshold;
+    type JurorBond = JurorBond;
+    type MinJurorScore = MinJurorScore;
+    type MinJurorReviews = MinJurorReviews;
+    type MaxJurors = MaxJurors;
+    type PanelSize = PanelSize;
+    type JuryDrawDelay = JuryDrawDelay;
+    type CommitPeriod = CommitPeriod;
+    type RevealPeriod = RevealPeriod;
+    type NoShowSlash = NoShowSlash;
+    type MaxSuspensionsPerBlock = MaxSuspensionsPerBlock;
+    type AppealBond = AppealBond;
+    type AppealPeriod = AppealPeriod;
+    type AppealDecisionPeriod = AppealDecisionPeriod;
+    type MaxAppeals = MaxAppeals;
+    type RatingCommitDeposit = RatingCommitDeposit;
+    type RatingRevealWindow = RatingRevealWindow;
+    type UnrevealedSlash = UnrevealedSlash;
+    type MaxCommitsPerBlock = MaxCommitsPerBlock;
+    // No marketplace pallet attests interactions yet; until one does, verified sessions are
+    // opened through governance.
+    type SessionOrigin = EnsureRoot<AccountId>;
+    type AnonymityVerifier = pallet_reputation::anonymous::Groth16Bn254;
+    type SessionDuration = SessionDuration;
+    type MaxSessionsPerBlock = MaxSessionsPerBlock;
+    type SessionDeposit = SessionDeposit;
+    type EpochLength = EpochLength;
+    type ContentDepositBase = ContentDepositBase;
+    type ContentDepositPerByte = ContentDepositPerByte;
+    type MaxContentLen = MaxContentLen;
+    type ContentReporterId = pallet_reputation::offchain::crypto::ContentReporterId;
+    type MaxContentChecks = MaxContentChecks;
+    type ContentRecheckPeriod = ContentRecheckPeriod;
+    type UnixTime = Timestamp;
+}
+
+// Content reports are unsigned transactions carrying a signed payload, so only the signing types
+// and unsigned submission are needed.
+impl frame_system::offchain::SigningTypes for Runtime {
+    type Public = <Signature as Verify>::Signer;
+    type Signature = Signature;
+}
+
+impl<C> frame_system::offchain::SendTransactionTypes<C> for Runtime
+where
+    RuntimeCall: From<C>,
+{
+    type Extrinsic = UncheckedExtrinsic;
+    type OverarchingCall = RuntimeCall;
 }
 
 construct_runtime!(
@@ -182,6 +429,12 @@ construct_runtime!(
         Grandpa: pallet_grandpa,
         Balances: pallet_balances,
         TransactionPayment: pallet_transaction_payment,
+        RandomnessCollectiveFlip: pallet_insecure_randomness_collective_flip,
+        Preimage: pallet_preimage,
+        Scheduler: pallet_scheduler,
+        Democracy: pallet_democracy,
+        Council: pallet_collective::<Instance1>,
+        TechnicalCommittee: pallet_collective::<Instance2>,
         Sudo: pallet_sudo,
         Reputation: pallet_reputation,
     }
@@ -204,12 +457,24 @@ pub type SignedPayload = generic::SignedPayload<RuntimeCall, SignedExtra>;
 pub type Block = generic::Block<Header, UncheckedExtrinsic>;
 pub type Header = generic::Header<BlockNumber, BlakeTwo256>;
 
+/// Migrations to apply on runtime upgrade.
+pub type Migrations = (
+    // Must run first: it compares the version being upgraded from with `RatingsMigration`.
+    pallet_reputation::migrations::lazy::StartRatingsMigration<Runtime>,
+    pallet_reputation::migrations::v1::MigrateToV1<Runtime>,
+    pallet_reputation::migrations::v2::MigrateToV2<Runtime>,
+    pallet_reputation::migrations::v3::MigrateToV3<Runtime>,
+    pallet_reputation::migrations::v4::MigrateToV4<Runtime>,
+    pallet_reputation::migrations::InitParams<Runtime, DefaultReputationParams>,
+);
+
 pub type Executive = frame_executive::Executive<
     Runtime,
     Block,
     frame_system::ChainContext<Runtime>,
     Runtime,
     AllPalletsWithSystem,
+    Migrations,
 >;
 
 pub mod opaque {
@@ -241,6 +506,7 @@ const RUNTIME_API_VERSIONS: sp_version::ApisVec = sp_version::create_apis_vec![
     [fg_primitives::GrandpaApi::<Block>::ID, fg_primitives::GrandpaApi::<Block>::VERSION],
     [frame_system_rpc_runtime_api::AccountNonceApi::<Block, AccountId, Nonce>::ID, frame_system_rpc_runtime_api::AccountNonceApi::<Block, AccountId, Nonce>::VERSION],
     [pallet_transaction_payment_rpc_runtime_api::TransactionPaymentApi::<Block, Balance>::ID, pallet_transaction_payment_rpc_runtime_api::TransactionPaymentApi::<Block, Balance>::VERSION],
+    [pallet_reputation_runtime_api::ReputationApi::<Block, AccountId, BlockNumber>::ID, pallet_reputation_runtime_api::ReputationApi::<Block, AccountId, BlockNumber>::VERSION],
     #[cfg(feature = "runtime-benchmarks")]
     [frame_benchmarking::Benchmark::<Block>::ID, frame_benchmarking::Benchmark::<Block>::VERSION],
     #[cfg(feature = "try-runtime")]
@@ -421,6 +687,56 @@ impl_runtime_apis! {
         }
     }
 
+    #[api_version(3)]
+    impl pallet_reputation_runtime_api::ReputationApi<Block, AccountId, BlockNumber> for Runtime {
+        fn current_epoch() -> u32 {
+            Reputation::current_epoch()
+        }
+
+        fn disclosure_epoch(epoch: u32) -> Option<pallet_reputation::disclosure::DisclosureEpoch<BlockNumber>> {
+            Reputation::epoch_roots(epoch)
+        }
+
+        fn disclosure_witness(who: AccountId) -> Option<pallet_reputation::disclosure::DisclosureWitness> {
+            Reputation::disclosure_witness(&who)
+        }
+
+        fn verify_score_proof(
+            epoch: u32,
+            threshold: u32,
+            min_reviews: u32,
+            challenge: [u8; 32],
+            proof: Vec<u8>,
+        ) -> bool {
+            Reputation::verify_score_proof(epoch, threshold, min_reviews, &challenge, &proof)
+        }
+
+        fn review_content(hash: [u8; 32]) -> Option<Vec<u8>> {
+            Reputation::review_content(&hash)
+        }
+
+        fn review_hash(to: AccountId, from: AccountId) -> Option<pallet_reputation::content::ContentRef> {
+            Reputation::ratings(to, from).map(|rating| rating.review_hash)
+        }
+
+        fn reviews_submitted() -> Vec<pallet_reputation::content::ContentRef> {
+            System::read_events_no_consensus()
+                .filter_map(|record| match record.event {
+                    RuntimeEvent::Reputation(pallet_reputation::Event::RatingSubmitted { from, to, .. }) => {
+                        Reputation::ratings(to, from).map(|rating| rating.review_hash)
+                    },
+                    RuntimeEvent::Reputation(pallet_reputation::Event::AnonymousRatingSubmitted {
+                        to,
+                        nullifier,
+                        ..
+                    }) => pallet_reputation::AnonymousRatings::<Runtime>::get(to, nullifier)
+                        .map(|rating| rating.review_hash),
+                    _ => None,
+                })
+                .collect()
+        }
+    }
+
     #[cfg(feature = "runtime-benchmarks")]
     impl frame_benchmarking::Benchmark<Block> for Runtime {
         fn benchmark_metadata(extra: bool) -> (
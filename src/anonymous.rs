@@ -0,0 +1,200 @@
+//! Anonymous ratings.
+//!
+//! Accounts with an active profile register an identity commitment, which is appended to an
+//! incremental Merkle tree of depth [`ANON_TREE_DEPTH`]. To rate anonymously, a rater proves in
+//! zero knowledge that they know the secret behind some leaf of a recent root, and publishes a
+//! nullifier derived from that secret and the target. The nullifier is the same every time the
+//! same person rates the same target, so each registered identity rates each target once, but it
+//! cannot be linked to the leaf or the account that registered it.
+//!
+//! The proof has four public inputs, in order: the tree root, the nullifier, the external
+//! nullifier ([`external_nullifier`] of the target) and the signal ([`signal_hash`] of the
+//! rating contents), so a relayer cannot alter the rating it submits.
+
+use super::*;
+use frame_support::pallet_prelude::*;
+use sp_std::vec::Vec;
+
+/// Depth of the identity tree: room for about a million registered raters.
+pub const ANON_TREE_DEPTH: u32 = 20;
+
+/// Recent roots a proof may be made against, so registrations landing between building a proof
+/// and submitting it do not invalidate it.
+pub const ANON_ROOT_HISTORY: u32 = 32;
+
+/// Upper bound on an encoded proof.
+pub const MAX_PROOF_LEN: u32 = 512;
+
+/// Upper bound on an encoded verifying key.
+pub const MAX_VERIFYING_KEY_LEN: u32 = 4096;
+
+/// Public inputs of an anonymous rating proof.
+pub struct PublicInputs {
+    pub root: [u8; 32],
+    pub nullifier: [u8; 32],
+    pub external_nullifier: [u8; 32],
+    pub signal: [u8; 32],
+}
+
+/// Hashing of the identity tree and verification of membership proofs over it. Both sides must
+/// match the circuit the verifying key was generated for.
+pub trait MembershipVerifier {
+    /// Parent of two tree nodes.
+    fn hash_pair(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32];
+
+    /// Check `proof` against `verifying_key` for `inputs`.
+    fn verify(verifying_key: &[u8], inputs: &PublicInputs, proof: &[u8]) -> bool;
+}
+
+/// No verifier: anonymous ratings are rejected.
+impl MembershipVerifier for () {
+    fn hash_pair(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+        Groth16Bn254::hash_pair(left, right)
+    }
+
+    fn verify(_verifying_key: &[u8], _inputs: &PublicInputs, _proof: &[u8]) -> bool {
+        false
+    }
+}
+
+/// Groth16 over BN254, verified in the runtime. Nodes are Blake2-256 with the top three bits
+/// cleared so every node is a valid BN254 scalar.
+///
+/// Verifying keys and proofs use arkworks' compressed canonical serialization.
+pub struct Groth16Bn254;
+
+impl MembershipVerifier for Groth16Bn254 {
+    fn hash_pair(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+        let mut preimage = [0u8; 64];
+        preimage[..32].copy_from_slice(left);
+        preimage[32..].copy_from_slice(right);
+        to_field_bytes(sp_io::hashing::blake2_256(&preimage))
+    }
+
+    fn verify(verifying_key: &[u8], inputs: &PublicInputs, proof: &[u8]) -> bool {
+        let public = [inputs.root, inputs.nullifier, inputs.external_nullifier, inputs.signal];
+        verify_groth16_bn254(verifying_key, &public, proof)
+    }
+}
+
+/// Check a Groth16 proof over BN254. Public inputs are little-endian field elements and must be
+/// in [`to_field_bytes`] form: otherwise several byte strings would verify as the same element,
+/// and a nullifier could be replayed under another key.
+pub fn verify_groth16_bn254(verifying_key: &[u8], public_inputs: &[[u8; 32]], proof: &[u8]) -> bool {
+    use ark_bn254::{Bn254, Fr};
+    use ark_ff::PrimeField;
+    use ark_groth16::{prepare_verifying_key, Groth16, Proof, VerifyingKey};
+    use ark_serialize::CanonicalDeserialize;
+
+    if !public_inputs.iter().all(is_field_bytes) {
+        return false
+    }
+
+    let Ok(vk) = VerifyingKey::<Bn254>::deserialize_compressed(verifying_key) else { return false };
+    let Ok(proof) = Proof::<Bn254>::deserialize_compressed(proof) else { return false };
+
+    let public: Vec<Fr> = public_inputs.iter().map(|input| Fr::from_le_bytes_mod_order(input)).collect();
+
+    Groth16::<Bn254>::verify_proof(&prepare_verifying_key(&vk), &proof, &public).unwrap_or(false)
+}
+
+/// Clear the top bits of a little-endian 256-bit value so it is below the BN254 scalar modulus.
+pub fn to_field_bytes(mut bytes: [u8; 32]) -> [u8; 32] {
+    bytes[31] &= 0x1f;
+    bytes
+}
+
+/// Whether `bytes` is already in [`to_field_bytes`] form, so it encodes exactly one field element.
+pub fn is_field_bytes(bytes: &[u8; 32]) -> bool {
+    to_field_bytes(*bytes) == *bytes
+}
+
+/// External nullifier for ratings of `target`: one rating per identity per target.
+pub fn external_nullifier<AccountId: Encode>(target: &AccountId) -> [u8; 32] {
+    to_field_bytes(sp_io::hashing::blake2_256(&(b"reputation/anon", target).encode()))
+}
+
+/// Signal binding a proof to the rating it accompanies.
+pub fn signal_hash<AccountId: Encode>(
+    target: &AccountId,
+    score: u8,
+    category_ratings: &[u8; 4],
+    review_hash: &ContentRef,
+) -> [u8; 32] {
+    to_field_bytes(sp_io::hashing::blake2_256(&(target, score, category_ratings, review_hash).encode()))
+}
+
+impl<T: Config> Pallet<T> {
+    /// Root of an empty subtree at each level.
+    fn zero_hashes() -> [[u8; 32]; ANON_TREE_DEPTH as usize] {
+        let mut zeros = [[0u8; 32]; ANON_TREE_DEPTH as usize];
+        for level in 1..ANON_TREE_DEPTH as usize {
+            zeros[level] = T::AnonymityVerifier::hash_pair(&zeros[level - 1], &zeros[level - 1]);
+        }
+        zeros
+    }
+
+    /// Append `leaf` to the identity tree and remember the new root.
+    pub(crate) fn insert_identity(leaf: [u8; 32]) -> DispatchResult {
+        let size = AnonTreeSize::<T>::get();
+        ensure!((size as u64) < 1u64 << ANON_TREE_DEPTH, Error::<T>::AnonymousTreeFull);
+
+        let zeros = Self::zero_hashes();
+        let mut index = size;
+        let mut node = to_field_bytes(leaf);
+        for level in 0..ANON_TREE_DEPTH {
+            let (left, right) = if index % 2 == 0 {
+                AnonFilledSubtrees::<T>::insert(level, node);
+                (node, zeros[level as usize])
+            } else {
+                (AnonFilledSubtrees::<T>::get(level).unwrap_or_default(), node)
+            };
+            node = T::AnonymityVerifier::hash_pair(&left, &right);
+            index /= 2;
+        }
+
+        AnonTreeSize::<T>::put(size + 1);
+        AnonRoots::<T>::mutate(|roots| {
+            if roots.is_full() {
+                roots.remove(0);
+            }
+            let _ = roots.try_push(node);
+        });
+
+        Ok(())
+    }
+
+    /// Everything about an anonymous rating except applying it: the target can be rated, the
+    /// root is recent, the nullifier unused and the proof valid. Shared by the call and
+    /// `validate_unsigned`.
+    pub(crate) fn check_anonymous_rating(
+        target: &T::AccountId,
+        score: u8,
+        category_ratings: &[u8; 4],
+        review_hash: &ContentRef,
+        root: &[u8; 32],
+        nullifier: &[u8; 32],
+        proof: &[u8],
+    ) -> DispatchResult {
+        ensure!(score >= 1 && score <= 5, Error::<T>::InvalidScore);
+        ensure!(
+            UserReputation::<T>::get(target).map_or(false, |profile| profile.status.is_active()),
+            Error::<T>::ProfileNotActive
+        );
+        ensure!(AnonRoots::<T>::get().contains(root), Error::<T>::UnknownRoot);
+        // `Nullifiers` is keyed by bytes, so only one encoding of each element may get this far.
+        ensure!(is_field_bytes(nullifier), Error::<T>::InvalidProof);
+        ensure!(!Nullifiers::<T>::contains_key(nullifier), Error::<T>::NullifierUsed);
+
+        let verifying_key = AnonVerifyingKey::<T>::get().ok_or(Error::<T>::AnonymousRatingsDisabled)?;
+        let inputs = PublicInputs {
+            root: *root,
+            nullifier: *nullifier,
+            external_nullifier: external_nullifier(target),
+            signal: signal_hash(target, score, category_ratings, review_hash),
+        };
+        ensure!(T::AnonymityVerifier::verify(&verifying_key, &inputs, proof), Error::<T>::InvalidProof);
+
+        Ok(())
+    }
+}
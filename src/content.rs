@@ -0,0 +1,261 @@
+//! References to review content.
+//!
+//! A [`ContentRef`] is the binary form of either a bare multihash (`<hash function> <digest
+//! length> <digest>`) or a CIDv1 (`0x01 <codec> <multihash>`), with varint codes. Only the hash
+//! functions and codecs listed here are accepted, and the digest length must match the hash
+//! function; anything else fails to decode, so every stored reference is well formed.
+
+use codec::{Decode, Encode, Error as CodecError, Input, MaxEncodedLen};
+use frame_support::{pallet_prelude::ConstU32, BoundedVec, RuntimeDebug};
+use scale_info::TypeInfo;
+use sp_std::vec::Vec;
+
+pub const SHA2_256: u64 = 0x12;
+pub const SHA2_512: u64 = 0x13;
+pub const KECCAK_256: u64 = 0x1b;
+pub const BLAKE3: u64 = 0x1e;
+pub const BLAKE2B_256: u64 = 0xb220;
+
+pub const RAW: u64 = 0x55;
+pub const DAG_PB: u64 = 0x70;
+pub const DAG_CBOR: u64 = 0x71;
+pub const DAG_JSON: u64 = 0x0129;
+
+/// Accepted hash functions and their digest lengths.
+const HASH_FUNCTIONS: &[(u64, usize)] =
+    &[(SHA2_256, 32), (SHA2_512, 64), (KECCAK_256, 32), (BLAKE3, 32), (BLAKE2B_256, 32)];
+
+/// Accepted CID codecs.
+const CODECS: &[u64] = &[RAW, DAG_PB, DAG_CBOR, DAG_JSON];
+
+/// Room for a CIDv1 with a 64-byte digest.
+pub const MAX_CONTENT_REF_LEN: u32 = 80;
+
+/// A validated multihash or CIDv1. See the module docs.
+#[derive(Clone, Encode, Eq, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+pub struct ContentRef(BoundedVec<u8, ConstU32<MAX_CONTENT_REF_LEN>>);
+
+/// What a [`ContentRef`] is made of.
+pub struct Parts<'a> {
+    /// `None` for a bare multihash.
+    pub codec: Option<u64>,
+    pub hash_function: u64,
+    pub digest: &'a [u8],
+}
+
+/// Unsigned varint of at most nine bytes. Padded encodings are refused, so every reference has
+/// one binary form.
+fn read_varint(bytes: &mut &[u8]) -> Option<u64> {
+    let mut value = 0u64;
+    for shift in (0..63).step_by(7) {
+        let (&byte, rest) = bytes.split_first()?;
+        *bytes = rest;
+        value |= ((byte & 0x7f) as u64) << shift;
+        if byte & 0x80 == 0 {
+            return (byte != 0 || shift == 0).then_some(value)
+        }
+    }
+    None
+}
+
+fn write_varint(mut value: u64, out: &mut Vec<u8>) {
+    while value >= 0x80 {
+        out.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+/// Split `bytes` into its parts, if it is a multihash or CIDv1 this pallet accepts.
+pub fn parse(bytes: &[u8]) -> Option<Parts<'_>> {
+    let mut rest = bytes;
+    // No accepted hash function has code 1, so a leading 1 can only be a CID version.
+    let codec = if rest.first() == Some(&0x01) {
+        rest = &rest[1..];
+        let codec = read_varint(&mut rest)?;
+        if !CODECS.contains(&codec) {
+            return None
+        }
+        Some(codec)
+    } else {
+        None
+    };
+
+    let hash_function = read_varint(&mut rest)?;
+    let len = read_varint(&mut rest)?;
+    let &(_, expected) = HASH_FUNCTIONS.iter().find(|(code, _)| *code == hash_function)?;
+    (len == expected as u64 && rest.len() == expected).then_some(Parts { codec, hash_function, digest: rest })
+}
+
+impl ContentRef {
+    /// Wrap the binary form of a multihash or CIDv1.
+    pub fn new(bytes: Vec<u8>) -> Option<Self> {
+        parse(&bytes)?;
+        bytes.try_into().ok().map(Self)
+    }
+
+    /// Bare multihash of a digest made with `hash_function`.
+    pub fn from_digest(hash_function: u64, digest: &[u8]) -> Option<Self> {
+        let mut bytes = Vec::with_capacity(digest.len() + 4);
+        write_varint(hash_function, &mut bytes);
+        write_varint(digest.len() as u64, &mut bytes);
+        bytes.extend_from_slice(digest);
+        Self::new(bytes)
+    }
+
+    pub fn sha2_256(digest: [u8; 32]) -> Self {
+        Self::from_digest(SHA2_256, &digest).expect("sha2-256 digests are 32 bytes; qed")
+    }
+
+    /// The reference for a body in the on-chain content store.
+    pub fn blake2_256(digest: [u8; 32]) -> Self {
+        Self::from_digest(BLAKE2B_256, &digest).expect("blake2b-256 digests are 32 bytes; qed")
+    }
+
+    pub fn parts(&self) -> Parts<'_> {
+        parse(&self.0).expect("only valid references are constructed or decoded; qed")
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+
+    pub fn digest(&self) -> &[u8] {
+        self.parts().digest
+    }
+
+    /// Key of the body in the on-chain content store this can refer to: a Blake2b-256 digest,
+    /// bare or in a `raw` CID.
+    pub fn stored_content_key(&self) -> Option<[u8; 32]> {
+        let parts = self.parts();
+        if parts.hash_function != BLAKE2B_256 || parts.codec.map_or(false, |codec| codec != RAW) {
+            return None
+        }
+        parts.digest.try_into().ok()
+    }
+
+    /// Binary CIDv1 of the reference; a bare multihash gets the `raw` codec.
+    pub fn to_cid_bytes(&self) -> Vec<u8> {
+        if self.parts().codec.is_some() {
+            return self.0.to_vec()
+        }
+        let mut bytes = Vec::with_capacity(self.0.len() + 2);
+        bytes.push(0x01);
+        write_varint(RAW, &mut bytes);
+        bytes.extend_from_slice(&self.0);
+        bytes
+    }
+
+    /// Whether `content` hashes to the digest. `None` for hash functions the runtime cannot
+    /// compute.
+    pub fn matches(&self, content: &[u8]) -> Option<bool> {
+        let parts = self.parts();
+        let digest = match parts.hash_function {
+            SHA2_256 => sp_io::hashing::sha2_256(content),
+            KECCAK_256 => sp_io::hashing::keccak_256(content),
+            BLAKE2B_256 => sp_io::hashing::blake2_256(content),
+            _ => return None,
+        };
+        Some(digest[..] == *parts.digest)
+    }
+}
+
+impl Decode for ContentRef {
+    fn decode<I: Input>(input: &mut I) -> Result<Self, CodecError> {
+        let bytes = BoundedVec::<u8, ConstU32<MAX_CONTENT_REF_LEN>>::decode(input)?;
+        parse(&bytes).ok_or("unsupported or malformed content reference")?;
+        Ok(Self(bytes))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DIGEST: [u8; 32] = [0xab; 32];
+
+    fn multihash(hash_function: &[u8], digest: &[u8]) -> Vec<u8> {
+        [hash_function, &[digest.len() as u8][..], digest].concat()
+    }
+
+    fn cid(codec: &[u8], multihash: &[u8]) -> Vec<u8> {
+        [&[0x01][..], codec, multihash].concat()
+    }
+
+    #[test]
+    fn prefixes_match_frontend() {
+        // `sha256ContentRef` and `blake2ContentRef` in `frontend/src/api.ts`.
+        let sha256 = ContentRef::sha2_256(DIGEST);
+        assert_eq!(sha256.as_bytes(), [&[0x12, 0x20][..], &DIGEST[..]].concat());
+        let blake2 = ContentRef::blake2_256(DIGEST);
+        assert_eq!(blake2.as_bytes(), [&[0xa0, 0xe4, 0x02, 0x20][..], &DIGEST[..]].concat());
+    }
+
+    #[test]
+    fn bare_multihash_round_trips() {
+        // What a CIDv0 (`Qm...`) encodes.
+        let bytes = multihash(&[0x12], &DIGEST);
+        let content = ContentRef::new(bytes.clone()).unwrap();
+
+        let parts = content.parts();
+        assert_eq!((parts.codec, parts.hash_function, parts.digest), (None, SHA2_256, &DIGEST[..]));
+        assert_eq!(content.as_bytes(), bytes);
+        assert_eq!(ContentRef::decode(&mut &content.encode()[..]).unwrap(), content);
+        assert_eq!(content.to_cid_bytes(), cid(&[0x55], &bytes));
+    }
+
+    #[test]
+    fn cid_v1_round_trips() {
+        let digest = [0xcd; 64];
+        let codecs: [(u64, &[u8]); 4] =
+            [(RAW, &[0x55]), (DAG_PB, &[0x70]), (DAG_CBOR, &[0x71]), (DAG_JSON, &[0xa9, 0x02])];
+        for (codec, codec_bytes) in codecs {
+            let bytes = cid(codec_bytes, &multihash(&[0x13], &digest));
+            let content = ContentRef::new(bytes.clone()).unwrap();
+
+            let parts = content.parts();
+            assert_eq!((parts.codec, parts.hash_function, parts.digest), (Some(codec), SHA2_512, &digest[..]));
+            assert_eq!(ContentRef::decode(&mut &content.encode()[..]).unwrap(), content);
+            assert_eq!(content.to_cid_bytes(), bytes);
+        }
+    }
+
+    #[test]
+    fn unknown_codes_are_rejected() {
+        // git-raw codec, then sha3-256 and identity hash functions.
+        assert!(parse(&cid(&[0x78], &multihash(&[0x12], &DIGEST))).is_none());
+        assert!(parse(&multihash(&[0x16], &DIGEST)).is_none());
+        assert!(parse(&multihash(&[0x00], &DIGEST)).is_none());
+        // Only CIDv1 is written with a version.
+        assert!(parse(&[&[0x02, 0x70][..], &multihash(&[0x12], &DIGEST)].concat()).is_none());
+    }
+
+    #[test]
+    fn wrong_digest_length_is_rejected() {
+        assert!(parse(&multihash(&[0x12], &DIGEST[..31])).is_none());
+        assert!(parse(&multihash(&[0x12], &[0xab; 64])).is_none());
+        // The declared length and the digest disagree.
+        assert!(parse(&[&[0x12, 0x20][..], &DIGEST[..31]].concat()).is_none());
+        assert!(parse(&[&multihash(&[0x12], &DIGEST)[..], &[0][..]].concat()).is_none());
+        assert!(ContentRef::from_digest(SHA2_256, &DIGEST[..20]).is_none());
+    }
+
+    #[test]
+    fn overlong_varints_are_rejected() {
+        // Ten bytes, more than a u64 needs.
+        let long = [&[0x01][..], &[0xff; 9][..], &[0x01][..], &multihash(&[0x12], &DIGEST)[..]].concat();
+        assert!(parse(&long).is_none());
+        // sha2-256 padded to two bytes, and raw to three.
+        assert!(parse(&[&[0x92, 0x00, 0x20][..], &DIGEST[..]].concat()).is_none());
+        assert!(parse(&cid(&[0xd5, 0x80, 0x00], &multihash(&[0x12], &DIGEST))).is_none());
+        // Cut off mid-varint.
+        assert!(parse(&[0x01, 0x80]).is_none());
+    }
+
+    #[test]
+    fn decode_rejects_invalid_references() {
+        let bytes: BoundedVec<u8, ConstU32<MAX_CONTENT_REF_LEN>> =
+            multihash(&[0x16], &DIGEST).try_into().unwrap();
+        assert!(ContentRef::decode(&mut &bytes.encode()[..]).is_err());
+    }
+}
@@ -0,0 +1,203 @@
+//! Private reputation disclosure.
+//!
+//! Profiles that opt in with a disclosure key get a leaf in a Merkle tree of depth
+//! [`DISCLOSURE_TREE_DEPTH`] committing to their `total_score` and `review_count`. The leaf is
+//! refreshed whenever the profile changes, and every `EpochLength` blocks the root is committed
+//! as that epoch's root at the end of the block.
+//!
+//! A holder can then prove in zero knowledge that some leaf under an epoch root belongs to a key
+//! they know the secret of, and that its average is at least a threshold over at least a number
+//! of reviews, without revealing the leaf, the account or the aggregates. The proof has four
+//! public inputs, in order: the epoch root, the threshold in hundredths of a point, the minimum
+//! review count and a challenge chosen by the verifier so the proof cannot be replayed to
+//! someone else.
+//!
+//! Nodes are Blake2s-256 with the top three bits cleared, so they are BN254 scalars and cheap to
+//! prove in a circuit.
+
+use super::*;
+use blake2::{Blake2s256, Digest};
+use frame_support::pallet_prelude::*;
+use frame_system::pallet_prelude::BlockNumberFor;
+use sp_runtime::traits::Zero;
+use sp_std::vec::Vec;
+
+/// Depth of the disclosure tree: room for about a million opted-in profiles.
+pub const DISCLOSURE_TREE_DEPTH: u32 = 20;
+
+/// Epoch roots kept; proofs against older epochs can no longer be checked on chain.
+pub const DISCLOSURE_EPOCH_HISTORY: u32 = 64;
+
+/// A profile's place in the disclosure tree.
+#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+pub struct DisclosureEntry {
+    pub index: u32,
+    /// [`disclosure_key`] of the holder's secret.
+    pub key: [u8; 32],
+}
+
+/// A committed epoch root and the block it was committed at.
+#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+pub struct DisclosureEpoch<BlockNumber> {
+    pub root: [u8; 32],
+    pub at: BlockNumber,
+}
+
+/// Everything a holder needs besides their secret to prove against the current tree. Read it at
+/// the block an epoch was committed at for a proof against that epoch.
+#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo)]
+pub struct DisclosureWitness {
+    pub index: u32,
+    pub total_score: u32,
+    pub review_count: u32,
+    /// Sibling of each node on the path from the leaf, leaf level first.
+    pub siblings: Vec<[u8; 32]>,
+    pub root: [u8; 32],
+}
+
+/// Parent of two tree nodes.
+pub fn hash_pair(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+    let mut hasher = Blake2s256::new();
+    hasher.update(left);
+    hasher.update(right);
+    anonymous::to_field_bytes(hasher.finalize().into())
+}
+
+/// Public key for a disclosure secret.
+pub fn disclosure_key(secret: &[u8; 32]) -> [u8; 32] {
+    hash_pair(secret, &[0u8; 32])
+}
+
+/// Aggregates as committed in a leaf: `total_score` then `review_count`, little-endian.
+pub fn pack_aggregates(total_score: u32, review_count: u32) -> [u8; 32] {
+    let mut packed = [0u8; 32];
+    packed[..4].copy_from_slice(&total_score.to_le_bytes());
+    packed[4..8].copy_from_slice(&review_count.to_le_bytes());
+    packed
+}
+
+/// Leaf for `key` with the given aggregates.
+pub fn leaf(key: &[u8; 32], total_score: u32, review_count: u32) -> [u8; 32] {
+    hash_pair(key, &pack_aggregates(total_score, review_count))
+}
+
+/// Root of an empty subtree at each level, leaves included.
+pub fn zero_hashes() -> [[u8; 32]; DISCLOSURE_TREE_DEPTH as usize + 1] {
+    let mut zeros = [[0u8; 32]; DISCLOSURE_TREE_DEPTH as usize + 1];
+    for level in 1..=DISCLOSURE_TREE_DEPTH as usize {
+        zeros[level] = hash_pair(&zeros[level - 1], &zeros[level - 1]);
+    }
+    zeros
+}
+
+/// Public inputs of a score proof, in circuit order. The top three bits of `challenge` are
+/// ignored so it is always a canonical field element.
+pub fn public_inputs(root: &[u8; 32], threshold: u32, min_reviews: u32, challenge: &[u8; 32]) -> [[u8; 32]; 4] {
+    let mut threshold_input = [0u8; 32];
+    threshold_input[..4].copy_from_slice(&threshold.to_le_bytes());
+    let mut min_reviews_input = [0u8; 32];
+    min_reviews_input[..4].copy_from_slice(&min_reviews.to_le_bytes());
+    [*root, threshold_input, min_reviews_input, anonymous::to_field_bytes(*challenge)]
+}
+
+/// Check a score proof against a root the caller trusts. `threshold` is in hundredths of a
+/// point, so 450 proves an average of at least 4.5.
+pub fn verify_score_proof_against(
+    verifying_key: &[u8],
+    root: &[u8; 32],
+    threshold: u32,
+    min_reviews: u32,
+    challenge: &[u8; 32],
+    proof: &[u8],
+) -> bool {
+    anonymous::verify_groth16_bn254(verifying_key, &public_inputs(root, threshold, min_reviews, challenge), proof)
+}
+
+impl<T: Config> Pallet<T> {
+    /// Current root of the disclosure tree.
+    pub fn disclosure_root() -> [u8; 32] {
+        DisclosureNodes::<T>::get(DISCLOSURE_TREE_DEPTH, 0).unwrap_or(zero_hashes()[DISCLOSURE_TREE_DEPTH as usize])
+    }
+
+    /// Write `leaf` at `index` and rehash the path above it.
+    pub(crate) fn set_disclosure_leaf(index: u32, leaf: [u8; 32]) {
+        let zeros = zero_hashes();
+        let mut index = index;
+        let mut node = leaf;
+        DisclosureNodes::<T>::insert(0, index, node);
+        for level in 0..DISCLOSURE_TREE_DEPTH {
+            let sibling = DisclosureNodes::<T>::get(level, index ^ 1).unwrap_or(zeros[level as usize]);
+            node = if index % 2 == 0 { hash_pair(&node, &sibling) } else { hash_pair(&sibling, &node) };
+            index /= 2;
+            DisclosureNodes::<T>::insert(level + 1, index, node);
+        }
+    }
+
+    /// Recommit `who`'s leaf to their profile as it is now. Profiles that are not active get an
+    /// empty leaf, which no one can prove membership of.
+    pub(crate) fn refresh_disclosure_leaf(who: &T::AccountId) {
+        let Some(entry) = DisclosureKeys::<T>::get(who) else { return };
+        let leaf = match UserReputation::<T>::get(who) {
+            Some(profile) if profile.status.is_active() =>
+                leaf(&entry.key, profile.total_score, profile.review_count),
+            _ => [0u8; 32],
+        };
+        Self::set_disclosure_leaf(entry.index, leaf);
+    }
+
+    /// Commit the tree root for the epoch ending at `n`, if one does.
+    pub(crate) fn commit_epoch(n: BlockNumberFor<T>) {
+        let length = T::EpochLength::get();
+        if length.is_zero() || !(n % length).is_zero() {
+            return
+        }
+        let Ok(epoch) = u32::try_from(n / length) else { return };
+
+        let root = Self::disclosure_root();
+        EpochRoots::<T>::insert(epoch, DisclosureEpoch { root, at: n });
+        if let Some(expired) = epoch.checked_sub(DISCLOSURE_EPOCH_HISTORY) {
+            EpochRoots::<T>::remove(expired);
+        }
+        CurrentEpoch::<T>::put(epoch);
+
+        Self::deposit_event(Event::EpochCommitted { epoch, root });
+    }
+
+    /// Path from `who`'s leaf to the current root.
+    pub fn disclosure_witness(who: &T::AccountId) -> Option<DisclosureWitness> {
+        let entry = DisclosureKeys::<T>::get(who)?;
+        let profile = UserReputation::<T>::get(who).filter(|profile| profile.status.is_active())?;
+
+        let zeros = zero_hashes();
+        let mut index = entry.index;
+        let mut siblings = Vec::with_capacity(DISCLOSURE_TREE_DEPTH as usize);
+        for level in 0..DISCLOSURE_TREE_DEPTH {
+            siblings.push(DisclosureNodes::<T>::get(level, index ^ 1).unwrap_or(zeros[level as usize]));
+            index /= 2;
+        }
+
+        Some(DisclosureWitness {
+            index: entry.index,
+            total_score: profile.total_score,
+            review_count: profile.review_count,
+            siblings,
+            root: Self::disclosure_root(),
+        })
+    }
+
+    /// Check a score proof against the root committed for `epoch`.
+    pub fn verify_score_proof(
+        epoch: u32,
+        threshold: u32,
+        min_reviews: u32,
+        challenge: &[u8; 32],
+        proof: &[u8],
+    ) -> bool {
+        let (Some(committed), Some(verifying_key)) =
+            (EpochRoots::<T>::get(epoch), DisclosureVerifyingKey::<T>::get())
+        else {
+            return false
+        };
+        verify_score_proof_against(&verifying_key, &committed.root, threshold, min_reviews, challenge, proof)
+    }
+}
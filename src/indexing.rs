@@ -0,0 +1,86 @@
+//! Offchain indexing of rating changes.
+//!
+//! Every rating submitted, hidden or unhidden, anonymous ones included, is written as a compact [`RatingRecord`] to the
+//! node's offchain database through `sp_io::offchain_index`, for nodes run with offchain
+//! indexing enabled to build query tables from.
+//!
+//! Offchain index writes of every imported block land in the same database, forks included,
+//! and the pallet cannot know the hash of the block it is building. So the changes of a block
+//! form a hash chain instead: each [`IndexEntry`] is stored under [`record_key`] of its link,
+//! the hash of the previous link and the record, starting from the parent hash. The last link
+//! is kept in `RatingChangesHead` for the block's state, and a reader walks back from it to the
+//! parent hash. Sibling blocks only share keys where they wrote identical entries.
+
+use super::*;
+use frame_support::pallet_prelude::*;
+use sp_runtime::traits::Hash as _;
+use sp_std::vec::Vec;
+
+/// Prefix of every key this module writes.
+pub const INDEX_PREFIX: &[u8] = b"reputation::ratings::";
+
+/// What happened to a rating.
+#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug)]
+pub enum RatingChange {
+    Submitted { score: u8, category_ratings: CategoryScores },
+    Hidden,
+    Unhidden,
+}
+
+/// Who gave a rating.
+#[derive(Clone, Encode, Decode, Eq, PartialEq, Ord, PartialOrd, Hash, RuntimeDebug)]
+pub enum Rater<AccountId> {
+    Account(AccountId),
+    /// An anonymous rater, known only by the nullifier its proof spent.
+    Anonymous([u8; 32]),
+}
+
+/// One change to the rating `from` gave `to`.
+#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug)]
+pub struct RatingRecord<AccountId> {
+    pub to: AccountId,
+    pub from: Rater<AccountId>,
+    pub change: RatingChange,
+    /// Time of the block, in milliseconds since the Unix epoch.
+    #[codec(compact)]
+    pub at: u64,
+}
+
+/// What is stored under a link: the record and the link before it.
+#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug)]
+pub struct IndexEntry<AccountId, Hash> {
+    /// The previous change's link, or the parent hash for a block's first change.
+    pub previous: Hash,
+    pub record: RatingRecord<AccountId>,
+}
+
+/// Key of the [`IndexEntry`] whose link is `link`.
+pub fn record_key<Hash: Encode>(link: &Hash) -> Vec<u8> {
+    let mut key = INDEX_PREFIX.to_vec();
+    link.encode_to(&mut key);
+    key
+}
+
+impl<T: Config> Pallet<T> {
+    /// Write `change` to the offchain index and move `RatingChangesHead` on. The write itself
+    /// is a no-op on nodes without offchain indexing.
+    pub(crate) fn index_rating_change(to: &T::AccountId, from: &T::AccountId, change: RatingChange) {
+        Self::index_change(to, Rater::Account(from.clone()), change)
+    }
+
+    /// Index an anonymous rating of `to`. They cannot be flagged, so are never hidden.
+    pub(crate) fn index_anonymous_rating(to: &T::AccountId, nullifier: [u8; 32], change: RatingChange) {
+        Self::index_change(to, Rater::Anonymous(nullifier), change)
+    }
+
+    fn index_change(to: &T::AccountId, from: Rater<T::AccountId>, change: RatingChange) {
+        let previous =
+            RatingChangesHead::<T>::get().unwrap_or_else(frame_system::Pallet::<T>::parent_hash);
+        let at = T::UnixTime::now().as_millis() as u64;
+        let entry = IndexEntry { previous, record: RatingRecord { to: to.clone(), from, change, at } };
+
+        let link = T::Hashing::hash_of(&entry);
+        sp_io::offchain_index::set(&record_key(&link), &entry.encode());
+        RatingChangesHead::<T>::put(link);
+    }
+}
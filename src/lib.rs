@@ -2,22 +2,106 @@
 
 pub use pallet::*;
 
+pub mod anonymous;
+pub mod content;
+pub mod disclosure;
+pub mod indexing;
+pub mod migrations;
+pub mod offchain;
+mod origins;
+pub mod traits;
+
+pub use origins::EnsureReputation;
+pub use traits::{OnProfileChanged, OnRatingSubmitted, ReputationProvider};
+
+#[cfg(test)]
+mod mock;
+#[cfg(test)]
+mod tests;
+
 #[frame_support::pallet]
 pub mod pallet {
-    use frame_support::pallet_prelude::*;
-    use frame_system::pallet_prelude::*;
+    use frame_support::{
+        pallet_prelude::*,
+        traits::{
+            Currency, ExistenceRequirement, Imbalance, OnUnbalanced, Randomness,
+            ReservableCurrency, UnixTime, WithdrawReasons,
+        },
+        PalletId,
+    };
+    use frame_system::{
+        offchain::{AppCrypto, SendTransactionTypes, SigningTypes},
+        pallet_prelude::*,
+    };
     use scale_info::TypeInfo;
+    use sp_runtime::{
+        traits::{AccountIdConversion, Hash, One, Saturating, Zero},
+        Perbill,
+    };
+    use sp_std::vec::Vec;
+
+    use crate::{
+        anonymous::{self, MAX_PROOF_LEN, MAX_VERIFYING_KEY_LEN},
+        content::ContentRef,
+        disclosure::{self, DisclosureEntry, DisclosureEpoch},
+        indexing::RatingChange,
+        migrations,
+        offchain::ContentReportOf,
+        OnProfileChanged, OnRatingSubmitted,
+    };
+    #[cfg(any(feature = "try-runtime", test))]
+    use sp_runtime::TryRuntimeError;
+
+    /// The in-code storage version.
+    const STORAGE_VERSION: StorageVersion = StorageVersion::new(4);
+
+    /// Upper bound on the raw storage key kept as the lazy migration cursor.
+    pub const MAX_CURSOR_LEN: u32 = 256;
+
+    /// Most deferred blocks whose ratings are published in one block once a migration is done.
+    pub const MAX_DEFERRED_BLOCKS: u32 = 16;
+
+    /// Blocks after the intended one a session's publication can be moved to when that
+    /// block's `SessionsDue` is full.
+    pub const MAX_SESSION_ROLLOVER: u32 = 16;
 
     #[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
-    pub struct ReputationProfile<AccountId> {
+    pub struct ReputationProfile<AccountId, BlockNumber> {
         pub total_score: u32,
         pub review_count: u32,
         pub category_scores: CategoryScores,
-        pub active: bool,
+        pub status: ProfileStatus<BlockNumber>,
         pub staked_amount: u128,
         pub owner: AccountId,
     }
 
+    /// Where a profile stands. Only `Active` profiles can be rated, vote or serve as jurors.
+    #[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+    pub enum ProfileStatus<BlockNumber> {
+        Active,
+        /// Paused by its owner.
+        Paused,
+        /// Held by governance while it looks into the profile.
+        UnderReview,
+        /// Suspended by governance; back to `Active` at `until`.
+        Suspended { until: BlockNumber, reason: SuspensionReason },
+        Banned,
+    }
+
+    impl<BlockNumber> ProfileStatus<BlockNumber> {
+        pub fn is_active(&self) -> bool {
+            matches!(self, ProfileStatus::Active)
+        }
+    }
+
+    #[derive(Clone, Copy, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+    pub enum SuspensionReason {
+        Spam,
+        Abuse,
+        Fraud,
+        Other,
+    }
+
     #[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
     pub struct CategoryScores {
         pub communication: u32,
@@ -27,33 +111,455 @@ pub mod pallet {
     }
 
     #[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
-    pub struct Rating<AccountId> {
+    pub struct Rating<AccountId, Balance> {
         pub from: AccountId,
         pub to: AccountId,
         pub score: u8,
         pub category_ratings: CategoryScores,
         pub timestamp: u64,
-        pub review_hash: [u8; 32], // IPFS hash of detailed review
+        pub review_hash: ContentRef, // multihash or CID of the detailed review
+        pub tip: Balance, // paid to `to` alongside the rating
+    }
+
+    pub type BalanceOf<T> =
+        <<T as Config>::Currency as Currency<<T as frame_system::Config>::AccountId>>::Balance;
+    pub type NegativeImbalanceOf<T> = <<T as Config>::Currency as Currency<
+        <T as frame_system::Config>::AccountId,
+    >>::NegativeImbalance;
+
+    pub type RatingOf<T> = Rating<<T as frame_system::Config>::AccountId, BalanceOf<T>>;
+    pub type ProfileOf<T> = ReputationProfile<<T as frame_system::Config>::AccountId, BlockNumberFor<T>>;
+    pub type StatusOf<T> = ProfileStatus<BlockNumberFor<T>>;
+
+    /// Knobs governance can turn through `set_params` without a runtime upgrade.
+    #[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+    #[cfg_attr(feature = "std", derive(frame_support::Serialize, frame_support::Deserialize))]
+    #[cfg_attr(feature = "std", serde(crate = "frame_support::serde"))]
+    pub struct ReputationParameters<Balance, BlockNumber> {
+        /// Smallest amount accepted by `stake_reputation`.
+        pub min_stake_amount: u128,
+        /// Ratings a single profile can receive.
+        pub max_reviews_per_user: u32,
+        /// Reserved from a rater for as long as their rating stands, and slashed if moderation
+        /// hides it for good.
+        pub rating_deposit: Balance,
+        /// Blocks an account waits between two ratings.
+        pub rating_cooldown: BlockNumber,
+        /// Milliseconds after which a rating's weight in `get_weighted_average_score` halves.
+        /// Zero disables decay.
+        pub decay_half_life: u64,
+    }
+
+    impl<Balance: Zero, BlockNumber: Zero> Default for ReputationParameters<Balance, BlockNumber> {
+        /// Accepts ratings and any non-zero stake, without deposit, cooldown or decay.
+        fn default() -> Self {
+            Self {
+                min_stake_amount: 1,
+                max_reviews_per_user: 10_000,
+                rating_deposit: Zero::zero(),
+                rating_cooldown: Zero::zero(),
+                decay_half_life: 0,
+            }
+        }
+    }
+
+    pub type ParamsOf<T> = ReputationParameters<BalanceOf<T>, BlockNumberFor<T>>;
+
+    /// Funds one account has vouched for another, held in reserve.
+    #[derive(CloneNoBound, Encode, Decode, EqNoBound, PartialEqNoBound, RuntimeDebugNoBound, TypeInfo, MaxEncodedLen)]
+    #[scale_info(skip_type_params(T))]
+    #[codec(mel_bound())]
+    pub struct Vouch<T: Config> {
+        /// Currently backing the target.
+        pub active: BalanceOf<T>,
+        /// `(amount, unlock_at)` chunks withdrawn from `active` but still slashable.
+        pub unbonding: BoundedVec<(BalanceOf<T>, BlockNumberFor<T>), T::MaxUnbondingChunks>,
+    }
+
+    pub type CaseId = u32;
+
+    /// Why a rating was flagged for moderation.
+    #[derive(Clone, Copy, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+    pub enum FlagReason {
+        Spam,
+        Abusive,
+        Fraudulent,
+        ConflictOfInterest,
+        Other,
+    }
+
+    /// A juror's vote on a flagged rating.
+    #[derive(Clone, Copy, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+    pub enum Verdict {
+        Hide,
+        Keep,
+    }
+
+    /// A flagged rating in front of a juror panel.
+    #[derive(CloneNoBound, Encode, Decode, EqNoBound, PartialEqNoBound, RuntimeDebugNoBound, TypeInfo, MaxEncodedLen)]
+    #[scale_info(skip_type_params(T))]
+    #[codec(mel_bound())]
+    pub struct ModerationCase<T: Config> {
+        /// Rated user.
+        pub to: T::AccountId,
+        /// Rater.
+        pub from: T::AccountId,
+        pub jurors: BoundedVec<T::AccountId, T::PanelSize>,
+        /// Last block commitments are accepted in.
+        pub commit_end: BlockNumberFor<T>,
+        /// Last block votes can be revealed in.
+        pub reveal_end: BlockNumberFor<T>,
+    }
+
+    /// What a moderation action changed, kept so a successful appeal can undo it.
+    #[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+    pub enum ModerationKind<AccountId, BlockNumber> {
+        /// A juror panel hid the rating `from` left for `to`.
+        RatingHidden { to: AccountId, from: AccountId },
+        /// Governance moved the profile from `previous` to `applied`.
+        StatusChanged { previous: ProfileStatus<BlockNumber>, applied: ProfileStatus<BlockNumber> },
+        /// `stake_slashed` was taken from the profile's stake.
+        Penalised { stake_slashed: u128 },
+    }
+
+    /// A moderation action that can be appealed by `subject`.
+    #[derive(CloneNoBound, Encode, Decode, EqNoBound, PartialEqNoBound, RuntimeDebugNoBound, TypeInfo, MaxEncodedLen)]
+    #[scale_info(skip_type_params(T))]
+    #[codec(mel_bound())]
+    pub struct ModerationAction<T: Config> {
+        pub subject: T::AccountId,
+        pub kind: ModerationKind<T::AccountId, BlockNumberFor<T>>,
+        pub at: BlockNumberFor<T>,
+    }
+
+    #[derive(CloneNoBound, Encode, Decode, EqNoBound, PartialEqNoBound, RuntimeDebugNoBound, TypeInfo, MaxEncodedLen)]
+    #[scale_info(skip_type_params(T))]
+    #[codec(mel_bound())]
+    pub struct Appeal<T: Config> {
+        pub appellant: T::AccountId,
+        pub evidence_hash: [u8; 32],
+        pub bond: BalanceOf<T>,
+        /// Undecided appeals lapse after this block and the bond is returned.
+        pub deadline: BlockNumberFor<T>,
+    }
+
+    /// On-chain record of a moderation action overturned on appeal.
+    #[derive(CloneNoBound, Encode, Decode, EqNoBound, PartialEqNoBound, RuntimeDebugNoBound, TypeInfo, MaxEncodedLen)]
+    #[scale_info(skip_type_params(T))]
+    #[codec(mel_bound())]
+    pub struct Reversal<T: Config> {
+        pub action: ModerationAction<T>,
+        pub appellant: T::AccountId,
+        pub evidence_hash: [u8; 32],
+        pub at: BlockNumberFor<T>,
+    }
+
+    /// A rating revealed against its commitment, waiting to be published.
+    #[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+    pub struct SealedRating {
+        pub score: u8,
+        pub category_ratings: CategoryScores,
+        pub review_hash: ContentRef,
+    }
+
+    /// A committed rating. The reveal is kept sealed until it is published.
+    #[derive(CloneNoBound, Encode, Decode, EqNoBound, PartialEqNoBound, RuntimeDebugNoBound, TypeInfo, MaxEncodedLen)]
+    #[scale_info(skip_type_params(T))]
+    #[codec(mel_bound())]
+    pub struct RatingCommitment<T: Config> {
+        /// `T::Hashing::hash_of(&(score, [communication, reliability, quality, professionalism], review_hash, salt))`.
+        pub commitment: T::Hash,
+        pub deposit: BalanceOf<T>,
+        /// Block the reveal window closes in, when the rating is published or expires.
+        pub closes_at: BlockNumberFor<T>,
+        pub revealed: Option<SealedRating>,
+    }
+
+    pub type SessionId = u32;
+
+    /// A double-blind review session: both sides rate each other and neither rating is
+    /// published before the other.
+    #[derive(CloneNoBound, Encode, Decode, EqNoBound, PartialEqNoBound, RuntimeDebugNoBound, TypeInfo, MaxEncodedLen)]
+    #[scale_info(skip_type_params(T))]
+    #[codec(mel_bound())]
+    pub struct ReviewSession<T: Config> {
+        pub first: T::AccountId,
+        pub second: T::AccountId,
+        /// Opened by `SessionOrigin` on behalf of an interaction it vouches for.
+        pub verified: bool,
+        /// Block the session is published in even if a side has not rated.
+        pub deadline: BlockNumberFor<T>,
+        /// `first`'s rating of `second`.
+        pub first_rating: Option<SealedRating>,
+        /// `second`'s rating of `first`.
+        pub second_rating: Option<SealedRating>,
+    }
+
+    /// A rating submitted through an anonymous membership proof. It has no `from`.
+    #[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+    pub struct AnonymousRating<AccountId> {
+        pub to: AccountId,
+        pub score: u8,
+        pub category_ratings: CategoryScores,
+        pub timestamp: u64,
+        pub review_hash: ContentRef,
+    }
+
+    /// A content key wrapped for one reader: ChaCha20-Poly1305 under a key derived from x25519
+    /// between the envelope's ephemeral key and `recipient`.
+    #[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+    pub struct WrappedKey {
+        /// x25519 public key of the reader.
+        pub recipient: [u8; 32],
+        /// Encrypted 32-byte content key and its tag.
+        pub ciphertext: [u8; 48],
+    }
+
+    /// How to read an encrypted review. The content behind `review_hash` is encrypted under a
+    /// fresh content key, which is wrapped for each party and, when governance holds one, the
+    /// moderator key.
+    #[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+    pub struct ReviewEnvelope {
+        pub ephemeral: [u8; 32],
+        /// For the rated user.
+        pub to: WrappedKey,
+        /// For the rater.
+        pub from: WrappedKey,
+        pub moderator: Option<WrappedKey>,
+    }
+
+    /// A review body kept on chain under its Blake2-256 hash. Once forgotten, `body` is gone and
+    /// the deposit returned, but the entry stays so the hash still resolves to "erased".
+    #[derive(CloneNoBound, Encode, Decode, EqNoBound, PartialEqNoBound, RuntimeDebugNoBound, TypeInfo, MaxEncodedLen)]
+    #[scale_info(skip_type_params(T))]
+    #[codec(mel_bound())]
+    pub struct StoredContent<T: Config> {
+        pub depositor: T::AccountId,
+        pub deposit: BalanceOf<T>,
+        pub body: Option<BoundedVec<u8, T::MaxContentLen>>,
+    }
+
+    /// What the off-chain worker found behind a rating's `review_hash`.
+    #[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+    pub enum ContentStatus {
+        /// Fetched and hashed to the reference.
+        ContentVerified,
+        /// Not found, or did not hash to the reference.
+        ContentMissing,
+    }
+
+    /// Latest content report on a rating.
+    #[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+    pub struct ContentCheck<BlockNumber> {
+        pub status: ContentStatus,
+        /// Block the reporting worker ran at.
+        pub at: BlockNumber,
     }
 
     #[pallet::pallet]
+    #[pallet::storage_version(STORAGE_VERSION)]
     pub struct Pallet<T>(_);
 
     #[pallet::config]
-    pub trait Config: frame_system::Config {
+    pub trait Config: frame_system::Config + SigningTypes + SendTransactionTypes<Call<Self>> {
         type RuntimeEvent: From<Event<Self>> + IsType<<Self as frame_system::Config>::RuntimeEvent>;
         
+        /// Origin allowed to change `ReputationParams`.
+        type ParamsOrigin: EnsureOrigin<Self::RuntimeOrigin>;
+
+        /// Origin allowed to remove jurors from the pool and to review, suspend or ban profiles.
+        type ModerationOrigin: EnsureOrigin<Self::RuntimeOrigin>;
+
+        /// Notified after every rating is stored. `()` when nothing listens.
+        type OnRatingSubmitted: OnRatingSubmitted<Self::AccountId>;
+
+        /// Notified whenever a profile's score, stake or status changes. `()` when nothing
+        /// listens.
+        type OnProfileChanged: OnProfileChanged<Self::AccountId>;
+
+        /// Multi-block rewrite of `Ratings` run from `on_idle`. `()` when none is pending.
+        type RatingsMigration: migrations::lazy::RatingsMigration<Self>;
+
+        /// Currency vouching bonds are reserved in.
+        type Currency: ReservableCurrency<Self::AccountId>;
+
+        /// Origin allowed to penalise a profile, which slashes everyone vouching for it.
+        type SlashOrigin: EnsureOrigin<Self::RuntimeOrigin>;
+
+        /// Handler for slashed vouching bonds, once the penalty can no longer be reversed, and
+        /// for other forfeited deposits.
+        type Slash: OnUnbalanced<NegativeImbalanceOf<Self>>;
+
+        /// Derives the account that holds slashed vouching bonds while the penalty can still be
+        /// reversed on appeal.
         #[pallet::constant]
-        type MinStakeAmount: Get<u128>;
-        
+        type PalletId: Get<PalletId>;
+
+        #[pallet::constant]
+        type MinVouchAmount: Get<BalanceOf<Self>>;
+
+        #[pallet::constant]
+        type MaxVouchersPerProfile: Get<u32>;
+
+        /// Maximum pending unvouch chunks per voucher and target.
+        #[pallet::constant]
+        type MaxUnbondingChunks: Get<u32>;
+
+        /// Blocks an unvouched amount stays reserved, and slashable, before it can be withdrawn.
+        #[pallet::constant]
+        type VouchUnbondingPeriod: Get<BlockNumberFor<Self>>;
+
+        /// Reviews a profile needs before its owner can vote on the helpfulness of others'.
+        #[pallet::constant]
+        type MinHelpfulnessVoterReviews: Get<u32>;
+
+        /// Source of randomness for drawing juror panels.
+        ///
+        /// Panels are only as fair as this source. `pallet_insecure_randomness_collective_flip`
+        /// mixes recent block hashes, which a block author can bias by choosing what to build or
+        /// whether to publish, so a colluding author can steer who judges a case. Prefer BABE's
+        /// VRF output (`pallet_babe::RandomnessFromOneEpochAgo`) on chains that run BABE.
+        type Randomness: Randomness<Self::Hash, BlockNumberFor<Self>>;
+
+        /// Reserved from a flagger; returned if the rating is hidden, forfeited to the majority
+        /// jurors if it is kept.
+        #[pallet::constant]
+        type FlagDeposit: Get<BalanceOf<Self>>;
+
+        /// Flags that put a rating in front of a juror panel.
+        #[pallet::constant]
+        type FlagThreshold: Get<u32>;
+
+        /// Reserved from every registered juror.
+        #[pallet::constant]
+        type JurorBond: Get<BalanceOf<Self>>;
+
+        /// Minimum average score to serve as a juror.
+        #[pallet::constant]
+        type MinJurorScore: Get<u32>;
+
+        /// Minimum number of reviews to serve as a juror.
+        #[pallet::constant]
+        type MinJurorReviews: Get<u32>;
+
+        #[pallet::constant]
+        type MaxJurors: Get<u32>;
+
+        /// Jurors drawn per case. Fewer are drawn if fewer are eligible.
+        #[pallet::constant]
+        type PanelSize: Get<u32>;
+
+        /// Blocks between a rating reaching `FlagThreshold` and its panel being drawn, so the
+        /// randomness drawn from is not known when the last flag is sent.
+        #[pallet::constant]
+        type JuryDrawDelay: Get<BlockNumberFor<Self>>;
+
+        #[pallet::constant]
+        type CommitPeriod: Get<BlockNumberFor<Self>>;
+
+        #[pallet::constant]
+        type RevealPeriod: Get<BlockNumberFor<Self>>;
+
+        /// Share of the juror bond, and of the juror's reputation stake, slashed from a drawn
+        /// juror who does not reveal a vote.
+        #[pallet::constant]
+        type NoShowSlash: Get<Perbill>;
+
+        /// Suspensions that can expire in the same block.
+        #[pallet::constant]
+        type MaxSuspensionsPerBlock: Get<u32>;
+
+        /// Origin deciding appeals against moderation actions.
+        type AppealOrigin: EnsureOrigin<Self::RuntimeOrigin>;
+
+        /// Reserved from an appellant; returned unless the appeal is rejected.
+        #[pallet::constant]
+        type AppealBond: Get<BalanceOf<Self>>;
+
+        /// Blocks after a moderation action during which it can be appealed.
+        #[pallet::constant]
+        type AppealPeriod: Get<BlockNumberFor<Self>>;
+
+        /// Blocks `AppealOrigin` has to decide an appeal before it lapses.
+        #[pallet::constant]
+        type AppealDecisionPeriod: Get<BlockNumberFor<Self>>;
+
+        /// Appeals waiting for a decision at once.
+        #[pallet::constant]
+        type MaxAppeals: Get<u32>;
+
+        /// Reserved for a rating commitment; returned on reveal.
         #[pallet::constant]
-        type MaxReviewsPerUser: Get<u32>;
+        type RatingCommitDeposit: Get<BalanceOf<Self>>;
+
+        /// Blocks after a commitment during which it can be revealed.
+        #[pallet::constant]
+        type RatingRevealWindow: Get<BlockNumberFor<Self>>;
+
+        /// Share of the deposit slashed when a commitment is never revealed. The rest is
+        /// returned.
+        #[pallet::constant]
+        type UnrevealedSlash: Get<Perbill>;
+
+        /// Reveal windows that can close in the same block.
+        #[pallet::constant]
+        type MaxCommitsPerBlock: Get<u32>;
+
+        /// Origin that opens verified review sessions, e.g. a marketplace or escrow pallet
+        /// attesting that two accounts actually dealt with each other.
+        type SessionOrigin: EnsureOrigin<Self::RuntimeOrigin>;
+
+        /// Blocks a review session stays open for.
+        #[pallet::constant]
+        type SessionDuration: Get<BlockNumberFor<Self>>;
+
+        /// Sessions that can be published in the same block.
+        #[pallet::constant]
+        type MaxSessionsPerBlock: Get<u32>;
+
+        /// Reserved from whoever opens an unverified session until it is published.
+        #[pallet::constant]
+        type SessionDeposit: Get<BalanceOf<Self>>;
+
+        /// Tree hashing and proof verification for anonymous ratings. `()` disables them.
+        type AnonymityVerifier: anonymous::MembershipVerifier;
+
+        /// Blocks per disclosure epoch. The disclosure tree root is committed at the end of every
+        /// block divisible by it.
+        #[pallet::constant]
+        type EpochLength: Get<BlockNumberFor<Self>>;
+
+        /// Reserved for each review body stored on chain, on top of the per-byte deposit.
+        #[pallet::constant]
+        type ContentDepositBase: Get<BalanceOf<Self>>;
+
+        /// Reserved per byte of a review body stored on chain.
+        #[pallet::constant]
+        type ContentDepositPerByte: Get<BalanceOf<Self>>;
+
+        /// Longest review body the chain stores.
+        #[pallet::constant]
+        type MaxContentLen: Get<u32>;
+
+        /// Key content reports are signed with.
+        type ContentReporterId: AppCrypto<Self::Public, Self::Signature>;
+
+        /// Ratings an off-chain worker checks content for per run.
+        #[pallet::constant]
+        type MaxContentChecks: Get<u32>;
+
+        /// Blocks before a checked rating is checked again.
+        #[pallet::constant]
+        type ContentRecheckPeriod: Get<BlockNumberFor<Self>>;
+
+        /// Clock for the times written to the offchain index.
+        type UnixTime: UnixTime;
     }
 
     #[pallet::storage]
     #[pallet::getter(fn user_reputation)]
     pub type UserReputation<T: Config> = 
-        StorageMap<_, Blake2_128Concat, T::AccountId, ReputationProfile<T::AccountId>>;
+        StorageMap<_, Blake2_128Concat, T::AccountId, ProfileOf<T>>;
 
     #[pallet::storage]
     #[pallet::getter(fn ratings)]
@@ -62,7 +568,7 @@ pub mod pallet {
             _,
             Blake2_128Concat, T::AccountId,  // rated user
             Blake2_128Concat, T::AccountId,  // rater
-            Rating<T::AccountId>,
+            RatingOf<T>,
         >;
 
     #[pallet::storage]
@@ -70,35 +576,1678 @@ pub mod pallet {
     pub type RatingCount<T: Config> = 
         StorageMap<_, Blake2_128Concat, T::AccountId, u32, ValueQuery>;
 
-    #[pallet::event]
-    #[pallet::generate_deposit(pub(super) fn deposit_event)]
-    pub enum Event<T: Config> {
-        ProfileCreated { who: T::AccountId },
-        RatingSubmitted { from: T::AccountId, to: T::AccountId, score: u8 },
-        ReputationStaked { who: T::AccountId, amount: u128 },
-        ReputationUnstaked { who: T::AccountId, amount: u128 },
-        ProfileDeactivated { who: T::AccountId },
+    /// `rating_deposit` reserved from a rater, keyed like `Ratings`.
+    #[pallet::storage]
+    #[pallet::getter(fn rating_deposits)]
+    pub type RatingDeposits<T: Config> =
+        StorageDoubleMap<_, Blake2_128Concat, T::AccountId, Blake2_128Concat, T::AccountId, BalanceOf<T>>;
+
+    /// Block of each account's last rating, for `rating_cooldown`.
+    #[pallet::storage]
+    #[pallet::getter(fn last_rated_at)]
+    pub type LastRatedAt<T: Config> = StorageMap<_, Blake2_128Concat, T::AccountId, BlockNumberFor<T>>;
+
+    /// Total tips received alongside ratings, per profile.
+    #[pallet::storage]
+    #[pallet::getter(fn tips_received)]
+    pub type TipsReceived<T: Config> =
+        StorageMap<_, Blake2_128Concat, T::AccountId, BalanceOf<T>, ValueQuery>;
+
+    /// Vouches keyed by (target, voucher).
+    #[pallet::storage]
+    #[pallet::getter(fn vouches)]
+    pub type Vouches<T: Config> =
+        StorageDoubleMap<
+            _,
+            Blake2_128Concat, T::AccountId,  // vouched-for user
+            Blake2_128Concat, T::AccountId,  // voucher
+            Vouch<T>,
+        >;
+
+    /// Total active vouched stake behind a profile, shown as "backed by" next to `staked_amount`.
+    #[pallet::storage]
+    #[pallet::getter(fn backed_by)]
+    pub type BackedBy<T: Config> =
+        StorageMap<_, Blake2_128Concat, T::AccountId, BalanceOf<T>, ValueQuery>;
+
+    #[pallet::storage]
+    #[pallet::getter(fn voucher_count)]
+    pub type VoucherCount<T: Config> =
+        StorageMap<_, Blake2_128Concat, T::AccountId, u32, ValueQuery>;
+
+    /// Net helpfulness (upvotes minus downvotes) of each rating, keyed like `Ratings`.
+    #[pallet::storage]
+    #[pallet::getter(fn helpfulness)]
+    pub type Helpfulness<T: Config> =
+        StorageDoubleMap<
+            _,
+            Blake2_128Concat, T::AccountId,  // rated user
+            Blake2_128Concat, T::AccountId,  // rater
+            i32,
+            ValueQuery,
+        >;
+
+    /// Helpfulness votes keyed by (rated user, rater, voter). `true` is an upvote.
+    #[pallet::storage]
+    pub type HelpfulnessVotes<T: Config> =
+        StorageNMap<
+            _,
+            (
+                NMapKey<Blake2_128Concat, T::AccountId>,  // rated user
+                NMapKey<Blake2_128Concat, T::AccountId>,  // rater
+                NMapKey<Blake2_128Concat, T::AccountId>,  // voter
+            ),
+            bool,
+        >;
+
+    #[pallet::type_value]
+    pub fn DefaultParams<T: Config>() -> ParamsOf<T> {
+        ReputationParameters::default()
     }
 
-    #[pallet::error]
-    pub enum Error<T> {
-        ProfileAlreadyExists,
-        ProfileNotFound,
-        CannotRateSelf,
-        AlreadyRated,
-        InvalidScore,
-        InsufficientStake,
-        ProfileNotActive,
-        TooManyReviews,
+    /// Current pallet parameters, set at genesis and changed through `set_params`. Read as
+    /// `DefaultParams` until then, so ratings are never rejected for a missing value.
+    #[pallet::storage]
+    #[pallet::getter(fn params)]
+    pub type ReputationParams<T: Config> = StorageValue<_, ParamsOf<T>, ValueQuery, DefaultParams<T>>;
+
+    /// Registered jurors. Panels are drawn from the members still eligible at draw time.
+    #[pallet::storage]
+    #[pallet::getter(fn jurors)]
+    pub type Jurors<T: Config> = StorageValue<_, BoundedVec<T::AccountId, T::MaxJurors>, ValueQuery>;
+
+    /// Open cases a juror sits on. Jurors cannot leave the pool while this is non-zero.
+    #[pallet::storage]
+    pub type JurorActiveCases<T: Config> =
+        StorageMap<_, Blake2_128Concat, T::AccountId, u32, ValueQuery>;
+
+    /// Flags keyed by (rated user, rater, flagger).
+    #[pallet::storage]
+    pub type Flags<T: Config> =
+        StorageNMap<
+            _,
+            (
+                NMapKey<Blake2_128Concat, T::AccountId>,  // rated user
+                NMapKey<Blake2_128Concat, T::AccountId>,  // rater
+                NMapKey<Blake2_128Concat, T::AccountId>,  // flagger
+            ),
+            FlagReason,
+        >;
+
+    #[pallet::storage]
+    #[pallet::getter(fn flag_count)]
+    pub type FlagCount<T: Config> =
+        StorageDoubleMap<_, Blake2_128Concat, T::AccountId, Blake2_128Concat, T::AccountId, u32, ValueQuery>;
+
+    #[pallet::storage]
+    pub type NextCaseId<T: Config> = StorageValue<_, CaseId, ValueQuery>;
+
+    /// `(to, from)` of cases whose panel is drawn at a block, keyed by block and case id.
+    #[pallet::storage]
+    pub type JuryDraws<T: Config> = StorageDoubleMap<
+        _,
+        Twox64Concat,
+        BlockNumberFor<T>,
+        Twox64Concat,
+        CaseId,
+        (T::AccountId, T::AccountId),
+        OptionQuery,
+    >;
+
+    #[pallet::storage]
+    #[pallet::getter(fn cases)]
+    pub type Cases<T: Config> = StorageMap<_, Twox64Concat, CaseId, ModerationCase<T>>;
+
+    /// Case currently open against a rating, keyed like `Ratings`.
+    #[pallet::storage]
+    pub type OpenCase<T: Config> =
+        StorageDoubleMap<_, Blake2_128Concat, T::AccountId, Blake2_128Concat, T::AccountId, CaseId>;
+
+    #[pallet::storage]
+    pub type JurorCommits<T: Config> =
+        StorageDoubleMap<_, Twox64Concat, CaseId, Blake2_128Concat, T::AccountId, T::Hash>;
+
+    #[pallet::storage]
+    pub type JurorVotes<T: Config> =
+        StorageDoubleMap<_, Twox64Concat, CaseId, Blake2_128Concat, T::AccountId, Verdict>;
+
+    /// Ratings hidden by moderation, keyed like `Ratings`, with the case that hid them. Hidden
+    /// ratings stay stored but no longer count towards the profile.
+    #[pallet::storage]
+    #[pallet::getter(fn hidden_ratings)]
+    pub type HiddenRatings<T: Config> =
+        StorageDoubleMap<_, Blake2_128Concat, T::AccountId, Blake2_128Concat, T::AccountId, CaseId>;
+
+    /// Profiles whose suspension ends at a block, lifted in that block's `on_initialize`. An
+    /// entry is skipped if the profile's status changed since.
+    #[pallet::storage]
+    pub type SuspensionExpiries<T: Config> = StorageMap<
+        _,
+        Twox64Concat,
+        BlockNumberFor<T>,
+        BoundedVec<T::AccountId, T::MaxSuspensionsPerBlock>,
+        ValueQuery,
+    >;
+
+    /// Appealable moderation actions, keyed by the case id they were announced with.
+    #[pallet::storage]
+    #[pallet::getter(fn moderations)]
+    pub type Moderations<T: Config> = StorageMap<_, Twox64Concat, CaseId, ModerationAction<T>>;
+
+    #[pallet::storage]
+    #[pallet::getter(fn appeals)]
+    pub type Appeals<T: Config> = StorageMap<_, Twox64Concat, CaseId, Appeal<T>>;
+
+    /// Pending appeals in filing order, so deadlines are ascending.
+    #[pallet::storage]
+    pub type AppealQueue<T: Config> = StorageValue<_, BoundedVec<CaseId, T::MaxAppeals>, ValueQuery>;
+
+    /// What each voucher lost to a `Penalised` moderation action, refunded if it is reversed.
+    #[pallet::storage]
+    pub type VoucherSlashes<T: Config> = StorageMap<
+        _,
+        Twox64Concat,
+        CaseId,
+        BoundedVec<(T::AccountId, BalanceOf<T>), T::MaxVouchersPerProfile>,
+        ValueQuery,
+    >;
+
+    /// Moderation actions whose appeal period ends at a block, to forget them then.
+    #[pallet::storage]
+    pub type ModerationsExpiring<T: Config> =
+        StorageDoubleMap<_, Twox64Concat, BlockNumberFor<T>, Twox64Concat, CaseId, (), OptionQuery>;
+
+    /// Moderation actions overturned on appeal.
+    #[pallet::storage]
+    #[pallet::getter(fn reversals)]
+    pub type Reversals<T: Config> = StorageMap<_, Twox64Concat, CaseId, Reversal<T>>;
+
+    /// Committed ratings keyed like `Ratings`.
+    #[pallet::storage]
+    #[pallet::getter(fn rating_commits)]
+    pub type RatingCommits<T: Config> =
+        StorageDoubleMap<_, Blake2_128Concat, T::AccountId, Blake2_128Concat, T::AccountId, RatingCommitment<T>>;
+
+    /// `(to, from)` of commitments whose reveal window closes at a block.
+    #[pallet::storage]
+    pub type CommitWindowsClosing<T: Config> = StorageMap<
+        _,
+        Twox64Concat,
+        BlockNumberFor<T>,
+        BoundedVec<(T::AccountId, T::AccountId), T::MaxCommitsPerBlock>,
+        ValueQuery,
+    >;
+
+    #[pallet::storage]
+    pub type NextSessionId<T: Config> = StorageValue<_, SessionId, ValueQuery>;
+
+    #[pallet::storage]
+    #[pallet::getter(fn sessions)]
+    pub type Sessions<T: Config> = StorageMap<_, Twox64Concat, SessionId, ReviewSession<T>>;
+
+    /// Open session between two accounts, keyed by the lower account first.
+    #[pallet::storage]
+    pub type SessionBetween<T: Config> =
+        StorageDoubleMap<_, Blake2_128Concat, T::AccountId, Blake2_128Concat, T::AccountId, SessionId>;
+
+    /// `SessionDeposit` reserved from the `first` side of an unverified session.
+    #[pallet::storage]
+    pub type SessionDeposits<T: Config> = StorageMap<_, Twox64Concat, SessionId, BalanceOf<T>>;
+
+    /// Sessions to publish at a block, either because both sides rated or the deadline came.
+    #[pallet::storage]
+    pub type SessionsDue<T: Config> = StorageMap<
+        _,
+        Twox64Concat,
+        BlockNumberFor<T>,
+        BoundedVec<SessionId, T::MaxSessionsPerBlock>,
+        ValueQuery,
+    >;
+
+    /// Identity commitment leaf index of each registered anonymous rater.
+    #[pallet::storage]
+    pub type AnonRegistered<T: Config> = StorageMap<_, Blake2_128Concat, T::AccountId, u32>;
+
+    #[pallet::storage]
+    #[pallet::getter(fn anon_tree_size)]
+    pub type AnonTreeSize<T: Config> = StorageValue<_, u32, ValueQuery>;
+
+    /// Rightmost filled node per level of the identity tree, which is all an append needs.
+    #[pallet::storage]
+    pub type AnonFilledSubtrees<T: Config> = StorageMap<_, Twox64Concat, u32, [u8; 32]>;
+
+    /// Most recent identity tree roots, oldest first.
+    #[pallet::storage]
+    #[pallet::getter(fn anon_roots)]
+    pub type AnonRoots<T: Config> =
+        StorageValue<_, BoundedVec<[u8; 32], ConstU32<{ anonymous::ANON_ROOT_HISTORY }>>, ValueQuery>;
+
+    /// Verifying key for anonymous rating proofs. Anonymous ratings are off until it is set.
+    #[pallet::storage]
+    pub type AnonVerifyingKey<T: Config> =
+        StorageValue<_, BoundedVec<u8, ConstU32<MAX_VERIFYING_KEY_LEN>>, OptionQuery>;
+
+    /// Nullifiers already used.
+    #[pallet::storage]
+    pub type Nullifiers<T: Config> = StorageMap<_, Identity, [u8; 32], ()>;
+
+    /// Anonymous ratings keyed by (rated user, nullifier).
+    #[pallet::storage]
+    #[pallet::getter(fn anonymous_ratings)]
+    pub type AnonymousRatings<T: Config> = StorageDoubleMap<
+        _,
+        Blake2_128Concat,
+        T::AccountId,
+        Identity,
+        [u8; 32],
+        AnonymousRating<T::AccountId>,
+    >;
+
+    /// Disclosure tree position and key of each opted-in profile.
+    #[pallet::storage]
+    pub type DisclosureKeys<T: Config> = StorageMap<_, Blake2_128Concat, T::AccountId, DisclosureEntry>;
+
+    #[pallet::storage]
+    #[pallet::getter(fn disclosure_tree_size)]
+    pub type DisclosureTreeSize<T: Config> = StorageValue<_, u32, ValueQuery>;
+
+    /// Disclosure tree nodes keyed by (level, index), leaves at level 0. Missing nodes are empty
+    /// subtrees.
+    #[pallet::storage]
+    pub type DisclosureNodes<T: Config> =
+        StorageDoubleMap<_, Twox64Concat, u32, Twox64Concat, u32, [u8; 32]>;
+
+    /// Committed disclosure tree roots of the last `DISCLOSURE_EPOCH_HISTORY` epochs.
+    #[pallet::storage]
+    #[pallet::getter(fn epoch_roots)]
+    pub type EpochRoots<T: Config> =
+        StorageMap<_, Twox64Concat, u32, DisclosureEpoch<BlockNumberFor<T>>>;
+
+    /// Last epoch committed.
+    #[pallet::storage]
+    #[pallet::getter(fn current_epoch)]
+    pub type CurrentEpoch<T: Config> = StorageValue<_, u32, ValueQuery>;
+
+    /// Verifying key for score proofs. Nothing verifies on chain until it is set.
+    #[pallet::storage]
+    pub type DisclosureVerifyingKey<T: Config> =
+        StorageValue<_, BoundedVec<u8, ConstU32<MAX_VERIFYING_KEY_LEN>>, OptionQuery>;
+
+    /// x25519 public key each profile receives encrypted reviews under.
+    #[pallet::storage]
+    #[pallet::getter(fn encryption_key)]
+    pub type EncryptionKeys<T: Config> = StorageMap<_, Blake2_128Concat, T::AccountId, [u8; 32]>;
+
+    /// Governance-held x25519 key every encrypted review is also readable with.
+    #[pallet::storage]
+    #[pallet::getter(fn moderator_encryption_key)]
+    pub type ModeratorEncryptionKey<T: Config> = StorageValue<_, [u8; 32], OptionQuery>;
+
+    /// Envelopes of encrypted reviews keyed by (rated user, rater).
+    #[pallet::storage]
+    #[pallet::getter(fn review_envelope)]
+    pub type ReviewEnvelopes<T: Config> = StorageDoubleMap<
+        _,
+        Blake2_128Concat,
+        T::AccountId,
+        Blake2_128Concat,
+        T::AccountId,
+        ReviewEnvelope,
+    >;
+
+    /// Review bodies stored on chain, keyed by Blake2-256 of the body.
+    #[pallet::storage]
+    pub type ReviewContent<T: Config> = StorageMap<_, Identity, [u8; 32], StoredContent<T>>;
+
+    /// Ratings whose `review_hash` points into `ReviewContent`, per key, whether or not the body
+    /// is stored yet. A body cannot be removed while any refer to it.
+    #[pallet::storage]
+    pub type ContentReferences<T: Config> = StorageMap<_, Identity, [u8; 32], u32, ValueQuery>;
+
+    /// Accounts whose keys may sign content reports.
+    #[pallet::storage]
+    pub type ContentReporters<T: Config> = StorageMap<_, Blake2_128Concat, T::AccountId, ()>;
+
+    /// Latest content report per rating, keyed by (rated user, rater).
+    #[pallet::storage]
+    #[pallet::getter(fn content_check)]
+    pub type ContentChecks<T: Config> = StorageDoubleMap<
+        _,
+        Blake2_128Concat,
+        T::AccountId,
+        Blake2_128Concat,
+        T::AccountId,
+        ContentCheck<BlockNumberFor<T>>,
+    >;
+
+    /// Link of the last rating change written to the offchain index this block, if any. Reset
+    /// in `on_initialize`; see `indexing`.
+    #[pallet::storage]
+    pub type RatingChangesHead<T: Config> = StorageValue<_, T::Hash, OptionQuery>;
+
+    /// First block whose due ratings were left unpublished because `Ratings` was being
+    /// migrated. Blocks from it on are published, oldest first, once the migration is done.
+    #[pallet::storage]
+    pub type PublishingDeferredFrom<T: Config> = StorageValue<_, BlockNumberFor<T>, OptionQuery>;
+
+    /// Raw key of the last rating rewritten by the running lazy migration, if any.
+    #[pallet::storage]
+    pub type RatingsMigrationCursor<T: Config> =
+        StorageValue<_, BoundedVec<u8, ConstU32<MAX_CURSOR_LEN>>, OptionQuery>;
+
+    #[pallet::genesis_config]
+    pub struct GenesisConfig<T: Config> {
+        /// Initial `ReputationParams`.
+        pub params: ParamsOf<T>,
+        /// Accounts that start with an active profile.
+        pub profiles: Vec<T::AccountId>,
+        /// `(who, amount)` stakes on genesis profiles.
+        pub stakes: Vec<(T::AccountId, u128)>,
+        /// `(from, to, score, [communication, reliability, quality, professionalism])`.
+        pub ratings: Vec<(T::AccountId, T::AccountId, u8, [u8; 4])>,
+        /// Accounts whose keys may sign content reports.
+        pub content_reporters: Vec<T::AccountId>,
+    }
+
+    #[cfg(feature = "std")]
+    impl<T: Config> Default for GenesisConfig<T> {
+        fn default() -> Self {
+            Self {
+                params: Default::default(),
+                profiles: Default::default(),
+                stakes: Default::default(),
+                ratings: Default::default(),
+                content_reporters: Default::default(),
+            }
+        }
+    }
+
+    #[pallet::genesis_build]
+    impl<T: Config> GenesisBuild<T> for GenesisConfig<T> {
+        fn build(&self) {
+            // Goes through the same checks as the extrinsics, so an invalid chain spec fails to
+            // build instead of starting with inconsistent reputation.
+            // Kept above the existential deposit, so slashes held there are never too small to
+            // keep and refund.
+            let pot = Pallet::<T>::account_id();
+            let minimum = T::Currency::minimum_balance();
+            if T::Currency::free_balance(&pot) < minimum {
+                let _ = T::Currency::make_free_balance_be(&pot, minimum);
+            }
+
+            Pallet::<T>::ensure_valid_params(&self.params)
+                .unwrap_or_else(|e| panic!("invalid genesis params {:?}: {:?}", self.params, e));
+            ReputationParams::<T>::put(&self.params);
+
+            for who in &self.profiles {
+                Pallet::<T>::do_create_profile(who.clone())
+                    .unwrap_or_else(|e| panic!("invalid genesis profile {:?}: {:?}", who, e));
+            }
+
+            for (who, amount) in &self.stakes {
+                Pallet::<T>::do_stake(who.clone(), *amount)
+                    .unwrap_or_else(|e| panic!("invalid genesis stake for {:?}: {:?}", who, e));
+            }
+
+            for (from, to, score, [communication, reliability, quality, professionalism]) in &self.ratings {
+                let category_ratings = CategoryScores {
+                    communication: *communication as u32,
+                    reliability: *reliability as u32,
+                    quality: *quality as u32,
+                    professionalism: *professionalism as u32,
+                };
+                let no_review = ContentRef::sha2_256([0u8; 32]);
+                Pallet::<T>::do_submit_rating(from.clone(), to.clone(), *score, category_ratings, no_review, Zero::zero())
+                    .unwrap_or_else(|e| panic!("invalid genesis rating {:?} -> {:?}: {:?}", from, to, e));
+            }
+
+            for who in &self.content_reporters {
+                ContentReporters::<T>::insert(who, ());
+            }
+        }
+    }
+
+    #[pallet::event]
+    #[pallet::generate_deposit(pub(super) fn deposit_event)]
+    pub enum Event<T: Config> {
+        ProfileCreated { who: T::AccountId },
+        RatingSubmitted { from: T::AccountId, to: T::AccountId, score: u8 },
+        RatingTipped { from: T::AccountId, to: T::AccountId, amount: BalanceOf<T> },
+        ReputationStaked { who: T::AccountId, amount: u128 },
+        ReputationUnstaked { who: T::AccountId, amount: u128 },
+        ProfileDeactivated { who: T::AccountId },
+        RatingsMigrated { version: u16 },
+        Vouched { voucher: T::AccountId, target: T::AccountId, amount: BalanceOf<T> },
+        Unvouched { voucher: T::AccountId, target: T::AccountId, amount: BalanceOf<T> },
+        VouchWithdrawn { voucher: T::AccountId, target: T::AccountId, amount: BalanceOf<T> },
+        ProfilePenalised { who: T::AccountId, proportion: Perbill, stake_slashed: u128 },
+        VoucherSlashed { voucher: T::AccountId, target: T::AccountId, amount: BalanceOf<T> },
+        RatingVoted { voter: T::AccountId, to: T::AccountId, from: T::AccountId, helpful: bool, net: i32 },
+        JurorRegistered { who: T::AccountId },
+        JurorUnregistered { who: T::AccountId },
+        RatingFlagged { flagger: T::AccountId, to: T::AccountId, from: T::AccountId, reason: FlagReason },
+        /// A rating reached `FlagThreshold`; its panel is drawn at `draw_at`.
+        CaseFlagged { case_id: CaseId, to: T::AccountId, from: T::AccountId, draw_at: BlockNumberFor<T> },
+        /// A panel was drawn. With no jurors the case is left to `ModerationOrigin`.
+        CaseOpened { case_id: CaseId, to: T::AccountId, from: T::AccountId, jurors: Vec<T::AccountId> },
+        VoteCommitted { case_id: CaseId, juror: T::AccountId },
+        VoteRevealed { case_id: CaseId, juror: T::AccountId, verdict: Verdict },
+        CaseResolved { case_id: CaseId, verdict: Verdict, hide_votes: u32, keep_votes: u32 },
+        JurorRewarded { case_id: CaseId, juror: T::AccountId, amount: BalanceOf<T> },
+        JurorNoShow { case_id: CaseId, juror: T::AccountId, slashed: BalanceOf<T>, stake_slashed: u128 },
+        RatingHidden { to: T::AccountId, from: T::AccountId, case_id: CaseId },
+        ParamsUpdated { old: ParamsOf<T>, new: ParamsOf<T> },
+        JurorRemoved { who: T::AccountId, slashed: BalanceOf<T> },
+        ProfileReactivated { who: T::AccountId },
+        ProfileStatusChanged { who: T::AccountId, status: StatusOf<T> },
+        ModerationRecorded { case_id: CaseId, subject: T::AccountId },
+        AppealFiled { case_id: CaseId, appellant: T::AccountId, evidence_hash: [u8; 32] },
+        AppealRejected { case_id: CaseId, bond_slashed: BalanceOf<T> },
+        AppealLapsed { case_id: CaseId },
+        ModerationReversed { case_id: CaseId, subject: T::AccountId },
+        RatingCommitted { from: T::AccountId, to: T::AccountId, closes_at: BlockNumberFor<T> },
+        RatingRevealed { from: T::AccountId, to: T::AccountId },
+        RatingCommitExpired { from: T::AccountId, to: T::AccountId, slashed: BalanceOf<T> },
+        /// A revealed rating could not be published, e.g. because the profile was paused since.
+        RatingCommitDiscarded { from: T::AccountId, to: T::AccountId, error: DispatchError },
+        SessionOpened { session_id: SessionId, first: T::AccountId, second: T::AccountId, verified: bool, deadline: BlockNumberFor<T> },
+        SessionRatingSealed { session_id: SessionId, from: T::AccountId },
+        SessionPublished { session_id: SessionId, ratings: u8 },
+        SessionRatingDiscarded { session_id: SessionId, from: T::AccountId, to: T::AccountId, error: DispatchError },
+        AnonymousIdentityRegistered { who: T::AccountId, index: u32, root: [u8; 32] },
+        AnonymousRatingSubmitted { to: T::AccountId, nullifier: [u8; 32], score: u8 },
+        AnonymousVerifyingKeySet,
+        DisclosureKeySet { who: T::AccountId, index: u32 },
+        DisclosureKeyCleared { who: T::AccountId },
+        EpochCommitted { epoch: u32, root: [u8; 32] },
+        DisclosureVerifyingKeySet,
+        EncryptionKeySet { who: T::AccountId, key: Option<[u8; 32]> },
+        ModeratorEncryptionKeySet { key: Option<[u8; 32]> },
+        ContentStored { hash: [u8; 32], depositor: T::AccountId, len: u32 },
+        ContentRemoved { hash: [u8; 32] },
+        ContentForgotten { hash: [u8; 32] },
+        ContentReporterAdded { who: T::AccountId },
+        ContentReporterRemoved { who: T::AccountId },
+        ContentChecked { to: T::AccountId, from: T::AccountId, status: ContentStatus },
+        /// A reversed penalty gave a voucher back what it lost.
+        VoucherRefunded { voucher: T::AccountId, target: T::AccountId, amount: BalanceOf<T> },
+        /// A rating stayed hidden past its appeal, so its deposit was slashed.
+        RatingDepositSlashed { to: T::AccountId, from: T::AccountId, amount: BalanceOf<T> },
+    }
+
+    #[pallet::error]
+    pub enum Error<T> {
+        ProfileAlreadyExists,
+        ProfileNotFound,
+        CannotRateSelf,
+        AlreadyRated,
+        InvalidScore,
+        InsufficientStake,
+        ProfileNotActive,
+        TooManyReviews,
+        MigrationInProgress,
+        CannotVouchSelf,
+        InsufficientVouch,
+        TooManyVouchers,
+        NotVouched,
+        TooManyUnbondingChunks,
+        RatingNotFound,
+        AlreadyVoted,
+        CannotVoteOwnRating,
+        NotEnoughReviews,
+        AlreadyJuror,
+        NotJuror,
+        TooManyJurors,
+        NotEligibleJuror,
+        JurorOnActiveCase,
+        AlreadyFlagged,
+        CaseAlreadyOpen,
+        CaseNotFound,
+        NotOnPanel,
+        CommitPhaseOver,
+        NotInRevealPhase,
+        AlreadyCommitted,
+        NoCommitment,
+        AlreadyRevealed,
+        CommitmentMismatch,
+        CaseStillOpen,
+        InvalidParams,
+        InvalidStatusTransition,
+        TooManySuspensions,
+        ModerationNotFound,
+        NotAffected,
+        AppealPeriodOver,
+        AlreadyAppealed,
+        TooManyAppeals,
+        AppealNotFound,
+        RevealWindowClosed,
+        TooManyCommitments,
+        SessionAlreadyOpen,
+        SessionNotFound,
+        NotInSession,
+        SessionClosed,
+        TooManySessions,
+        AlreadyRegistered,
+        AnonymousTreeFull,
+        UnknownRoot,
+        NullifierUsed,
+        InvalidProof,
+        AnonymousRatingsDisabled,
+        NoDisclosureKey,
+        DisclosureTreeFull,
+        NoEncryptionKey,
+        /// An envelope is wrapped for a key other than the one on chain.
+        EnvelopeKeyMismatch,
+        ContentAlreadyStored,
+        ContentNotFound,
+        ContentTooLong,
+        NotDepositor,
+        NotContentReporter,
+        AlreadyContentReporter,
+        InvalidReport,
+        /// The case has a juror panel to decide it.
+        CaseHasPanel,
+        /// No juror could be drawn; `ModerationOrigin` decides the case.
+        AwaitingGovernance,
+        /// No unvouched chunk has finished unbonding yet.
+        NothingToWithdraw,
+        /// The caller rated less than `rating_cooldown` blocks ago.
+        RatingCooldown,
+        /// Ratings refer to the stored body.
+        ContentInUse,
+    }
+
+    #[pallet::hooks]
+    impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+        fn on_initialize(n: BlockNumberFor<T>) -> Weight {
+            // Before anything below can change a rating.
+            RatingChangesHead::<T>::kill();
+
+            T::DbWeight::get()
+                .writes(1)
+                .saturating_add(Self::expire_suspensions(n))
+                .saturating_add(Self::lapse_appeals(n))
+                .saturating_add(Self::expire_moderations(n))
+                .saturating_add(Self::draw_juries(n))
+                .saturating_add(Self::publish_due(n))
+                // `commit_epoch` in `on_finalize`.
+                .saturating_add(T::DbWeight::get().reads_writes(1, 3))
+        }
+
+        fn on_finalize(n: BlockNumberFor<T>) {
+            Self::commit_epoch(n);
+        }
+
+        fn on_idle(_n: BlockNumberFor<T>, remaining_weight: Weight) -> Weight {
+            migrations::lazy::step::<T>(remaining_weight)
+        }
+
+        fn offchain_worker(n: BlockNumberFor<T>) {
+            Self::check_content(n);
+        }
+
+        #[cfg(feature = "try-runtime")]
+        fn try_state(_n: BlockNumberFor<T>) -> Result<(), TryRuntimeError> {
+            Self::do_try_state()
+        }
+    }
+
+    #[pallet::validate_unsigned]
+    impl<T: Config> ValidateUnsigned for Pallet<T> {
+        type Call = Call<T>;
+
+        fn validate_unsigned(_source: TransactionSource, call: &Self::Call) -> TransactionValidity {
+            match call {
+                Call::submit_anonymous_rating {
+                    target,
+                    score,
+                    category_ratings,
+                    review_hash,
+                    root,
+                    nullifier,
+                    proof,
+                } => {
+                    Self::check_anonymous_rating(target, *score, category_ratings, review_hash, root, nullifier, proof)
+                        .map_err(|_| InvalidTransaction::BadProof)?;
+
+                    ValidTransaction::with_tag_prefix("ReputationAnonymousRating")
+                        .priority(TransactionPriority::max_value() / 2)
+                        .and_provides(nullifier)
+                        .longevity(64)
+                        .propagate(true)
+                        .build()
+                },
+                Call::report_content { report, signature } => {
+                    Self::check_content_report(report, signature).map_err(|_| InvalidTransaction::BadProof)?;
+
+                    ValidTransaction::with_tag_prefix("ReputationContentReport")
+                        .priority(TransactionPriority::max_value() / 4)
+                        .and_provides((&report.to, &report.from, report.at))
+                        .longevity(5)
+                        .propagate(true)
+                        .build()
+                },
+                _ => InvalidTransaction::Call.into(),
+            }
+        }
+    }
+
+    #[pallet::call]
+    impl<T: Config> Pallet<T> {
+        #[pallet::weight(10_000)]
+        #[pallet::call_index(0)]
+        pub fn create_profile(origin: OriginFor<T>) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            Self::do_create_profile(who)
+        }
+
+        #[pallet::weight(10_000)]
+        #[pallet::call_index(1)]
+        pub fn submit_rating(
+            origin: OriginFor<T>,
+            target: T::AccountId,
+            score: u8,
+            communication: u8,
+            reliability: u8,
+            quality: u8,
+            professionalism: u8,
+            review_hash: ContentRef,
+        ) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            let category_ratings = CategoryScores {
+                communication: communication as u32,
+                reliability: reliability as u32,
+                quality: quality as u32,
+                professionalism: professionalism as u32,
+            };
+
+            Self::note_rating(&who)?;
+            Self::do_submit_rating(who, target, score, category_ratings, review_hash, Zero::zero())
+        }
+
+        #[pallet::weight(10_000)]
+        #[pallet::call_index(2)]
+        pub fn stake_reputation(origin: OriginFor<T>, amount: u128) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            Self::do_stake(who, amount)
+        }
+
+        #[pallet::weight(10_000)]
+        #[pallet::call_index(3)]
+        pub fn deactivate_profile(origin: OriginFor<T>) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            let mut profile = UserReputation::<T>::get(&who)
+                .ok_or(Error::<T>::ProfileNotFound)?;
+
+            ensure!(profile.status.is_active(), Error::<T>::InvalidStatusTransition);
+            profile.status = ProfileStatus::Paused;
+            UserReputation::<T>::insert(&who, profile);
+            Self::profile_changed(&who);
+
+            Self::deposit_event(Event::ProfileDeactivated { who });
+
+            Ok(())
+        }
+
+        /// Like `submit_rating`, but also transfers `tip` to `target` in the same call.
+        #[pallet::weight(10_000)]
+        #[pallet::call_index(8)]
+        pub fn submit_rating_with_tip(
+            origin: OriginFor<T>,
+            target: T::AccountId,
+            score: u8,
+            communication: u8,
+            reliability: u8,
+            quality: u8,
+            professionalism: u8,
+            review_hash: ContentRef,
+            tip: BalanceOf<T>,
+        ) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            let category_ratings = CategoryScores {
+                communication: communication as u32,
+                reliability: reliability as u32,
+                quality: quality as u32,
+                professionalism: professionalism as u32,
+            };
+
+            Self::note_rating(&who)?;
+            Self::do_submit_rating(who, target, score, category_ratings, review_hash, tip)
+        }
+
+        /// Reserve `amount` of the caller's funds as backing for `target`'s profile.
+        #[pallet::weight(10_000)]
+        #[pallet::call_index(4)]
+        pub fn vouch(origin: OriginFor<T>, target: T::AccountId, amount: BalanceOf<T>) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            ensure!(who != target, Error::<T>::CannotVouchSelf);
+            ensure!(amount >= T::MinVouchAmount::get(), Error::<T>::InsufficientVouch);
+            ensure!(UserReputation::<T>::contains_key(&who), Error::<T>::ProfileNotFound);
+            ensure!(UserReputation::<T>::contains_key(&target), Error::<T>::ProfileNotFound);
+
+            let mut vouch = match Vouches::<T>::get(&target, &who) {
+                Some(vouch) => vouch,
+                None => {
+                    let count = VoucherCount::<T>::get(&target);
+                    ensure!(count < T::MaxVouchersPerProfile::get(), Error::<T>::TooManyVouchers);
+                    VoucherCount::<T>::insert(&target, count + 1);
+                    Vouch { active: Zero::zero(), unbonding: Default::default() }
+                },
+            };
+
+            T::Currency::reserve(&who, amount)?;
+
+            vouch.active = vouch.active.saturating_add(amount);
+            Vouches::<T>::insert(&target, &who, vouch);
+            BackedBy::<T>::mutate(&target, |backed| *backed = backed.saturating_add(amount));
+
+            Self::deposit_event(Event::Vouched { voucher: who, target, amount });
+
+            Ok(())
+        }
+
+        /// Stop backing `target` with `amount`. The funds stay reserved, and slashable, for
+        /// `VouchUnbondingPeriod` blocks before `withdraw_unvouched` releases them.
+        #[pallet::weight(10_000)]
+        #[pallet::call_index(5)]
+        pub fn unvouch(origin: OriginFor<T>, target: T::AccountId, amount: BalanceOf<T>) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            let mut vouch = Vouches::<T>::get(&target, &who).ok_or(Error::<T>::NotVouched)?;
+            ensure!(!amount.is_zero() && vouch.active >= amount, Error::<T>::InsufficientVouch);
+
+            let unlock_at = frame_system::Pallet::<T>::block_number()
+                .saturating_add(T::VouchUnbondingPeriod::get());
+            vouch
+                .unbonding
+                .try_push((amount, unlock_at))
+                .map_err(|_| Error::<T>::TooManyUnbondingChunks)?;
+            vouch.active = vouch.active.saturating_sub(amount);
+
+            Vouches::<T>::insert(&target, &who, vouch);
+            BackedBy::<T>::mutate(&target, |backed| *backed = backed.saturating_sub(amount));
+
+            Self::deposit_event(Event::Unvouched { voucher: who, target, amount });
+
+            Ok(())
+        }
+
+        /// Release every unvouched chunk for `target` whose unbonding period has passed.
+        #[pallet::weight(10_000)]
+        #[pallet::call_index(6)]
+        pub fn withdraw_unvouched(origin: OriginFor<T>, target: T::AccountId) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            let mut vouch = Vouches::<T>::get(&target, &who).ok_or(Error::<T>::NotVouched)?;
+            let now = frame_system::Pallet::<T>::block_number();
+
+            let mut amount: BalanceOf<T> = Zero::zero();
+            vouch.unbonding.retain(|(chunk, unlock_at)| {
+                if *unlock_at <= now {
+                    amount = amount.saturating_add(*chunk);
+                    false
+                } else {
+                    true
+                }
+            });
+            ensure!(!amount.is_zero(), Error::<T>::NothingToWithdraw);
+
+            T::Currency::unreserve(&who, amount);
+
+            if vouch.active.is_zero() && vouch.unbonding.is_empty() {
+                Vouches::<T>::remove(&target, &who);
+                VoucherCount::<T>::mutate(&target, |count| *count = count.saturating_sub(1));
+            } else {
+                Vouches::<T>::insert(&target, &who, vouch);
+            }
+
+            Self::deposit_event(Event::VouchWithdrawn { voucher: who, target, amount });
+
+            Ok(())
+        }
+
+        /// Penalise `target`: cut `proportion` of its stake and of every voucher's bond.
+        #[pallet::weight(10_000)]
+        #[pallet::call_index(7)]
+        pub fn penalise(origin: OriginFor<T>, target: T::AccountId, proportion: Perbill) -> DispatchResult {
+            T::SlashOrigin::ensure_origin(origin)?;
+
+            let (stake_slashed, voucher_slashes) = Self::do_penalise(&target, proportion)?;
+            let case_id = Self::record_moderation(None, target, ModerationKind::Penalised { stake_slashed });
+            VoucherSlashes::<T>::insert(case_id, BoundedVec::truncate_from(voucher_slashes));
+
+            Ok(())
+        }
+
+        /// Mark the rating `from` left for `to` as helpful.
+        #[pallet::weight(10_000)]
+        #[pallet::call_index(9)]
+        pub fn upvote_rating(origin: OriginFor<T>, to: T::AccountId, from: T::AccountId) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            Self::do_vote_rating(who, to, from, true)
+        }
+
+        /// Mark the rating `from` left for `to` as unhelpful.
+        #[pallet::weight(10_000)]
+        #[pallet::call_index(10)]
+        pub fn downvote_rating(origin: OriginFor<T>, to: T::AccountId, from: T::AccountId) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            Self::do_vote_rating(who, to, from, false)
+        }
+
+        /// Join the juror pool, reserving `JurorBond`.
+        #[pallet::weight(10_000)]
+        #[pallet::call_index(11)]
+        pub fn register_juror(origin: OriginFor<T>) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            ensure!(Self::is_eligible_juror(&who), Error::<T>::NotEligibleJuror);
+
+            let mut jurors = Jurors::<T>::get();
+            ensure!(!jurors.contains(&who), Error::<T>::AlreadyJuror);
+            jurors.try_push(who.clone()).map_err(|_| Error::<T>::TooManyJurors)?;
+
+            T::Currency::reserve(&who, T::JurorBond::get())?;
+            Jurors::<T>::put(jurors);
+
+            Self::deposit_event(Event::JurorRegistered { who });
+
+            Ok(())
+        }
+
+        /// Leave the juror pool and release the bond. Not possible while sitting on a case.
+        #[pallet::weight(10_000)]
+        #[pallet::call_index(12)]
+        pub fn unregister_juror(origin: OriginFor<T>) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            ensure!(JurorActiveCases::<T>::get(&who) == 0, Error::<T>::JurorOnActiveCase);
+            Self::do_remove_juror(&who)?;
+            T::Currency::unreserve(&who, T::JurorBond::get());
+
+            Self::deposit_event(Event::JurorUnregistered { who });
+
+            Ok(())
+        }
+
+        /// Flag the rating `from` left for `to`, reserving `FlagDeposit`. The flag that reaches
+        /// `FlagThreshold` opens a case, whose panel is drawn `JuryDrawDelay` blocks later.
+        #[pallet::weight(10_000)]
+        #[pallet::call_index(13)]
+        pub fn flag_rating(
+            origin: OriginFor<T>,
+            to: T::AccountId,
+            from: T::AccountId,
+            reason: FlagReason,
+        ) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            ensure!(UserReputation::<T>::contains_key(&who), Error::<T>::ProfileNotFound);
+            ensure!(Ratings::<T>::contains_key(&to, &from), Error::<T>::RatingNotFound);
+            ensure!(!OpenCase::<T>::contains_key(&to, &from), Error::<T>::CaseAlreadyOpen);
+            ensure!(!Flags::<T>::contains_key((&to, &from, &who)), Error::<T>::AlreadyFlagged);
+
+            T::Currency::reserve(&who, T::FlagDeposit::get())?;
+            Flags::<T>::insert((&to, &from, &who), reason);
+            let count = FlagCount::<T>::mutate(&to, &from, |count| {
+                *count += 1;
+                *count
+            });
+
+            Self::deposit_event(Event::RatingFlagged { flagger: who, to: to.clone(), from: from.clone(), reason });
+
+            if count >= T::FlagThreshold::get() {
+                Self::flag_case(to, from);
+            }
+
+            Ok(())
+        }
+
+        /// Commit to a verdict as `T::Hashing::hash_of(&(verdict, salt))`.
+        #[pallet::weight(10_000)]
+        #[pallet::call_index(14)]
+        pub fn commit_vote(origin: OriginFor<T>, case_id: CaseId, commitment: T::Hash) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            let case = Cases::<T>::get(case_id).ok_or(Error::<T>::CaseNotFound)?;
+            ensure!(case.jurors.contains(&who), Error::<T>::NotOnPanel);
+            ensure!(
+                frame_system::Pallet::<T>::block_number() <= case.commit_end,
+                Error::<T>::CommitPhaseOver
+            );
+            ensure!(!JurorCommits::<T>::contains_key(case_id, &who), Error::<T>::AlreadyCommitted);
+
+            JurorCommits::<T>::insert(case_id, &who, commitment);
+
+            Self::deposit_event(Event::VoteCommitted { case_id, juror: who });
+
+            Ok(())
+        }
+
+        /// Reveal a committed verdict during the reveal phase.
+        #[pallet::weight(10_000)]
+        #[pallet::call_index(15)]
+        pub fn reveal_vote(
+            origin: OriginFor<T>,
+            case_id: CaseId,
+            verdict: Verdict,
+            salt: [u8; 32],
+        ) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            let case = Cases::<T>::get(case_id).ok_or(Error::<T>::CaseNotFound)?;
+            let now = frame_system::Pallet::<T>::block_number();
+            ensure!(now > case.commit_end && now <= case.reveal_end, Error::<T>::NotInRevealPhase);
+            ensure!(!JurorVotes::<T>::contains_key(case_id, &who), Error::<T>::AlreadyRevealed);
+
+            let commitment = JurorCommits::<T>::get(case_id, &who).ok_or(Error::<T>::NoCommitment)?;
+            ensure!(
+                T::Hashing::hash_of(&(verdict, salt)) == commitment,
+                Error::<T>::CommitmentMismatch
+            );
+
+            JurorVotes::<T>::insert(case_id, &who, verdict);
+
+            Self::deposit_event(Event::VoteRevealed { case_id, juror: who, verdict });
+
+            Ok(())
+        }
+
+        /// Apply the majority verdict of a case once its reveal phase is over. Anyone can call.
+        #[pallet::weight(10_000)]
+        #[pallet::call_index(16)]
+        pub fn resolve_case(origin: OriginFor<T>, case_id: CaseId) -> DispatchResult {
+            ensure_signed(origin)?;
+
+            Self::do_resolve_case(case_id)
+        }
+
+        /// Replace `ReputationParams`.
+        #[pallet::weight(10_000)]
+        #[pallet::call_index(17)]
+        pub fn set_params(origin: OriginFor<T>, params: ParamsOf<T>) -> DispatchResult {
+            T::ParamsOrigin::ensure_origin(origin)?;
+
+            Self::ensure_valid_params(&params)?;
+
+            let old = ReputationParams::<T>::get();
+            ReputationParams::<T>::put(&params);
+
+            Self::deposit_event(Event::ParamsUpdated { old, new: params });
+
+            Ok(())
+        }
+
+        /// Remove a misbehaving juror from the pool, slashing their bond. Cases they already
+        /// sit on still count their votes.
+        #[pallet::weight(10_000)]
+        #[pallet::call_index(18)]
+        pub fn remove_juror(origin: OriginFor<T>, who: T::AccountId) -> DispatchResult {
+            T::ModerationOrigin::ensure_origin(origin)?;
+
+            Self::do_remove_juror(&who)?;
+            let (slashed, _) = T::Currency::slash_reserved(&who, T::JurorBond::get());
+            let amount = slashed.peek();
+            T::Slash::on_unbalanced(slashed);
+
+            Self::deposit_event(Event::JurorRemoved { who, slashed: amount });
+
+            Ok(())
+        }
+
+        /// Bring a profile its owner paused back to `Active`.
+        #[pallet::weight(10_000)]
+        #[pallet::call_index(19)]
+        pub fn reactivate_profile(origin: OriginFor<T>) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            let mut profile = UserReputation::<T>::get(&who)
+                .ok_or(Error::<T>::ProfileNotFound)?;
+
+            ensure!(profile.status == ProfileStatus::Paused, Error::<T>::InvalidStatusTransition);
+            profile.status = ProfileStatus::Active;
+            UserReputation::<T>::insert(&who, profile);
+            Self::profile_changed(&who);
+
+            Self::deposit_event(Event::ProfileReactivated { who });
+
+            Ok(())
+        }
+
+        /// Put a profile under review, suspend, ban or restore it. `Paused` is left to the
+        /// owner.
+        #[pallet::weight(10_000)]
+        #[pallet::call_index(20)]
+        pub fn set_profile_status(origin: OriginFor<T>, who: T::AccountId, status: StatusOf<T>) -> DispatchResult {
+            T::ModerationOrigin::ensure_origin(origin)?;
+
+            let previous = Self::do_set_status(&who, status.clone())?;
+            if !status.is_active() {
+                Self::record_moderation(None, who, ModerationKind::StatusChanged { previous, applied: status });
+            }
+
+            Ok(())
+        }
+
+        /// Contest a moderation action taken against the caller, reserving `AppealBond`.
+        #[pallet::weight(10_000)]
+        #[pallet::call_index(21)]
+        pub fn appeal(origin: OriginFor<T>, case_id: CaseId, evidence_hash: [u8; 32]) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            let action = Moderations::<T>::get(case_id).ok_or(Error::<T>::ModerationNotFound)?;
+            ensure!(action.subject == who, Error::<T>::NotAffected);
+            let now = frame_system::Pallet::<T>::block_number();
+            ensure!(now <= action.at.saturating_add(T::AppealPeriod::get()), Error::<T>::AppealPeriodOver);
+            ensure!(!Appeals::<T>::contains_key(case_id), Error::<T>::AlreadyAppealed);
+
+            AppealQueue::<T>::try_append(case_id).map_err(|_| Error::<T>::TooManyAppeals)?;
+            let bond = T::AppealBond::get();
+            T::Currency::reserve(&who, bond)?;
+            Appeals::<T>::insert(
+                case_id,
+                Appeal {
+                    appellant: who.clone(),
+                    evidence_hash,
+                    bond,
+                    deadline: now.saturating_add(T::AppealDecisionPeriod::get()),
+                },
+            );
+
+            Self::deposit_event(Event::AppealFiled { case_id, appellant: who, evidence_hash });
+
+            Ok(())
+        }
+
+        /// Decide a pending appeal. Upholding it reverses the moderation action and returns the
+        /// bond; rejecting it slashes the bond and makes the action final.
+        #[pallet::weight(10_000)]
+        #[pallet::call_index(22)]
+        pub fn decide_appeal(origin: OriginFor<T>, case_id: CaseId, uphold: bool) -> DispatchResult {
+            T::AppealOrigin::ensure_origin(origin)?;
+
+            let appeal = Appeals::<T>::get(case_id).ok_or(Error::<T>::AppealNotFound)?;
+            let action = Moderations::<T>::get(case_id).ok_or(Error::<T>::ModerationNotFound)?;
+
+            if uphold {
+                Self::reverse_moderation(case_id, &action)?;
+                T::Currency::unreserve(&appeal.appellant, appeal.bond);
+                Reversals::<T>::insert(
+                    case_id,
+                    Reversal {
+                        action: action.clone(),
+                        appellant: appeal.appellant,
+                        evidence_hash: appeal.evidence_hash,
+                        at: frame_system::Pallet::<T>::block_number(),
+                    },
+                );
+                Self::deposit_event(Event::ModerationReversed { case_id, subject: action.subject });
+            } else {
+                let (slashed, _) = T::Currency::slash_reserved(&appeal.appellant, appeal.bond);
+                let bond_slashed = slashed.peek();
+                T::Slash::on_unbalanced(slashed);
+                Self::deposit_event(Event::AppealRejected { case_id, bond_slashed });
+            }
+
+            Self::close_moderation(case_id);
+            Appeals::<T>::remove(case_id);
+            AppealQueue::<T>::mutate(|queue| queue.retain(|id| *id != case_id));
+
+            Ok(())
+        }
+
+        /// Commit to a rating of `target` without disclosing it, reserving
+        /// `RatingCommitDeposit`. See `RatingCommitment::commitment` for the preimage.
+        #[pallet::weight(10_000)]
+        #[pallet::call_index(23)]
+        pub fn commit_rating(origin: OriginFor<T>, target: T::AccountId, commitment: T::Hash) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            ensure!(who != target, Error::<T>::CannotRateSelf);
+            ensure!(UserReputation::<T>::contains_key(&target), Error::<T>::ProfileNotFound);
+            ensure!(!Ratings::<T>::contains_key(&target, &who), Error::<T>::AlreadyRated);
+            ensure!(!RatingCommits::<T>::contains_key(&target, &who), Error::<T>::AlreadyCommitted);
+            ensure!(Self::session_between(&who, &target).is_none(), Error::<T>::SessionAlreadyOpen);
+            Self::note_rating(&who)?;
+
+            let closes_at = frame_system::Pallet::<T>::block_number().saturating_add(T::RatingRevealWindow::get());
+            CommitWindowsClosing::<T>::try_append(closes_at, (target.clone(), who.clone()))
+                .map_err(|_| Error::<T>::TooManyCommitments)?;
+
+            let deposit = T::RatingCommitDeposit::get();
+            T::Currency::reserve(&who, deposit)?;
+            RatingCommits::<T>::insert(
+                &target,
+                &who,
+                RatingCommitment { commitment, deposit, closes_at, revealed: None },
+            );
+
+            Self::deposit_event(Event::RatingCommitted { from: who, to: target, closes_at });
+
+            Ok(())
+        }
+
+        /// Reveal a committed rating and get the deposit back. It is published once `target`'s
+        /// own commitment for the caller is revealed too, or when the window closes.
+        #[pallet::weight(10_000)]
+        #[pallet::call_index(24)]
+        pub fn reveal_rating(
+            origin: OriginFor<T>,
+            target: T::AccountId,
+            score: u8,
+            communication: u8,
+            reliability: u8,
+            quality: u8,
+            professionalism: u8,
+            review_hash: ContentRef,
+            salt: [u8; 32],
+        ) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            let mut commit = RatingCommits::<T>::get(&target, &who).ok_or(Error::<T>::NoCommitment)?;
+            ensure!(commit.revealed.is_none(), Error::<T>::AlreadyRevealed);
+            ensure!(
+                frame_system::Pallet::<T>::block_number() < commit.closes_at,
+                Error::<T>::RevealWindowClosed
+            );
+            ensure!(score >= 1 && score <= 5, Error::<T>::InvalidScore);
+
+            let preimage = (score, [communication, reliability, quality, professionalism], &review_hash, salt);
+            ensure!(T::Hashing::hash_of(&preimage) == commit.commitment, Error::<T>::CommitmentMismatch);
+
+            T::Currency::unreserve(&who, commit.deposit);
+            commit.deposit = Zero::zero();
+            commit.revealed = Some(SealedRating {
+                score,
+                category_ratings: CategoryScores {
+                    communication: communication as u32,
+                    reliability: reliability as u32,
+                    quality: quality as u32,
+                    professionalism: professionalism as u32,
+                },
+                review_hash,
+            });
+            RatingCommits::<T>::insert(&target, &who, commit);
+
+            Self::deposit_event(Event::RatingRevealed { from: who.clone(), to: target.clone() });
+
+            // Both sides have revealed: neither can react to the other any more.
+            let counterpart_revealed = RatingCommits::<T>::get(&who, &target)
+                .map_or(false, |commit| commit.revealed.is_some());
+            // Ratings cannot be written mid-migration; the window closing publishes them instead.
+            if counterpart_revealed && !RatingsMigrationCursor::<T>::exists() {
+                Self::publish_commit(&target, &who);
+                Self::publish_commit(&who, &target);
+            }
+
+            Ok(())
+        }
+
+        /// Open a double-blind review session with `counterparty`.
+        #[pallet::weight(10_000)]
+        #[pallet::call_index(25)]
+        pub fn open_session(origin: OriginFor<T>, counterparty: T::AccountId) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            Self::do_open_session(who, counterparty, false).map(|_| ())
+        }
+
+        /// Open a review session for an interaction `SessionOrigin` has verified.
+        #[pallet::weight(10_000)]
+        #[pallet::call_index(26)]
+        pub fn open_verified_session(origin: OriginFor<T>, first: T::AccountId, second: T::AccountId) -> DispatchResult {
+            T::SessionOrigin::ensure_origin(origin)?;
+
+            Self::do_open_session(first, second, true).map(|_| ())
+        }
+
+        /// Rate the other side of a session. The rating stays sealed until the other side has
+        /// rated too or the deadline passes, then both are published together.
+        #[pallet::weight(10_000)]
+        #[pallet::call_index(27)]
+        pub fn submit_session_rating(
+            origin: OriginFor<T>,
+            session_id: SessionId,
+            score: u8,
+            communication: u8,
+            reliability: u8,
+            quality: u8,
+            professionalism: u8,
+            review_hash: ContentRef,
+        ) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            let mut session = Sessions::<T>::get(session_id).ok_or(Error::<T>::SessionNotFound)?;
+            let now = frame_system::Pallet::<T>::block_number();
+            ensure!(now < session.deadline, Error::<T>::SessionClosed);
+            ensure!(score >= 1 && score <= 5, Error::<T>::InvalidScore);
+
+            let slot = if who == session.first {
+                &mut session.first_rating
+            } else if who == session.second {
+                &mut session.second_rating
+            } else {
+                return Err(Error::<T>::NotInSession.into())
+            };
+            ensure!(slot.is_none(), Error::<T>::AlreadyRated);
+            Self::note_rating(&who)?;
+            *slot = Some(SealedRating {
+                score,
+                category_ratings: CategoryScores {
+                    communication: communication as u32,
+                    reliability: reliability as u32,
+                    quality: quality as u32,
+                    professionalism: professionalism as u32,
+                },
+                review_hash,
+            });
+
+            // Both sides are in: publish at the start of the next block with room.
+            if session.first_rating.is_some() && session.second_rating.is_some() {
+                Self::schedule_session(now.saturating_add(One::one()), session_id)?;
+            }
+            Sessions::<T>::insert(session_id, session);
+
+            Self::deposit_event(Event::SessionRatingSealed { session_id, from: who });
+
+            Ok(())
+        }
+
+        /// Add an identity commitment to the anonymous rater tree. One per account.
+        #[pallet::weight(10_000)]
+        #[pallet::call_index(28)]
+        pub fn register_anonymous_identity(origin: OriginFor<T>, commitment: [u8; 32]) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            let profile = UserReputation::<T>::get(&who).ok_or(Error::<T>::ProfileNotFound)?;
+            ensure!(profile.status.is_active(), Error::<T>::ProfileNotActive);
+            ensure!(!AnonRegistered::<T>::contains_key(&who), Error::<T>::AlreadyRegistered);
+
+            let index = AnonTreeSize::<T>::get();
+            Self::insert_identity(commitment)?;
+            AnonRegistered::<T>::insert(&who, index);
+
+            let root = AnonRoots::<T>::get().last().copied().unwrap_or_default();
+            Self::deposit_event(Event::AnonymousIdentityRegistered { who, index, root });
+
+            Ok(())
+        }
+
+        /// Rate `target` without an account. Submitted unsigned; see `anonymous` for the
+        /// proof's public inputs.
+        #[pallet::weight(10_000)]
+        #[pallet::call_index(29)]
+        pub fn submit_anonymous_rating(
+            origin: OriginFor<T>,
+            target: T::AccountId,
+            score: u8,
+            category_ratings: [u8; 4],
+            review_hash: ContentRef,
+            root: [u8; 32],
+            nullifier: [u8; 32],
+            proof: BoundedVec<u8, ConstU32<MAX_PROOF_LEN>>,
+        ) -> DispatchResult {
+            ensure_none(origin)?;
+
+            // `validate_unsigned` also runs right before dispatch, so the proof is checked
+            // against the state this block applies it to.
+            ensure!(!RatingsMigrationCursor::<T>::exists(), Error::<T>::MigrationInProgress);
+            ensure!(!Nullifiers::<T>::contains_key(nullifier), Error::<T>::NullifierUsed);
+
+            let mut profile = UserReputation::<T>::get(&target).ok_or(Error::<T>::ProfileNotFound)?;
+            ensure!(profile.status.is_active(), Error::<T>::ProfileNotActive);
+            let current_count = RatingCount::<T>::get(&target);
+            ensure!(current_count < Self::params().max_reviews_per_user, Error::<T>::TooManyReviews);
+
+            let [communication, reliability, quality, professionalism] = category_ratings.map(|c| c as u32);
+            let category_ratings = CategoryScores { communication, reliability, quality, professionalism };
+
+            profile.total_score += score as u32;
+            profile.review_count += 1;
+            profile.category_scores.communication += communication;
+            profile.category_scores.reliability += reliability;
+            profile.category_scores.quality += quality;
+            profile.category_scores.professionalism += professionalism;
+
+            Nullifiers::<T>::insert(nullifier, ());
+            Self::note_content_reference(&review_hash);
+            AnonymousRatings::<T>::insert(
+                &target,
+                nullifier,
+                AnonymousRating {
+                    to: target.clone(),
+                    score,
+                    category_ratings: category_ratings.clone(),
+                    timestamp: Self::get_timestamp(),
+                    review_hash,
+                },
+            );
+            UserReputation::<T>::insert(&target, profile);
+            RatingCount::<T>::insert(&target, current_count + 1);
+
+            T::OnRatingSubmitted::on_anonymous_rating_submitted(&target, score);
+            Self::profile_changed(&target);
+            Self::index_anonymous_rating(&target, nullifier, RatingChange::Submitted { score, category_ratings });
+
+            Self::deposit_event(Event::AnonymousRatingSubmitted { to: target, nullifier, score });
+
+            Ok(())
+        }
+
+        /// Set the verifying key anonymous rating proofs are checked against.
+        #[pallet::weight(10_000)]
+        #[pallet::call_index(30)]
+        pub fn set_anonymous_verifying_key(
+            origin: OriginFor<T>,
+            verifying_key: BoundedVec<u8, ConstU32<MAX_VERIFYING_KEY_LEN>>,
+        ) -> DispatchResult {
+            T::ParamsOrigin::ensure_origin(origin)?;
+
+            AnonVerifyingKey::<T>::put(verifying_key);
+
+            Self::deposit_event(Event::AnonymousVerifyingKeySet);
+
+            Ok(())
+        }
+
+        /// Opt in to private disclosure with the key of a secret, or rotate the key. The profile's
+        /// aggregates are committed under it from now on.
+        #[pallet::weight(10_000)]
+        #[pallet::call_index(31)]
+        pub fn set_disclosure_key(origin: OriginFor<T>, key: [u8; 32]) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+            ensure!(UserReputation::<T>::contains_key(&who), Error::<T>::ProfileNotFound);
+
+            let index = match DisclosureKeys::<T>::get(&who) {
+                Some(entry) => entry.index,
+                None => {
+                    let size = DisclosureTreeSize::<T>::get();
+                    ensure!(
+                        (size as u64) < 1u64 << disclosure::DISCLOSURE_TREE_DEPTH,
+                        Error::<T>::DisclosureTreeFull
+                    );
+                    DisclosureTreeSize::<T>::put(size + 1);
+                    size
+                },
+            };
+            DisclosureKeys::<T>::insert(&who, DisclosureEntry { index, key });
+            Self::refresh_disclosure_leaf(&who);
+
+            Self::deposit_event(Event::DisclosureKeySet { who, index });
+
+            Ok(())
+        }
+
+        /// Opt out of private disclosure. The leaf is emptied but its index stays taken.
+        #[pallet::weight(10_000)]
+        #[pallet::call_index(32)]
+        pub fn clear_disclosure_key(origin: OriginFor<T>) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            let entry = DisclosureKeys::<T>::take(&who).ok_or(Error::<T>::NoDisclosureKey)?;
+            Self::set_disclosure_leaf(entry.index, [0u8; 32]);
+
+            Self::deposit_event(Event::DisclosureKeyCleared { who });
+
+            Ok(())
+        }
+
+        /// Set the verifying key score proofs are checked against.
+        #[pallet::weight(10_000)]
+        #[pallet::call_index(33)]
+        pub fn set_disclosure_verifying_key(
+            origin: OriginFor<T>,
+            verifying_key: BoundedVec<u8, ConstU32<MAX_VERIFYING_KEY_LEN>>,
+        ) -> DispatchResult {
+            T::ParamsOrigin::ensure_origin(origin)?;
+
+            DisclosureVerifyingKey::<T>::put(verifying_key);
+
+            Self::deposit_event(Event::DisclosureVerifyingKeySet);
+
+            Ok(())
+        }
+
+        /// Publish or clear the x25519 key encrypted reviews of the caller are wrapped for.
+        #[pallet::weight(10_000)]
+        #[pallet::call_index(34)]
+        pub fn set_encryption_key(origin: OriginFor<T>, key: Option<[u8; 32]>) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+            ensure!(UserReputation::<T>::contains_key(&who), Error::<T>::ProfileNotFound);
+
+            match key {
+                Some(key) => EncryptionKeys::<T>::insert(&who, key),
+                None => EncryptionKeys::<T>::remove(&who),
+            }
+
+            Self::deposit_event(Event::EncryptionKeySet { who, key });
+
+            Ok(())
+        }
+
+        /// Set or clear the moderator key. While one is set, every encrypted review must also be
+        /// wrapped for it.
+        #[pallet::weight(10_000)]
+        #[pallet::call_index(35)]
+        pub fn set_moderator_encryption_key(origin: OriginFor<T>, key: Option<[u8; 32]>) -> DispatchResult {
+            T::ModerationOrigin::ensure_origin(origin)?;
+
+            ModeratorEncryptionKey::<T>::set(key);
+
+            Self::deposit_event(Event::ModeratorEncryptionKeySet { key });
+
+            Ok(())
+        }
+
+        /// `submit_rating` for a review whose content is encrypted; `review_hash` is the hash of
+        /// the ciphertext. Both parties need an encryption key.
+        #[pallet::weight(10_000)]
+        #[pallet::call_index(36)]
+        pub fn submit_encrypted_rating(
+            origin: OriginFor<T>,
+            target: T::AccountId,
+            score: u8,
+            communication: u8,
+            reliability: u8,
+            quality: u8,
+            professionalism: u8,
+            review_hash: ContentRef,
+            envelope: ReviewEnvelope,
+        ) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            let target_key = EncryptionKeys::<T>::get(&target).ok_or(Error::<T>::NoEncryptionKey)?;
+            let own_key = EncryptionKeys::<T>::get(&who).ok_or(Error::<T>::NoEncryptionKey)?;
+            ensure!(
+                envelope.to.recipient == target_key && envelope.from.recipient == own_key,
+                Error::<T>::EnvelopeKeyMismatch
+            );
+            let moderator_wrapped = match (ModeratorEncryptionKey::<T>::get(), &envelope.moderator) {
+                (Some(key), Some(wrapped)) => wrapped.recipient == key,
+                (None, None) => true,
+                _ => false,
+            };
+            ensure!(moderator_wrapped, Error::<T>::EnvelopeKeyMismatch);
+
+            let category_ratings = CategoryScores {
+                communication: communication as u32,
+                reliability: reliability as u32,
+                quality: quality as u32,
+                professionalism: professionalism as u32,
+            };
+
+            Self::note_rating(&who)?;
+            Self::do_submit_rating(who.clone(), target.clone(), score, category_ratings, review_hash, Zero::zero())?;
+            ReviewEnvelopes::<T>::insert(&target, &who, envelope);
+
+            Ok(())
+        }
+
+        /// Store a review body on chain, reserving a deposit for its size. Rate with
+        /// `ContentRef::blake2_256` of its hash as `review_hash`.
+        #[pallet::weight(10_000)]
+        #[pallet::call_index(37)]
+        pub fn store_review_content(origin: OriginFor<T>, body: Vec<u8>) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            let len = body.len() as u32;
+            let body: BoundedVec<u8, T::MaxContentLen> =
+                body.try_into().map_err(|_| Error::<T>::ContentTooLong)?;
+            let hash = sp_io::hashing::blake2_256(&body);
+            ensure!(!ReviewContent::<T>::contains_key(hash), Error::<T>::ContentAlreadyStored);
+
+            let deposit = T::ContentDepositBase::get()
+                .saturating_add(T::ContentDepositPerByte::get().saturating_mul(len.into()));
+            T::Currency::reserve(&who, deposit)?;
+            ReviewContent::<T>::insert(hash, StoredContent { depositor: who.clone(), deposit, body: Some(body) });
+
+            Self::deposit_event(Event::ContentStored { hash, depositor: who, len });
+
+            Ok(())
+        }
+
+        /// Remove a body the caller stored and get the deposit back, unless a rating refers to it.
+        #[pallet::weight(10_000)]
+        #[pallet::call_index(38)]
+        pub fn remove_review_content(origin: OriginFor<T>, hash: [u8; 32]) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            let content = ReviewContent::<T>::get(hash).ok_or(Error::<T>::ContentNotFound)?;
+            ensure!(content.depositor == who, Error::<T>::NotDepositor);
+            ensure!(ContentReferences::<T>::get(hash) == 0, Error::<T>::ContentInUse);
+
+            T::Currency::unreserve(&who, content.deposit);
+            ReviewContent::<T>::remove(hash);
+
+            Self::deposit_event(Event::ContentRemoved { hash });
+
+            Ok(())
+        }
+
+        /// Erase a stored body for good, keeping its hash. The deposit goes back to whoever
+        /// stored it.
+        #[pallet::weight(10_000)]
+        #[pallet::call_index(39)]
+        pub fn forget_review_content(origin: OriginFor<T>, hash: [u8; 32]) -> DispatchResult {
+            T::ModerationOrigin::ensure_origin(origin)?;
+
+            ReviewContent::<T>::try_mutate(hash, |content| -> DispatchResult {
+                let content = content.as_mut().ok_or(Error::<T>::ContentNotFound)?;
+                ensure!(content.body.is_some(), Error::<T>::ContentNotFound);
+
+                T::Currency::unreserve(&content.depositor, content.deposit);
+                content.deposit = Zero::zero();
+                content.body = None;
+
+                Ok(())
+            })?;
+
+            Self::deposit_event(Event::ContentForgotten { hash });
+
+            Ok(())
+        }
+
+        /// Let `who`'s keys sign content reports.
+        #[pallet::weight(10_000)]
+        #[pallet::call_index(40)]
+        pub fn add_content_reporter(origin: OriginFor<T>, who: T::AccountId) -> DispatchResult {
+            T::ModerationOrigin::ensure_origin(origin)?;
+            ensure!(!ContentReporters::<T>::contains_key(&who), Error::<T>::AlreadyContentReporter);
+
+            ContentReporters::<T>::insert(&who, ());
+
+            Self::deposit_event(Event::ContentReporterAdded { who });
+
+            Ok(())
+        }
+
+        #[pallet::weight(10_000)]
+        #[pallet::call_index(41)]
+        pub fn remove_content_reporter(origin: OriginFor<T>, who: T::AccountId) -> DispatchResult {
+            T::ModerationOrigin::ensure_origin(origin)?;
+            ensure!(ContentReporters::<T>::contains_key(&who), Error::<T>::NotContentReporter);
+
+            ContentReporters::<T>::remove(&who);
+
+            Self::deposit_event(Event::ContentReporterRemoved { who });
+
+            Ok(())
+        }
+
+        /// Record what an off-chain worker found behind a rating's `review_hash`. Submitted
+        /// unsigned with a payload signed by a content reporter; see `offchain`.
+        #[pallet::weight(10_000)]
+        #[pallet::call_index(42)]
+        pub fn report_content(
+            origin: OriginFor<T>,
+            report: ContentReportOf<T>,
+            signature: T::Signature,
+        ) -> DispatchResult {
+            ensure_none(origin)?;
+            Self::check_content_report(&report, &signature)?;
+
+            ContentChecks::<T>::insert(
+                &report.to,
+                &report.from,
+                ContentCheck { status: report.status.clone(), at: report.at },
+            );
+
+            Self::deposit_event(Event::ContentChecked { to: report.to, from: report.from, status: report.status });
+
+            Ok(())
+        }
+
+        /// Decide a case no juror could be drawn for.
+        #[pallet::weight(10_000)]
+        #[pallet::call_index(43)]
+        pub fn decide_case(origin: OriginFor<T>, case_id: CaseId, verdict: Verdict) -> DispatchResult {
+            T::ModerationOrigin::ensure_origin(origin)?;
+
+            let case = Cases::<T>::get(case_id).ok_or(Error::<T>::CaseNotFound)?;
+            ensure!(case.jurors.is_empty(), Error::<T>::CaseHasPanel);
+
+            Self::close_case(case_id, case, verdict, 0, 0)
+        }
     }
 
-    #[pallet::call]
     impl<T: Config> Pallet<T> {
-        #[pallet::weight(10_000)]
-        #[pallet::call_index(0)]
-        pub fn create_profile(origin: OriginFor<T>) -> DispatchResult {
-            let who = ensure_signed(origin)?;
+        /// Checks `set_params` and genesis apply to new parameters.
+        pub(crate) fn ensure_valid_params(params: &ParamsOf<T>) -> DispatchResult {
+            ensure!(params.max_reviews_per_user > 0, Error::<T>::InvalidParams);
+            Ok(())
+        }
+
+        /// Milliseconds since the Unix epoch, as stored in `Rating::timestamp`.
+        fn get_timestamp() -> u64 {
+            T::UnixTime::now().as_millis() as u64
+        }
+
+        /// Enforce `rating_cooldown` on `who` and start it again. Called where an account
+        /// decides to rate, not where the rating is eventually published.
+        fn note_rating(who: &T::AccountId) -> DispatchResult {
+            let cooldown = Self::params().rating_cooldown;
+            if cooldown.is_zero() {
+                return Ok(())
+            }
+
+            let now = frame_system::Pallet::<T>::block_number();
+            if let Some(last) = LastRatedAt::<T>::get(who) {
+                ensure!(now >= last.saturating_add(cooldown), Error::<T>::RatingCooldown);
+            }
+            LastRatedAt::<T>::insert(who, now);
+
+            Ok(())
+        }
 
+        pub(crate) fn do_create_profile(who: T::AccountId) -> DispatchResult {
             ensure!(!UserReputation::<T>::contains_key(&who), Error::<T>::ProfileAlreadyExists);
 
             let profile = ReputationProfile {
@@ -110,114 +2259,997 @@ pub mod pallet {
                     quality: 0,
                     professionalism: 0,
                 },
-                active: true,
+                status: ProfileStatus::Active,
                 staked_amount: 0,
                 owner: who.clone(),
             };
 
             UserReputation::<T>::insert(&who, profile);
+            Self::profile_changed(&who);
             Self::deposit_event(Event::ProfileCreated { who });
 
             Ok(())
         }
 
-        #[pallet::weight(10_000)]
-        #[pallet::call_index(1)]
-        pub fn submit_rating(
-            origin: OriginFor<T>,
+        pub(crate) fn do_submit_rating(
+            who: T::AccountId,
             target: T::AccountId,
             score: u8,
-            communication: u8,
-            reliability: u8,
-            quality: u8,
-            professionalism: u8,
-            review_hash: [u8; 32],
+            category_ratings: CategoryScores,
+            review_hash: ContentRef,
+            tip: BalanceOf<T>,
         ) -> DispatchResult {
-            let who = ensure_signed(origin)?;
-
             ensure!(who != target, Error::<T>::CannotRateSelf);
             ensure!(score >= 1 && score <= 5, Error::<T>::InvalidScore);
+            ensure!(!RatingsMigrationCursor::<T>::exists(), Error::<T>::MigrationInProgress);
             ensure!(UserReputation::<T>::contains_key(&target), Error::<T>::ProfileNotFound);
             ensure!(!Ratings::<T>::contains_key(&target, &who), Error::<T>::AlreadyRated);
+            ensure!(!RatingCommits::<T>::contains_key(&target, &who), Error::<T>::AlreadyCommitted);
+            ensure!(Self::session_between(&who, &target).is_none(), Error::<T>::SessionAlreadyOpen);
 
             let mut profile = UserReputation::<T>::get(&target)
                 .ok_or(Error::<T>::ProfileNotFound)?;
 
-            ensure!(profile.active, Error::<T>::ProfileNotActive);
+            ensure!(profile.status.is_active(), Error::<T>::ProfileNotActive);
 
             let current_count = RatingCount::<T>::get(&target);
-            ensure!(current_count < T::MaxReviewsPerUser::get(), Error::<T>::TooManyReviews);
+            ensure!(current_count < Self::params().max_reviews_per_user, Error::<T>::TooManyReviews);
 
             // Update reputation
             profile.total_score += score as u32;
             profile.review_count += 1;
-            profile.category_scores.communication += communication as u32;
-            profile.category_scores.reliability += reliability as u32;
-            profile.category_scores.quality += quality as u32;
-            profile.category_scores.professionalism += professionalism as u32;
+            profile.category_scores.communication += category_ratings.communication;
+            profile.category_scores.reliability += category_ratings.reliability;
+            profile.category_scores.quality += category_ratings.quality;
+            profile.category_scores.professionalism += category_ratings.professionalism;
 
             let rating = Rating {
                 from: who.clone(),
                 to: target.clone(),
                 score,
-                category_ratings: CategoryScores {
-                    communication: communication as u32,
-                    reliability: reliability as u32,
-                    quality: quality as u32,
-                    professionalism: professionalism as u32,
-                },
+                category_ratings,
                 timestamp: Self::get_timestamp(),
                 review_hash,
+                tip,
             };
 
-            Ratings::<T>::insert(&target, &who, rating);
+            // Genesis ratings are free: their raters need not be endowed.
+            if !frame_system::Pallet::<T>::block_number().is_zero() {
+                let deposit = Self::params().rating_deposit;
+                if !deposit.is_zero() {
+                    T::Currency::reserve(&who, deposit)?;
+                    RatingDeposits::<T>::insert(&target, &who, deposit);
+                }
+            }
+
+            if !tip.is_zero() {
+                T::Currency::transfer(&who, &target, tip, ExistenceRequirement::KeepAlive)?;
+                TipsReceived::<T>::mutate(&target, |total| *total = total.saturating_add(tip));
+            }
+
+            Self::note_content_reference(&rating.review_hash);
+            Ratings::<T>::insert(&target, &who, &rating);
             UserReputation::<T>::insert(&target, profile);
             RatingCount::<T>::insert(&target, current_count + 1);
 
-            Self::deposit_event(Event::RatingSubmitted { from: who, to: target, score });
+            T::OnRatingSubmitted::on_rating_submitted(&who, &target, score);
+            Self::profile_changed(&target);
+            let change = RatingChange::Submitted { score, category_ratings: rating.category_ratings };
+            Self::index_rating_change(&target, &who, change);
+
+            Self::deposit_event(Event::RatingSubmitted { from: who.clone(), to: target.clone(), score });
+            if !tip.is_zero() {
+                Self::deposit_event(Event::RatingTipped { from: who, to: target, amount: tip });
+            }
 
             Ok(())
         }
 
-        #[pallet::weight(10_000)]
-        #[pallet::call_index(2)]
-        pub fn stake_reputation(origin: OriginFor<T>, amount: u128) -> DispatchResult {
-            let who = ensure_signed(origin)?;
-
-            ensure!(amount >= T::MinStakeAmount::get(), Error::<T>::InsufficientStake);
+        pub(crate) fn do_stake(who: T::AccountId, amount: u128) -> DispatchResult {
+            ensure!(amount >= Self::params().min_stake_amount, Error::<T>::InsufficientStake);
 
             let mut profile = UserReputation::<T>::get(&who)
                 .ok_or(Error::<T>::ProfileNotFound)?;
 
             profile.staked_amount += amount;
             UserReputation::<T>::insert(&who, profile);
+            Self::profile_changed(&who);
 
             Self::deposit_event(Event::ReputationStaked { who, amount });
 
             Ok(())
         }
 
-        #[pallet::weight(10_000)]
-        #[pallet::call_index(3)]
-        pub fn deactivate_profile(origin: OriginFor<T>) -> DispatchResult {
-            let who = ensure_signed(origin)?;
-
-            let mut profile = UserReputation::<T>::get(&who)
+        /// Slash `proportion` of `target`'s stake and of every active and unbonding vouch for
+        /// it, so vouching for someone who is later penalised has a cost.
+        ///
+        /// The vouchers' losses are held in `account_id` until `close_moderation` or a reversal
+        /// settles them. Returns how much of the profile's own stake was slashed, and how much
+        /// each voucher actually lost.
+        pub fn do_penalise(
+            target: &T::AccountId,
+            proportion: Perbill,
+        ) -> Result<(u128, Vec<(T::AccountId, BalanceOf<T>)>), DispatchError> {
+            let mut profile = UserReputation::<T>::get(target)
                 .ok_or(Error::<T>::ProfileNotFound)?;
 
-            profile.active = false;
-            UserReputation::<T>::insert(&who, profile);
+            let stake_slashed = proportion * profile.staked_amount;
+            profile.staked_amount -= stake_slashed;
+            UserReputation::<T>::insert(target, profile);
+            Self::profile_changed(target);
 
-            Self::deposit_event(Event::ProfileDeactivated { who });
+            // Bounded by `MaxVouchersPerProfile`.
+            let vouches: Vec<_> = Vouches::<T>::iter_prefix(target).collect();
+            let mut voucher_slashes = Vec::with_capacity(vouches.len());
+            let mut held = NegativeImbalanceOf::<T>::zero();
+            for (voucher, mut vouch) in vouches {
+                let active_slash = proportion * vouch.active;
+                vouch.active = vouch.active.saturating_sub(active_slash);
+
+                let mut slashed = active_slash;
+                for (chunk, _) in vouch.unbonding.iter_mut() {
+                    let chunk_slash = proportion * *chunk;
+                    *chunk = chunk.saturating_sub(chunk_slash);
+                    slashed = slashed.saturating_add(chunk_slash);
+                }
+
+                let (imbalance, _) = T::Currency::slash_reserved(&voucher, slashed);
+                voucher_slashes.push((voucher.clone(), imbalance.peek()));
+                held.subsume(imbalance);
+
+                Vouches::<T>::insert(target, &voucher, vouch);
+                BackedBy::<T>::mutate(target, |backed| *backed = backed.saturating_sub(active_slash));
+
+                Self::deposit_event(Event::VoucherSlashed {
+                    voucher,
+                    target: target.clone(),
+                    amount: slashed,
+                });
+            }
+
+            T::Currency::resolve_creating(&Self::account_id(), held);
+
+            Self::deposit_event(Event::ProfilePenalised { who: target.clone(), proportion, stake_slashed });
+
+            Ok((stake_slashed, voucher_slashes))
+        }
+
+        pub(crate) fn do_vote_rating(
+            who: T::AccountId,
+            to: T::AccountId,
+            from: T::AccountId,
+            helpful: bool,
+        ) -> DispatchResult {
+            // Neither side of a rating gets a say in how helpful it is.
+            ensure!(who != to && who != from, Error::<T>::CannotVoteOwnRating);
+            ensure!(Ratings::<T>::contains_key(&to, &from), Error::<T>::RatingNotFound);
+            ensure!(
+                !HelpfulnessVotes::<T>::contains_key((&to, &from, &who)),
+                Error::<T>::AlreadyVoted
+            );
+
+            let voter = UserReputation::<T>::get(&who).ok_or(Error::<T>::ProfileNotFound)?;
+            ensure!(voter.status.is_active(), Error::<T>::ProfileNotActive);
+            ensure!(
+                voter.review_count >= T::MinHelpfulnessVoterReviews::get(),
+                Error::<T>::NotEnoughReviews
+            );
+
+            HelpfulnessVotes::<T>::insert((&to, &from, &who), helpful);
+            let net = Helpfulness::<T>::mutate(&to, &from, |net| {
+                *net = if helpful { net.saturating_add(1) } else { net.saturating_sub(1) };
+                *net
+            });
+
+            Self::deposit_event(Event::RatingVoted { voter: who, to, from, helpful, net });
 
             Ok(())
         }
-    }
 
-    impl<T: Config> Pallet<T> {
-        fn get_timestamp() -> u64 {
-            // In production, use pallet_timestamp
-            0
+        /// Weight of a rating in `get_weighted_average_score`: a base of 10, moved by one per
+        /// net helpfulness vote and clamped to `1..=50`.
+        pub fn rating_weight(to: &T::AccountId, from: &T::AccountId) -> u32 {
+            const BASE: i32 = 10;
+            (BASE + Helpfulness::<T>::get(to, from)).clamp(1, 5 * BASE) as u32
+        }
+
+        /// `weight` after `age` milliseconds of halving every `half_life`, interpolating
+        /// linearly within a half-life. Never decays to zero, so an average stays defined.
+        fn decayed(weight: u64, age: u64, half_life: u64) -> u64 {
+            if half_life == 0 {
+                return weight
+            }
+            let halvings = age / half_life;
+            if halvings >= 64 {
+                return 1
+            }
+            let weight = weight >> halvings;
+            let into_next = (weight / 2).saturating_mul(age % half_life) / half_life;
+            weight.saturating_sub(into_next).max(1)
+        }
+
+        /// Average score weighted by helpfulness and, with `decay_half_life` set, by age, in
+        /// hundredths (450 is 4.5 stars). Ratings without a timestamp do not decay.
+        pub fn get_weighted_average_score(who: &T::AccountId) -> Option<u32> {
+            if !UserReputation::<T>::contains_key(who) {
+                return None
+            }
+
+            // Scaled up so decayed weights keep their precision.
+            const SCALE: u64 = 1_000_000;
+            let half_life = Self::params().decay_half_life;
+            let now = Self::get_timestamp();
+
+            let (weighted, total_weight) = Ratings::<T>::iter_prefix(who)
+                .filter(|(from, _)| !HiddenRatings::<T>::contains_key(who, from))
+                .fold((0u64, 0u64), |(weighted, total), (from, rating)| {
+                    let weight = Self::rating_weight(who, &from) as u64 * SCALE;
+                    let weight = if rating.timestamp == 0 {
+                        weight
+                    } else {
+                        Self::decayed(weight, now.saturating_sub(rating.timestamp), half_life)
+                    };
+                    (weighted + weight * rating.score as u64 * 100, total + weight)
+                });
+
+            Some(if total_weight == 0 { 0 } else { (weighted / total_weight) as u32 })
+        }
+
+        /// Raters of `who`, most helpful review first.
+        pub fn ratings_by_helpfulness(who: &T::AccountId) -> Vec<(T::AccountId, i32)> {
+            let mut raters: Vec<_> = Ratings::<T>::iter_key_prefix(who)
+                .filter(|from| !HiddenRatings::<T>::contains_key(who, from))
+                .map(|from| {
+                    let net = Helpfulness::<T>::get(who, &from);
+                    (from, net)
+                })
+                .collect();
+            raters.sort_by(|a, b| b.1.cmp(&a.1));
+            raters
+        }
+
+        /// Whether `who` has an `Active` profile averaging at least `min_score` stars over at
+        /// least `min_reviews` reviews, with at least `min_stake` staked.
+        pub fn meets_reputation(who: &T::AccountId, min_score: u32, min_reviews: u32, min_stake: u128) -> bool {
+            UserReputation::<T>::get(who).map_or(false, |profile| {
+                profile.status.is_active() &&
+                    profile.review_count >= min_reviews &&
+                    profile.total_score >= min_score.saturating_mul(profile.review_count) &&
+                    (min_score == 0 || profile.review_count > 0) &&
+                    profile.staked_amount >= min_stake
+            })
+        }
+
+        /// Whether `who` currently meets the juror reputation requirements.
+        pub fn is_eligible_juror(who: &T::AccountId) -> bool {
+            Self::meets_reputation(who, T::MinJurorScore::get(), T::MinJurorReviews::get().max(1), 0)
+        }
+
+        fn do_remove_juror(who: &T::AccountId) -> DispatchResult {
+            Jurors::<T>::try_mutate(|jurors| -> DispatchResult {
+                let index = jurors.iter().position(|j| j == who).ok_or(Error::<T>::NotJuror)?;
+                jurors.swap_remove(index);
+                Ok(())
+            })
+        }
+
+        /// Open a case against a rating and schedule its panel draw.
+        fn flag_case(to: T::AccountId, from: T::AccountId) {
+            let case_id = NextCaseId::<T>::get();
+            NextCaseId::<T>::put(case_id.wrapping_add(1));
+            OpenCase::<T>::insert(&to, &from, case_id);
+
+            let delay = T::JuryDrawDelay::get().max(One::one());
+            let draw_at = frame_system::Pallet::<T>::block_number().saturating_add(delay);
+            JuryDraws::<T>::insert(draw_at, case_id, (to.clone(), from.clone()));
+
+            Self::deposit_event(Event::CaseFlagged { case_id, to, from, draw_at });
+        }
+
+        /// Draw the panels scheduled for `n`.
+        fn draw_juries(n: BlockNumberFor<T>) -> Weight {
+            let db = T::DbWeight::get();
+            let mut weight = Weight::zero();
+
+            for (case_id, (to, from)) in JuryDraws::<T>::drain_prefix(n) {
+                // The pool, flags and eligibility of each juror are read.
+                weight = weight.saturating_add(db.reads_writes(
+                    2u64.saturating_add(2 * T::MaxJurors::get() as u64),
+                    3u64.saturating_add(T::PanelSize::get() as u64),
+                ));
+                Self::open_case(case_id, to, from);
+            }
+
+            weight
+        }
+
+        /// Draw up to `PanelSize` eligible jurors, excluding both sides of the rating and its
+        /// flaggers, and start the case's commit phase. If nobody is eligible the panel is
+        /// empty and the case waits for `decide_case`.
+        fn open_case(case_id: CaseId, to: T::AccountId, from: T::AccountId) {
+            let mut pool: Vec<T::AccountId> = Jurors::<T>::get()
+                .into_iter()
+                .filter(|juror| {
+                    *juror != to &&
+                        *juror != from &&
+                        !Flags::<T>::contains_key((&to, &from, juror)) &&
+                        Self::is_eligible_juror(juror)
+                })
+                .collect();
+
+            let panel_size = pool.len().min(T::PanelSize::get() as usize);
+            let (seed, _) = T::Randomness::random(&(b"reputation/jury", case_id).encode());
+
+            // Partial Fisher-Yates: the first `panel_size` entries end up a uniform sample.
+            for i in 0..panel_size {
+                let draw = T::Hashing::hash_of(&(seed, i as u32));
+                let mut bytes = [0u8; 4];
+                bytes.copy_from_slice(&draw.as_ref()[..4]);
+                let j = i + (u32::from_le_bytes(bytes) as usize) % (pool.len() - i);
+                pool.swap(i, j);
+            }
+            pool.truncate(panel_size);
+
+            let jurors = BoundedVec::<_, T::PanelSize>::truncate_from(pool);
+            for juror in jurors.iter() {
+                JurorActiveCases::<T>::mutate(juror, |cases| *cases += 1);
+            }
+
+            let now = frame_system::Pallet::<T>::block_number();
+            let commit_end = now.saturating_add(T::CommitPeriod::get());
+            let reveal_end = commit_end.saturating_add(T::RevealPeriod::get());
+
+            Cases::<T>::insert(
+                case_id,
+                ModerationCase { to: to.clone(), from: from.clone(), jurors: jurors.clone(), commit_end, reveal_end },
+            );
+
+            Self::deposit_event(Event::CaseOpened { case_id, to, from, jurors: jurors.into_inner() });
+        }
+
+        pub(crate) fn do_resolve_case(case_id: CaseId) -> DispatchResult {
+            let case = Cases::<T>::get(case_id).ok_or(Error::<T>::CaseNotFound)?;
+            ensure!(!case.jurors.is_empty(), Error::<T>::AwaitingGovernance);
+            ensure!(
+                frame_system::Pallet::<T>::block_number() > case.reveal_end,
+                Error::<T>::CaseStillOpen
+            );
+
+            let (mut hide_votes, mut keep_votes) = (0u32, 0u32);
+            for juror in case.jurors.iter() {
+                match JurorVotes::<T>::get(case_id, juror) {
+                    Some(Verdict::Hide) => hide_votes += 1,
+                    Some(Verdict::Keep) => keep_votes += 1,
+                    None => {},
+                }
+            }
+            // A tie keeps the rating.
+            let verdict = if hide_votes > keep_votes { Verdict::Hide } else { Verdict::Keep };
+
+            Self::close_case(case_id, case, verdict, hide_votes, keep_votes)
+        }
+
+        /// Apply `verdict`: settle flag deposits, reward, slash and release the panel, and hide
+        /// the rating if the verdict says so.
+        fn close_case(
+            case_id: CaseId,
+            case: ModerationCase<T>,
+            verdict: Verdict,
+            hide_votes: u32,
+            keep_votes: u32,
+        ) -> DispatchResult {
+            // Forfeited flag deposits and no-show slashes are split among the majority.
+            let mut pot = NegativeImbalanceOf::<T>::zero();
+
+            let flaggers: Vec<_> = Flags::<T>::iter_key_prefix((&case.to, &case.from)).collect();
+            for flagger in flaggers {
+                match verdict {
+                    Verdict::Hide => {
+                        T::Currency::unreserve(&flagger, T::FlagDeposit::get());
+                    },
+                    Verdict::Keep => {
+                        let (slashed, _) = T::Currency::slash_reserved(&flagger, T::FlagDeposit::get());
+                        pot.subsume(slashed);
+                    },
+                }
+            }
+
+            let mut majority = Vec::new();
+            for juror in case.jurors.iter() {
+                JurorActiveCases::<T>::mutate(juror, |cases| *cases = cases.saturating_sub(1));
+
+                match JurorVotes::<T>::get(case_id, juror) {
+                    Some(vote) if vote == verdict => majority.push(juror.clone()),
+                    Some(_) => {},
+                    None => {
+                        // A juror who does not show up is slashed once, leaves the pool and gets
+                        // the rest of the bond back. No-shows on their other open cases cost
+                        // nothing further since the bond is gone.
+                        let mut amount = Zero::zero();
+                        if Self::do_remove_juror(juror).is_ok() {
+                            let (slashed, _) = T::Currency::slash_reserved(
+                                juror,
+                                T::NoShowSlash::get() * T::JurorBond::get(),
+                            );
+                            amount = slashed.peek();
+                            pot.subsume(slashed);
+                            T::Currency::unreserve(juror, T::JurorBond::get().saturating_sub(amount));
+                        }
+
+                        // Reputation counts for juror eligibility, so no-shows lose some of it too.
+                        let stake_slashed = UserReputation::<T>::mutate(juror, |profile| {
+                            profile.as_mut().map_or(0, |profile| {
+                                let cut = T::NoShowSlash::get() * profile.staked_amount;
+                                profile.staked_amount -= cut;
+                                cut
+                            })
+                        });
+                        if stake_slashed > 0 {
+                            Self::profile_changed(juror);
+                        }
+
+                        Self::deposit_event(Event::JurorNoShow {
+                            case_id,
+                            juror: juror.clone(),
+                            slashed: amount,
+                            stake_slashed,
+                        });
+                    },
+                }
+            }
+
+            if !majority.is_empty() {
+                let share = pot.peek() / BalanceOf::<T>::from(majority.len() as u32);
+                for juror in majority {
+                    let (reward, rest) = pot.split(share);
+                    pot = rest;
+                    let amount = reward.peek();
+                    T::Currency::resolve_creating(&juror, reward);
+                    Self::deposit_event(Event::JurorRewarded { case_id, juror, amount });
+                }
+            }
+            T::Slash::on_unbalanced(pot);
+
+            if verdict == Verdict::Hide {
+                Self::do_hide_rating(&case.to, &case.from, case_id)?;
+                Self::record_moderation(
+                    Some(case_id),
+                    case.from.clone(),
+                    ModerationKind::RatingHidden { to: case.to.clone(), from: case.from.clone() },
+                );
+            }
+
+            let _ = JurorCommits::<T>::clear_prefix(case_id, T::PanelSize::get(), None);
+            let _ = JurorVotes::<T>::clear_prefix(case_id, T::PanelSize::get(), None);
+            let _ = Flags::<T>::clear_prefix((&case.to, &case.from), T::FlagThreshold::get(), None);
+            FlagCount::<T>::remove(&case.to, &case.from);
+            OpenCase::<T>::remove(&case.to, &case.from);
+            Cases::<T>::remove(case_id);
+
+            Self::deposit_event(Event::CaseResolved { case_id, verdict, hide_votes, keep_votes });
+
+            Ok(())
+        }
+
+        /// Take a rating out of its target's profile aggregates without deleting it.
+        pub(crate) fn do_hide_rating(to: &T::AccountId, from: &T::AccountId, case_id: CaseId) -> DispatchResult {
+            let rating = Ratings::<T>::get(to, from).ok_or(Error::<T>::RatingNotFound)?;
+            if HiddenRatings::<T>::contains_key(to, from) {
+                return Ok(())
+            }
+
+            UserReputation::<T>::try_mutate(to, |profile| -> DispatchResult {
+                let profile = profile.as_mut().ok_or(Error::<T>::ProfileNotFound)?;
+                let scores = &mut profile.category_scores;
+                profile.total_score = profile.total_score.saturating_sub(rating.score as u32);
+                profile.review_count = profile.review_count.saturating_sub(1);
+                scores.communication = scores.communication.saturating_sub(rating.category_ratings.communication);
+                scores.reliability = scores.reliability.saturating_sub(rating.category_ratings.reliability);
+                scores.quality = scores.quality.saturating_sub(rating.category_ratings.quality);
+                scores.professionalism = scores.professionalism.saturating_sub(rating.category_ratings.professionalism);
+                Ok(())
+            })?;
+            HiddenRatings::<T>::insert(to, from, case_id);
+            Self::profile_changed(to);
+            Self::index_rating_change(to, from, RatingChange::Hidden);
+
+            Self::deposit_event(Event::RatingHidden { to: to.clone(), from: from.clone(), case_id });
+
+            Ok(())
+        }
+
+        /// Returns the status the profile had before.
+        pub(crate) fn do_set_status(who: &T::AccountId, status: StatusOf<T>) -> Result<StatusOf<T>, DispatchError> {
+            ensure!(status != ProfileStatus::Paused, Error::<T>::InvalidStatusTransition);
+
+            let mut profile = UserReputation::<T>::get(who).ok_or(Error::<T>::ProfileNotFound)?;
+
+            if let ProfileStatus::Suspended { until, .. } = status {
+                ensure!(
+                    until > frame_system::Pallet::<T>::block_number(),
+                    Error::<T>::InvalidStatusTransition
+                );
+                SuspensionExpiries::<T>::try_mutate(until, |expiring| {
+                    expiring.try_push(who.clone()).map_err(|_| Error::<T>::TooManySuspensions)
+                })?;
+            }
+
+            let previous = sp_std::mem::replace(&mut profile.status, status.clone());
+            UserReputation::<T>::insert(who, profile);
+            Self::profile_changed(who);
+
+            Self::deposit_event(Event::ProfileStatusChanged { who: who.clone(), status });
+
+            Ok(previous)
+        }
+
+        /// Lift suspensions ending at `n`.
+        fn expire_suspensions(n: BlockNumberFor<T>) -> Weight {
+            let db = T::DbWeight::get();
+            let expiring = SuspensionExpiries::<T>::take(n);
+            let mut weight = db.reads_writes(1, 1);
+
+            for who in expiring {
+                weight = weight.saturating_add(db.reads(1));
+                let Some(mut profile) = UserReputation::<T>::get(&who) else { continue };
+                if !matches!(profile.status, ProfileStatus::Suspended { until, .. } if until == n) {
+                    continue
+                }
+
+                profile.status = ProfileStatus::Active;
+                UserReputation::<T>::insert(&who, profile);
+                Self::profile_changed(&who);
+                weight = weight.saturating_add(db.writes(1));
+
+                Self::deposit_event(Event::ProfileStatusChanged { who, status: ProfileStatus::Active });
+            }
+
+            weight
+        }
+
+        /// Keep `kind` appealable by `subject` under `case_id`, or a fresh case id if the action
+        /// did not come out of a juror case, until its appeal period ends. Returns the case id.
+        fn record_moderation(
+            case_id: Option<CaseId>,
+            subject: T::AccountId,
+            kind: ModerationKind<T::AccountId, BlockNumberFor<T>>,
+        ) -> CaseId {
+            let case_id = case_id.unwrap_or_else(|| {
+                let id = NextCaseId::<T>::get();
+                NextCaseId::<T>::put(id.wrapping_add(1));
+                id
+            });
+
+            let now = frame_system::Pallet::<T>::block_number();
+            Moderations::<T>::insert(case_id, ModerationAction { subject: subject.clone(), kind, at: now });
+            let expires = now.saturating_add(T::AppealPeriod::get()).saturating_add(One::one());
+            ModerationsExpiring::<T>::insert(expires, case_id, ());
+
+            Self::deposit_event(Event::ModerationRecorded { case_id, subject });
+
+            case_id
+        }
+
+        /// Forget a moderation action that can no longer be appealed or reversed.
+        /// A rating still hidden at this point stays hidden, so its deposit is slashed, and
+        /// voucher slashes held for a refund go to `T::Slash`.
+        fn close_moderation(case_id: CaseId) {
+            let action = Moderations::<T>::take(case_id);
+            if let Some(ModerationAction { kind: ModerationKind::RatingHidden { to, from }, .. }) = action {
+                if HiddenRatings::<T>::contains_key(&to, &from) {
+                    if let Some(deposit) = RatingDeposits::<T>::take(&to, &from) {
+                        let (slashed, _) = T::Currency::slash_reserved(&from, deposit);
+                        let amount = slashed.peek();
+                        T::Slash::on_unbalanced(slashed);
+                        Self::deposit_event(Event::RatingDepositSlashed { to, from, amount });
+                    }
+                }
+            }
+            let held = VoucherSlashes::<T>::take(case_id)
+                .into_iter()
+                .fold(BalanceOf::<T>::zero(), |total, (_, amount)| total.saturating_add(amount));
+            Self::release_held(held);
+        }
+
+        /// Account holding voucher slashes until their penalty is final.
+        pub fn account_id() -> T::AccountId {
+            T::PalletId::get().into_account_truncating()
+        }
+
+        /// Pass `amount` held in `account_id` on to `T::Slash`.
+        fn release_held(amount: BalanceOf<T>) {
+            if amount.is_zero() {
+                return
+            }
+            let withdrawn = T::Currency::withdraw(
+                &Self::account_id(),
+                amount,
+                WithdrawReasons::TRANSFER,
+                ExistenceRequirement::KeepAlive,
+            );
+            match withdrawn {
+                Ok(imbalance) => T::Slash::on_unbalanced(imbalance),
+                Err(e) => log::error!(target: migrations::LOG_TARGET, "cannot release {:?} held slashes: {:?}", amount, e),
+            }
+        }
+
+        /// Forget moderation actions whose appeal period ended without an appeal. Appealed ones
+        /// are closed when the appeal is decided or lapses.
+        fn expire_moderations(n: BlockNumberFor<T>) -> Weight {
+            let db = T::DbWeight::get();
+            let mut weight = Weight::zero();
+
+            for (case_id, ()) in ModerationsExpiring::<T>::drain_prefix(n) {
+                weight = weight.saturating_add(db.reads_writes(1, 1));
+                if !Appeals::<T>::contains_key(case_id) {
+                    Self::close_moderation(case_id);
+                    // A hidden rating's deposit may be slashed.
+                    weight = weight.saturating_add(db.reads_writes(2, 4));
+                }
+            }
+
+            weight
+        }
+
+        /// Undo a moderation action. A status change is only rolled back while the profile is
+        /// still in the status it set; anything later was its own action.
+        fn reverse_moderation(case_id: CaseId, action: &ModerationAction<T>) -> DispatchResult {
+            match &action.kind {
+                ModerationKind::RatingHidden { to, from } => Self::do_unhide_rating(to, from),
+                ModerationKind::StatusChanged { previous, applied } => {
+                    let profile = UserReputation::<T>::get(&action.subject).ok_or(Error::<T>::ProfileNotFound)?;
+                    if profile.status != *applied {
+                        return Ok(())
+                    }
+
+                    let restored = match previous {
+                        ProfileStatus::Suspended { until, .. }
+                            if *until <= frame_system::Pallet::<T>::block_number() =>
+                            ProfileStatus::Active,
+                        status => status.clone(),
+                    };
+                    if restored == ProfileStatus::Paused {
+                        // Owner-only status, so it does not go through `do_set_status`.
+                        UserReputation::<T>::mutate(&action.subject, |profile| {
+                            if let Some(profile) = profile {
+                                profile.status = ProfileStatus::Paused;
+                            }
+                        });
+                        Self::profile_changed(&action.subject);
+                        Self::deposit_event(Event::ProfileStatusChanged {
+                            who: action.subject.clone(),
+                            status: ProfileStatus::Paused,
+                        });
+                        Ok(())
+                    } else {
+                        Self::do_set_status(&action.subject, restored).map(|_| ())
+                    }
+                },
+                ModerationKind::Penalised { stake_slashed } => {
+                    UserReputation::<T>::try_mutate(&action.subject, |profile| -> DispatchResult {
+                        let profile = profile.as_mut().ok_or(Error::<T>::ProfileNotFound)?;
+                        profile.staked_amount = profile.staked_amount.saturating_add(*stake_slashed);
+                        Ok(())
+                    })?;
+                    Self::profile_changed(&action.subject);
+
+                    // Paid out free from where the slashes were held: the vouches may have changed
+                    // since. What cannot be paid, say to a reaped voucher, goes to `T::Slash`.
+                    let pot = Self::account_id();
+                    let mut unpaid = BalanceOf::<T>::zero();
+                    for (voucher, amount) in VoucherSlashes::<T>::take(case_id) {
+                        if amount.is_zero() {
+                            continue
+                        }
+                        match T::Currency::transfer(&pot, &voucher, amount, ExistenceRequirement::KeepAlive) {
+                            Ok(()) => Self::deposit_event(Event::VoucherRefunded {
+                                voucher,
+                                target: action.subject.clone(),
+                                amount,
+                            }),
+                            Err(_) => unpaid = unpaid.saturating_add(amount),
+                        }
+                    }
+                    Self::release_held(unpaid);
+                    Ok(())
+                },
+            }
+        }
+
+        /// Put a hidden rating back into its target's profile aggregates. The rating stays
+        /// hidden unless it can be read, so it is not unhidden mid-migration.
+        pub(crate) fn do_unhide_rating(to: &T::AccountId, from: &T::AccountId) -> DispatchResult {
+            if !HiddenRatings::<T>::contains_key(to, from) {
+                return Ok(())
+            }
+            ensure!(!RatingsMigrationCursor::<T>::exists(), Error::<T>::MigrationInProgress);
+            let rating = Ratings::<T>::get(to, from).ok_or(Error::<T>::RatingNotFound)?;
+
+            UserReputation::<T>::try_mutate(to, |profile| -> DispatchResult {
+                let profile = profile.as_mut().ok_or(Error::<T>::ProfileNotFound)?;
+                let scores = &mut profile.category_scores;
+                profile.total_score = profile.total_score.saturating_add(rating.score as u32);
+                profile.review_count = profile.review_count.saturating_add(1);
+                scores.communication = scores.communication.saturating_add(rating.category_ratings.communication);
+                scores.reliability = scores.reliability.saturating_add(rating.category_ratings.reliability);
+                scores.quality = scores.quality.saturating_add(rating.category_ratings.quality);
+                scores.professionalism = scores.professionalism.saturating_add(rating.category_ratings.professionalism);
+                Ok(())
+            })?;
+            HiddenRatings::<T>::remove(to, from);
+            Self::profile_changed(to);
+            Self::index_rating_change(to, from, RatingChange::Unhidden);
+
+            Ok(())
+        }
+
+        /// Return the bonds of appeals nobody decided in time and make their actions final. The
+        /// queue is in filing order, so this stops at the first appeal still within its deadline.
+        fn lapse_appeals(n: BlockNumberFor<T>) -> Weight {
+            let db = T::DbWeight::get();
+            let mut weight = db.reads(1);
+
+            let mut queue = AppealQueue::<T>::get();
+            let mut lapsed = 0;
+            for case_id in queue.iter() {
+                weight = weight.saturating_add(db.reads(1));
+                match Appeals::<T>::get(case_id) {
+                    Some(appeal) if appeal.deadline >= n => break,
+                    Some(appeal) => {
+                        T::Currency::unreserve(&appeal.appellant, appeal.bond);
+                        Appeals::<T>::remove(case_id);
+                        // Closed, so the action cannot be appealed again.
+                        Self::close_moderation(*case_id);
+                        weight = weight.saturating_add(db.writes(4));
+                        Self::deposit_event(Event::AppealLapsed { case_id: *case_id });
+                    },
+                    None => {},
+                }
+                lapsed += 1;
+            }
+
+            if lapsed > 0 {
+                queue = queue.into_iter().skip(lapsed).collect::<Vec<_>>().try_into().unwrap_or_default();
+                AppealQueue::<T>::put(queue);
+                weight = weight.saturating_add(db.writes(1));
+            }
+
+            weight
+        }
+
+        /// Publish a revealed commitment or expire an unrevealed one.
+        fn publish_commit(to: &T::AccountId, from: &T::AccountId) {
+            let Some(commit) = RatingCommits::<T>::take(to, from) else { return };
+
+            match commit.revealed {
+                Some(sealed) => {
+                    let published = Self::do_submit_rating(
+                        from.clone(),
+                        to.clone(),
+                        sealed.score,
+                        sealed.category_ratings,
+                        sealed.review_hash,
+                        Zero::zero(),
+                    );
+                    if let Err(error) = published {
+                        Self::deposit_event(Event::RatingCommitDiscarded { from: from.clone(), to: to.clone(), error });
+                    }
+                },
+                None => {
+                    let (slashed, _) =
+                        T::Currency::slash_reserved(from, T::UnrevealedSlash::get() * commit.deposit);
+                    let amount = slashed.peek();
+                    T::Slash::on_unbalanced(slashed);
+                    T::Currency::unreserve(from, commit.deposit.saturating_sub(amount));
+
+                    Self::deposit_event(Event::RatingCommitExpired { from: from.clone(), to: to.clone(), slashed: amount });
+                },
+            }
+        }
+
+        /// Publish the ratings due at `n`, unless `Ratings` is being migrated. Blocks skipped
+        /// during a migration are caught up afterwards, `MAX_DEFERRED_BLOCKS` at a time.
+        fn publish_due(n: BlockNumberFor<T>) -> Weight {
+            let db = T::DbWeight::get();
+            let mut weight = db.reads(2);
+            let deferred_from = PublishingDeferredFrom::<T>::get();
+
+            if RatingsMigrationCursor::<T>::exists() {
+                if deferred_from.is_none() {
+                    PublishingDeferredFrom::<T>::put(n);
+                    weight = weight.saturating_add(db.writes(1));
+                }
+                return weight
+            }
+
+            let mut block = deferred_from.unwrap_or(n);
+            let mut caught_up = false;
+            for _ in 0..MAX_DEFERRED_BLOCKS {
+                weight = weight
+                    .saturating_add(Self::close_commit_windows(block))
+                    .saturating_add(Self::publish_sessions(block));
+                if block >= n {
+                    caught_up = true;
+                    break
+                }
+                block = block.saturating_add(One::one());
+            }
+
+            if deferred_from.is_some() {
+                if caught_up {
+                    PublishingDeferredFrom::<T>::kill();
+                } else {
+                    PublishingDeferredFrom::<T>::put(block);
+                }
+                weight = weight.saturating_add(db.writes(1));
+            }
+
+            weight
+        }
+
+        /// Settle commitments whose reveal window closes at `n`. Ones already published when
+        /// both sides revealed, or replaced by a later commitment since, are skipped.
+        fn close_commit_windows(n: BlockNumberFor<T>) -> Weight {
+            let db = T::DbWeight::get();
+            let closing = CommitWindowsClosing::<T>::take(n);
+            let mut weight = db.reads_writes(1, 1);
+
+            for (to, from) in closing {
+                weight = weight.saturating_add(db.reads(1));
+                if RatingCommits::<T>::get(&to, &from).map_or(false, |commit| commit.closes_at == n) {
+                    Self::publish_commit(&to, &from);
+                    // Publishing goes through `do_submit_rating`.
+                    weight = weight.saturating_add(db.reads_writes(5, 5));
+                }
+            }
+
+            weight
+        }
+
+        /// Open session between `a` and `b`, in either order.
+        pub fn session_between(a: &T::AccountId, b: &T::AccountId) -> Option<SessionId> {
+            let (lo, hi) = if a < b { (a, b) } else { (b, a) };
+            SessionBetween::<T>::get(lo, hi)
+        }
+
+        /// Open a review session between `first` and `second`. Other pallets can call this
+        /// directly for interactions they have verified.
+        pub fn do_open_session(
+            first: T::AccountId,
+            second: T::AccountId,
+            verified: bool,
+        ) -> Result<SessionId, DispatchError> {
+            ensure!(first != second, Error::<T>::CannotRateSelf);
+            ensure!(UserReputation::<T>::contains_key(&first), Error::<T>::ProfileNotFound);
+            ensure!(UserReputation::<T>::contains_key(&second), Error::<T>::ProfileNotFound);
+            ensure!(Self::session_between(&first, &second).is_none(), Error::<T>::SessionAlreadyOpen);
+            ensure!(
+                !Ratings::<T>::contains_key(&second, &first) && !Ratings::<T>::contains_key(&first, &second),
+                Error::<T>::AlreadyRated
+            );
+            ensure!(
+                !RatingCommits::<T>::contains_key(&second, &first) && !RatingCommits::<T>::contains_key(&first, &second),
+                Error::<T>::AlreadyCommitted
+            );
+
+            let session_id = NextSessionId::<T>::get();
+            let deadline = frame_system::Pallet::<T>::block_number().saturating_add(T::SessionDuration::get());
+            let deadline = Self::schedule_session(deadline, session_id)?;
+
+            // Opening is otherwise free, and every session takes a slot in `SessionsDue`.
+            if !verified {
+                let deposit = T::SessionDeposit::get();
+                T::Currency::reserve(&first, deposit)?;
+                SessionDeposits::<T>::insert(session_id, deposit);
+            }
+
+            let (lo, hi) = if first < second { (&first, &second) } else { (&second, &first) };
+            SessionBetween::<T>::insert(lo, hi, session_id);
+            Sessions::<T>::insert(
+                session_id,
+                ReviewSession {
+                    first: first.clone(),
+                    second: second.clone(),
+                    verified,
+                    deadline,
+                    first_rating: None,
+                    second_rating: None,
+                },
+            );
+            NextSessionId::<T>::put(session_id.wrapping_add(1));
+
+            Self::deposit_event(Event::SessionOpened { session_id, first, second, verified, deadline });
+
+            Ok(session_id)
+        }
+
+        /// Schedule `session_id` for publication at `at`, or the first block after it with room
+        /// in `SessionsDue`. Returns the block it is scheduled at.
+        fn schedule_session(
+            at: BlockNumberFor<T>,
+            session_id: SessionId,
+        ) -> Result<BlockNumberFor<T>, DispatchError> {
+            let mut at = at;
+            for _ in 0..=MAX_SESSION_ROLLOVER {
+                if SessionsDue::<T>::try_append(at, session_id).is_ok() {
+                    return Ok(at)
+                }
+                at = at.saturating_add(One::one());
+            }
+            Err(Error::<T>::TooManySessions.into())
+        }
+
+        /// Publish the sessions due at `n`. A session is listed twice when both sides rate
+        /// before the deadline; the second entry finds it gone.
+        fn publish_sessions(n: BlockNumberFor<T>) -> Weight {
+            let db = T::DbWeight::get();
+            let due = SessionsDue::<T>::take(n);
+            let mut weight = db.reads_writes(1, 1);
+
+            for session_id in due {
+                weight = weight.saturating_add(db.reads(1));
+                let Some(session) = Sessions::<T>::take(session_id) else { continue };
+                if let Some(deposit) = SessionDeposits::<T>::take(session_id) {
+                    T::Currency::unreserve(&session.first, deposit);
+                    weight = weight.saturating_add(db.reads_writes(2, 2));
+                }
+
+                let (lo, hi) = if session.first < session.second {
+                    (&session.first, &session.second)
+                } else {
+                    (&session.second, &session.first)
+                };
+                SessionBetween::<T>::remove(lo, hi);
+
+                let mut ratings = 0u8;
+                for (from, to, sealed) in [
+                    (&session.first, &session.second, session.first_rating),
+                    (&session.second, &session.first, session.second_rating),
+                ] {
+                    let Some(sealed) = sealed else { continue };
+                    let published = Self::do_submit_rating(
+                        from.clone(),
+                        to.clone(),
+                        sealed.score,
+                        sealed.category_ratings,
+                        sealed.review_hash,
+                        Zero::zero(),
+                    );
+                    match published {
+                        Ok(()) => ratings += 1,
+                        Err(error) => Self::deposit_event(Event::SessionRatingDiscarded {
+                            session_id,
+                            from: from.clone(),
+                            to: to.clone(),
+                            error,
+                        }),
+                    }
+                    // Goes through `do_submit_rating`.
+                    weight = weight.saturating_add(db.reads_writes(6, 5));
+                }
+
+                Self::deposit_event(Event::SessionPublished { session_id, ratings });
+            }
+
+            weight
+        }
+
+        /// Everything that has to follow a change to `who`'s profile.
+        pub(crate) fn profile_changed(who: &T::AccountId) {
+            Self::refresh_disclosure_leaf(who);
+            T::OnProfileChanged::on_profile_changed(who);
+        }
+
+        /// Count a new rating referring to `review_hash` against its stored body.
+        fn note_content_reference(review_hash: &ContentRef) {
+            if let Some(key) = review_hash.stored_content_key() {
+                ContentReferences::<T>::mutate(key, |count| *count = count.saturating_add(1));
+            }
+        }
+
+        /// A stored review body, unless it was never stored or has been forgotten.
+        pub fn review_content(hash: &[u8; 32]) -> Option<Vec<u8>> {
+            ReviewContent::<T>::get(hash).and_then(|content| content.body).map(|body| body.into_inner())
         }
 
         pub fn get_average_score(who: &T::AccountId) -> Option<u32> {
@@ -242,4 +3274,207 @@ pub mod pallet {
             })
         }
     }
-}
\ No newline at end of file
+
+    #[cfg(any(feature = "try-runtime", test))]
+    impl<T: Config> Pallet<T> {
+        /// Cross-map invariants of `UserReputation`, `Ratings` and `RatingCount`.
+        pub fn do_try_state() -> Result<(), TryRuntimeError> {
+            // Ratings may be in the previous layout until the lazy migration finishes.
+            if RatingsMigrationCursor::<T>::exists() {
+                return Ok(())
+            }
+
+            for (who, profile) in UserReputation::<T>::iter() {
+                ensure!(profile.owner == who, "profile owner does not match its key");
+
+                let mut total_score = 0u32;
+                let mut reviews = 0u32;
+                let mut tips: BalanceOf<T> = Zero::zero();
+                let mut stored = 0u32;
+                let mut decoded = 0u32;
+                for (from, rating) in Ratings::<T>::iter_prefix(&who) {
+                    ensure!(rating.to == who, "rating target does not match its key");
+                    ensure!(rating.from == from, "rating author does not match its key");
+                    tips = tips.saturating_add(rating.tip);
+                    stored += 1;
+                    decoded += 1;
+                    if !HiddenRatings::<T>::contains_key(&who, &from) {
+                        total_score = total_score.saturating_add(rating.score as u32);
+                        reviews += 1;
+                    }
+                }
+                for (nullifier, rating) in AnonymousRatings::<T>::iter_prefix(&who) {
+                    ensure!(rating.to == who, "anonymous rating target does not match its key");
+                    ensure!(Nullifiers::<T>::contains_key(nullifier), "anonymous rating nullifier not recorded");
+                    stored += 1;
+                    total_score = total_score.saturating_add(rating.score as u32);
+                    reviews += 1;
+                }
+
+                // Ratings the lazy migration could not translate are left for manual repair;
+                // the aggregates still count them but they cannot be summed.
+                let undecodable = (Ratings::<T>::iter_key_prefix(&who).count() as u32).saturating_sub(decoded);
+                ensure!(
+                    RatingCount::<T>::get(&who) == stored + undecodable,
+                    "RatingCount does not match the number of stored ratings"
+                );
+                if undecodable > 0 {
+                    log::warn!(
+                        target: migrations::LOG_TARGET,
+                        "skipping the aggregates of {:?}: {} of its ratings do not decode",
+                        who,
+                        undecodable,
+                    );
+                    continue
+                }
+
+                ensure!(
+                    TipsReceived::<T>::get(&who) == tips,
+                    "TipsReceived does not match the sum of rating tips"
+                );
+                ensure!(
+                    profile.review_count == reviews,
+                    "review_count does not match the number of visible ratings"
+                );
+                ensure!(
+                    profile.total_score == total_score,
+                    "total_score does not match the sum of visible ratings"
+                );
+            }
+
+            for (to, from) in Ratings::<T>::iter_keys() {
+                ensure!(to != from, "account has rated itself");
+                ensure!(UserReputation::<T>::contains_key(&to), "rating targets a missing profile");
+            }
+
+            for (who, count) in RatingCount::<T>::iter() {
+                ensure!(
+                    count == 0 || UserReputation::<T>::contains_key(&who),
+                    "RatingCount kept for a missing profile"
+                );
+            }
+
+            for (target, backed) in BackedBy::<T>::iter() {
+                let mut active: BalanceOf<T> = Zero::zero();
+                let mut vouchers = 0u32;
+                for (voucher, vouch) in Vouches::<T>::iter_prefix(&target) {
+                    ensure!(voucher != target, "account vouches for itself");
+                    active = active.saturating_add(vouch.active);
+                    vouchers += 1;
+                }
+                ensure!(backed == active, "BackedBy does not match the sum of active vouches");
+                ensure!(
+                    VoucherCount::<T>::get(&target) == vouchers,
+                    "VoucherCount does not match the number of vouches"
+                );
+            }
+
+            for (to, from, net) in Helpfulness::<T>::iter() {
+                ensure!(Ratings::<T>::contains_key(&to, &from), "helpfulness kept for a missing rating");
+                let votes = HelpfulnessVotes::<T>::iter_prefix_values((&to, &from))
+                    .fold(0i32, |acc, up| if up { acc + 1 } else { acc - 1 });
+                ensure!(net == votes, "Helpfulness does not match the recorded votes");
+            }
+
+            for (to, from, _) in HiddenRatings::<T>::iter() {
+                ensure!(Ratings::<T>::contains_key(&to, &from), "hidden marker kept for a missing rating");
+            }
+
+            for (case_id, case) in Cases::<T>::iter() {
+                ensure!(
+                    OpenCase::<T>::get(&case.to, &case.from) == Some(case_id),
+                    "case is not indexed by its rating"
+                );
+                ensure!(case.commit_end <= case.reveal_end, "case reveal phase ends before commit phase");
+            }
+            for (_, case_id, (to, from)) in JuryDraws::<T>::iter() {
+                ensure!(OpenCase::<T>::get(&to, &from) == Some(case_id), "jury drawn for a case that is not open");
+                ensure!(!Cases::<T>::contains_key(case_id), "jury drawn twice");
+            }
+
+            let now = frame_system::Pallet::<T>::block_number();
+            for (who, profile) in UserReputation::<T>::iter() {
+                if let ProfileStatus::Suspended { until, .. } = profile.status {
+                    ensure!(until > now, "suspension outlived its expiry");
+                    ensure!(
+                        SuspensionExpiries::<T>::get(until).contains(&who),
+                        "suspension is not scheduled to expire"
+                    );
+                }
+            }
+
+            let queue = AppealQueue::<T>::get();
+            ensure!(
+                Appeals::<T>::iter_keys().count() == queue.len(),
+                "AppealQueue does not match the pending appeals"
+            );
+            for case_id in queue.iter() {
+                ensure!(Appeals::<T>::contains_key(case_id), "queued appeal does not exist");
+                ensure!(Moderations::<T>::contains_key(case_id), "appeal against an unknown moderation action");
+            }
+            for (case_id, action) in Moderations::<T>::iter() {
+                let expires = action.at.saturating_add(T::AppealPeriod::get()).saturating_add(One::one());
+                ensure!(
+                    ModerationsExpiring::<T>::contains_key(expires, case_id) || Appeals::<T>::contains_key(case_id),
+                    "moderation action is never forgotten"
+                );
+            }
+
+            let deferred_from = PublishingDeferredFrom::<T>::get();
+            let deferred = |at: BlockNumberFor<T>| deferred_from.map_or(false, |from| at >= from);
+            for (to, from, commit) in RatingCommits::<T>::iter() {
+                ensure!(
+                    commit.closes_at > now || deferred(commit.closes_at),
+                    "rating commitment outlived its reveal window"
+                );
+                ensure!(
+                    CommitWindowsClosing::<T>::get(commit.closes_at).contains(&(to, from)),
+                    "rating commitment is not scheduled to close"
+                );
+            }
+
+            for (session_id, session) in Sessions::<T>::iter() {
+                ensure!(
+                    Self::session_between(&session.first, &session.second) == Some(session_id),
+                    "session is not indexed by its participants"
+                );
+                ensure!(session.deadline > now || deferred(session.deadline), "session outlived its deadline");
+                ensure!(
+                    SessionsDue::<T>::get(session.deadline).contains(&session_id),
+                    "session is not scheduled for publication"
+                );
+            }
+
+            ensure!(
+                AnonRegistered::<T>::iter_keys().count() as u32 == AnonTreeSize::<T>::get(),
+                "AnonTreeSize does not match the registered identities"
+            );
+
+            let disclosure_size = DisclosureTreeSize::<T>::get();
+            for (_, entry) in DisclosureKeys::<T>::iter() {
+                ensure!(entry.index < disclosure_size, "disclosure leaf outside the tree");
+            }
+            ensure!(
+                EpochRoots::<T>::iter_keys().count() as u32 <= disclosure::DISCLOSURE_EPOCH_HISTORY,
+                "more epoch roots kept than the history allows"
+            );
+
+            for (to, from) in ReviewEnvelopes::<T>::iter_keys() {
+                ensure!(Ratings::<T>::contains_key(&to, &from), "review envelope without a rating");
+            }
+
+            for (hash, content) in ReviewContent::<T>::iter() {
+                if let Some(body) = content.body {
+                    ensure!(
+                        sp_io::hashing::blake2_256(&body) == hash,
+                        "stored review body does not match its hash"
+                    );
+                } else {
+                    ensure!(content.deposit.is_zero(), "forgotten review body still holds a deposit");
+                }
+            }
+
+            Ok(())
+        }
+    }
+}
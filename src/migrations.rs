@@ -0,0 +1,579 @@
+//! Storage migrations for `pallet_reputation`.
+//!
+//! Single-block migrations live in a module named after the version they migrate to and are
+//! listed in the runtime's `Executive`. Each bumps the storage version as soon as it has run, so
+//! a chain several versions behind goes through all of them in one upgrade.
+//!
+//! Rewrites of `Ratings`, which can grow far beyond what fits in one block, go through [`lazy`]
+//! and are stepped from `on_idle` instead. The storage version does not wait for them: while
+//! `RatingsMigrationCursor` is set, ratings past it may still be in an older layout.
+
+use super::*;
+use codec::DecodeAll;
+use frame_support::{
+    pallet_prelude::*,
+    traits::{GetStorageVersion, OnRuntimeUpgrade, StorageVersion},
+};
+use sp_std::marker::PhantomData;
+#[cfg(feature = "try-runtime")]
+use sp_runtime::TryRuntimeError;
+#[cfg(feature = "try-runtime")]
+use sp_std::vec::Vec;
+
+pub(crate) const LOG_TARGET: &str = "runtime::reputation";
+
+/// Version 1 is the first versioned layout.
+///
+/// The unversioned (v0) layout is identical, so this only checks that every profile still
+/// decodes and stamps the version.
+pub mod v1 {
+    use super::*;
+
+    pub struct MigrateToV1<T>(PhantomData<T>);
+
+    impl<T: Config> OnRuntimeUpgrade for MigrateToV1<T> {
+        fn on_runtime_upgrade() -> Weight {
+            let on_chain = Pallet::<T>::on_chain_storage_version();
+            if on_chain != 0 {
+                log::info!(target: LOG_TARGET, "skipping v1 migration, on-chain version is {:?}", on_chain);
+                return T::DbWeight::get().reads(1);
+            }
+
+            let profiles = UserReputation::<T>::iter_values().count() as u64;
+            StorageVersion::new(1).put::<Pallet<T>>();
+
+            log::info!(target: LOG_TARGET, "migrated {} profiles to v1", profiles);
+            T::DbWeight::get().reads_writes(profiles + 1, 1)
+        }
+
+        #[cfg(feature = "try-runtime")]
+        fn pre_upgrade() -> Result<Vec<u8>, TryRuntimeError> {
+            let profiles = UserReputation::<T>::iter_keys().count() as u32;
+            Ok(profiles.encode())
+        }
+
+        #[cfg(feature = "try-runtime")]
+        fn post_upgrade(state: Vec<u8>) -> Result<(), TryRuntimeError> {
+            let before = u32::decode(&mut &state[..])
+                .map_err(|_| TryRuntimeError::Other("invalid pre_upgrade state"))?;
+            let decoded = UserReputation::<T>::iter_values().count() as u32;
+
+            ensure!(before == decoded, "some profiles no longer decode after the v1 migration");
+            ensure!(
+                Pallet::<T>::on_chain_storage_version() >= 1,
+                "storage version was not bumped to v1"
+            );
+            Ok(())
+        }
+    }
+}
+
+/// Version 2 adds `Rating::tip`.
+///
+/// `Ratings` is rewritten lazily through [`lazy`]; existing ratings carry no tip. Nothing else
+/// changed, so [`MigrateToV2`] only bumps the version.
+pub mod v2 {
+    use super::*;
+    use sp_runtime::traits::Zero;
+
+    pub struct MigrateToV2<T>(PhantomData<T>);
+
+    impl<T: Config> OnRuntimeUpgrade for MigrateToV2<T> {
+        fn on_runtime_upgrade() -> Weight {
+            let on_chain = Pallet::<T>::on_chain_storage_version();
+            if on_chain != 1 {
+                log::info!(target: LOG_TARGET, "skipping v2 migration, on-chain version is {:?}", on_chain);
+                return T::DbWeight::get().reads(1);
+            }
+
+            StorageVersion::new(2).put::<Pallet<T>>();
+
+            log::info!(target: LOG_TARGET, "migrated to v2");
+            T::DbWeight::get().reads_writes(1, 1)
+        }
+    }
+
+    /// `Rating` as stored up to v1.
+    #[derive(Decode)]
+    pub struct RatingV1<AccountId> {
+        pub from: AccountId,
+        pub to: AccountId,
+        pub score: u8,
+        pub category_ratings: CategoryScores,
+        pub timestamp: u64,
+        pub review_hash: [u8; 32],
+    }
+
+    pub struct MigrateRatingsToV2<T>(PhantomData<T>);
+
+    impl<T: Config> lazy::RatingsMigration<T> for MigrateRatingsToV2<T> {
+        const TO_VERSION: u16 = 2;
+
+        fn translate(raw: &[u8]) -> Option<RatingOf<T>> {
+            let old = RatingV1::<T::AccountId>::decode(&mut &raw[..]).ok()?;
+
+            Some(Rating {
+                from: old.from,
+                to: old.to,
+                score: old.score,
+                category_ratings: old.category_ratings,
+                timestamp: old.timestamp,
+                review_hash: old.review_hash,
+                tip: Zero::zero(),
+            })
+        }
+    }
+}
+
+/// Version 3 replaces `ReputationProfile::active` with `ProfileStatus`.
+///
+/// Active profiles become `Active`, deactivated ones `Paused`. Independent of the `Ratings`
+/// layout, so it runs whether or not a lazy rewrite is still going.
+pub mod v3 {
+    use super::*;
+
+    /// `ReputationProfile` as stored up to v2.
+    #[derive(Decode)]
+    pub struct ReputationProfileV2<AccountId> {
+        pub total_score: u32,
+        pub review_count: u32,
+        pub category_scores: CategoryScores,
+        pub active: bool,
+        pub staked_amount: u128,
+        pub owner: AccountId,
+    }
+
+    pub struct MigrateToV3<T>(PhantomData<T>);
+
+    impl<T: Config> OnRuntimeUpgrade for MigrateToV3<T> {
+        fn on_runtime_upgrade() -> Weight {
+            let on_chain = Pallet::<T>::on_chain_storage_version();
+            if on_chain != 2 {
+                log::info!(target: LOG_TARGET, "skipping v3 migration, on-chain version is {:?}", on_chain);
+                return T::DbWeight::get().reads(1);
+            }
+
+            let mut translated = 0u64;
+            UserReputation::<T>::translate::<ReputationProfileV2<T::AccountId>, _>(|_, old| {
+                translated += 1;
+                Some(ReputationProfile {
+                    total_score: old.total_score,
+                    review_count: old.review_count,
+                    category_scores: old.category_scores,
+                    status: if old.active { ProfileStatus::Active } else { ProfileStatus::Paused },
+                    staked_amount: old.staked_amount,
+                    owner: old.owner,
+                })
+            });
+            StorageVersion::new(3).put::<Pallet<T>>();
+
+            log::info!(target: LOG_TARGET, "migrated {} profiles to v3", translated);
+            T::DbWeight::get().reads_writes(translated + 1, translated + 1)
+        }
+
+        #[cfg(feature = "try-runtime")]
+        fn pre_upgrade() -> Result<Vec<u8>, TryRuntimeError> {
+            let mut profiles = 0u32;
+            let mut paused = 0u32;
+            for who in UserReputation::<T>::iter_keys() {
+                profiles += 1;
+                let key = UserReputation::<T>::hashed_key_for(&who);
+                let old = frame_support::storage::unhashed::get::<ReputationProfileV2<T::AccountId>>(&key);
+                if old.map_or(false, |old| !old.active) {
+                    paused += 1;
+                }
+            }
+            Ok((profiles, paused).encode())
+        }
+
+        #[cfg(feature = "try-runtime")]
+        fn post_upgrade(state: Vec<u8>) -> Result<(), TryRuntimeError> {
+            let (profiles, paused) = <(u32, u32)>::decode(&mut &state[..])
+                .map_err(|_| TryRuntimeError::Other("invalid pre_upgrade state"))?;
+
+            let mut decoded = 0u32;
+            let mut now_paused = 0u32;
+            for profile in UserReputation::<T>::iter_values() {
+                decoded += 1;
+                if profile.status == ProfileStatus::Paused {
+                    now_paused += 1;
+                }
+            }
+
+            ensure!(profiles == decoded, "some profiles no longer decode after the v3 migration");
+            ensure!(paused == now_paused, "deactivated profiles were not paused");
+            ensure!(
+                Pallet::<T>::on_chain_storage_version() >= 3,
+                "storage version was not bumped to v3"
+            );
+            Ok(())
+        }
+    }
+}
+
+/// Version 4 turns every `review_hash` into a `ContentRef`.
+///
+/// Existing 32-byte values are taken as raw sha2-256 digests. `Ratings` is rewritten lazily
+/// through [`lazy`] and [`MigrateToV4`] rewrites the rest in one block. Unrevealed rating
+/// commitments hash the old layout and could never be revealed, so they are cancelled and their
+/// deposits returned.
+pub mod v4 {
+    use super::*;
+    use crate::content::ContentRef;
+    use frame_support::traits::ReservableCurrency;
+    use frame_system::pallet_prelude::BlockNumberFor;
+    use sp_runtime::traits::Zero;
+
+    /// `Rating` as stored up to v3.
+    #[derive(Decode)]
+    pub struct RatingV3<AccountId, Balance> {
+        pub from: AccountId,
+        pub to: AccountId,
+        pub score: u8,
+        pub category_ratings: CategoryScores,
+        pub timestamp: u64,
+        pub review_hash: [u8; 32],
+        pub tip: Balance,
+    }
+
+    /// `SealedRating` as stored up to v3.
+    #[derive(Decode)]
+    pub struct SealedRatingV3 {
+        pub score: u8,
+        pub category_ratings: CategoryScores,
+        pub review_hash: [u8; 32],
+    }
+
+    impl From<SealedRatingV3> for SealedRating {
+        fn from(old: SealedRatingV3) -> Self {
+            SealedRating {
+                score: old.score,
+                category_ratings: old.category_ratings,
+                review_hash: ContentRef::sha2_256(old.review_hash),
+            }
+        }
+    }
+
+    /// `RatingCommitment` as stored up to v3.
+    #[derive(Decode)]
+    pub struct RatingCommitmentV3<Hash, Balance, BlockNumber> {
+        pub commitment: Hash,
+        pub deposit: Balance,
+        pub closes_at: BlockNumber,
+        pub revealed: Option<SealedRatingV3>,
+    }
+
+    /// `ReviewSession` as stored up to v3.
+    #[derive(Decode)]
+    pub struct ReviewSessionV3<AccountId, BlockNumber> {
+        pub first: AccountId,
+        pub second: AccountId,
+        pub verified: bool,
+        pub deadline: BlockNumber,
+        pub first_rating: Option<SealedRatingV3>,
+        pub second_rating: Option<SealedRatingV3>,
+    }
+
+    /// `AnonymousRating` as stored up to v3.
+    #[derive(Decode)]
+    pub struct AnonymousRatingV3<AccountId> {
+        pub to: AccountId,
+        pub score: u8,
+        pub category_ratings: CategoryScores,
+        pub timestamp: u64,
+        pub review_hash: [u8; 32],
+    }
+
+    /// Rewrites ratings from any earlier layout: v1, v2 and v3 (the same as v2), and v4 itself
+    /// for ratings a restarted rewrite already translated.
+    pub struct MigrateRatingsToV4<T>(PhantomData<T>);
+
+    impl<T: Config> lazy::RatingsMigration<T> for MigrateRatingsToV4<T> {
+        const TO_VERSION: u16 = 4;
+
+        fn translate(raw: &[u8]) -> Option<RatingOf<T>> {
+            // Each layout only decodes from values of its exact length, and every layout is
+            // longer than the one before it, so at most one of these succeeds.
+            if let Ok(rating) = RatingOf::<T>::decode_all(&mut &raw[..]) {
+                return Some(rating)
+            }
+            if let Ok(old) = RatingV3::<T::AccountId, BalanceOf<T>>::decode_all(&mut &raw[..]) {
+                return Some(Rating {
+                    from: old.from,
+                    to: old.to,
+                    score: old.score,
+                    category_ratings: old.category_ratings,
+                    timestamp: old.timestamp,
+                    review_hash: ContentRef::sha2_256(old.review_hash),
+                    tip: old.tip,
+                })
+            }
+            let old = v2::RatingV1::<T::AccountId>::decode_all(&mut &raw[..]).ok()?;
+            Some(Rating {
+                from: old.from,
+                to: old.to,
+                score: old.score,
+                category_ratings: old.category_ratings,
+                timestamp: old.timestamp,
+                review_hash: ContentRef::sha2_256(old.review_hash),
+                tip: Zero::zero(),
+            })
+        }
+    }
+
+    pub struct MigrateToV4<T>(PhantomData<T>);
+
+    impl<T: Config> OnRuntimeUpgrade for MigrateToV4<T> {
+        fn on_runtime_upgrade() -> Weight {
+            let on_chain = Pallet::<T>::on_chain_storage_version();
+            if on_chain != 3 {
+                log::info!(target: LOG_TARGET, "skipping v4 migration, on-chain version is {:?}", on_chain);
+                return T::DbWeight::get().reads(1);
+            }
+
+            let mut translated = 0u64;
+            let mut cancelled = 0u64;
+
+            AnonymousRatings::<T>::translate::<AnonymousRatingV3<T::AccountId>, _>(|_, _, old| {
+                translated += 1;
+                Some(AnonymousRating {
+                    to: old.to,
+                    score: old.score,
+                    category_ratings: old.category_ratings,
+                    timestamp: old.timestamp,
+                    review_hash: ContentRef::sha2_256(old.review_hash),
+                })
+            });
+
+            Sessions::<T>::translate::<ReviewSessionV3<T::AccountId, BlockNumberFor<T>>, _>(|_, old| {
+                translated += 1;
+                Some(ReviewSession {
+                    first: old.first,
+                    second: old.second,
+                    verified: old.verified,
+                    deadline: old.deadline,
+                    first_rating: old.first_rating.map(Into::into),
+                    second_rating: old.second_rating.map(Into::into),
+                })
+            });
+
+            RatingCommits::<T>::translate::<RatingCommitmentV3<T::Hash, BalanceOf<T>, BlockNumberFor<T>>, _>(
+                |_, from, old| {
+                    translated += 1;
+                    let Some(revealed) = old.revealed else {
+                        cancelled += 1;
+                        T::Currency::unreserve(&from, old.deposit);
+                        return None
+                    };
+                    Some(RatingCommitment {
+                        commitment: old.commitment,
+                        deposit: old.deposit,
+                        closes_at: old.closes_at,
+                        revealed: Some(revealed.into()),
+                    })
+                },
+            );
+
+            StorageVersion::new(4).put::<Pallet<T>>();
+
+            log::info!(
+                target: LOG_TARGET,
+                "migrated {} review references to v4, cancelled {} unrevealed commitments",
+                translated,
+                cancelled
+            );
+            T::DbWeight::get().reads_writes(translated + 1, translated + cancelled + 1)
+        }
+
+        #[cfg(feature = "try-runtime")]
+        fn pre_upgrade() -> Result<Vec<u8>, TryRuntimeError> {
+            let anonymous = AnonymousRatings::<T>::iter_keys().count() as u32;
+            let sessions = Sessions::<T>::iter_keys().count() as u32;
+            Ok((anonymous, sessions).encode())
+        }
+
+        #[cfg(feature = "try-runtime")]
+        fn post_upgrade(state: Vec<u8>) -> Result<(), TryRuntimeError> {
+            let (anonymous, sessions) = <(u32, u32)>::decode(&mut &state[..])
+                .map_err(|_| TryRuntimeError::Other("invalid pre_upgrade state"))?;
+
+            ensure!(
+                AnonymousRatings::<T>::iter_values().count() as u32 == anonymous,
+                "some anonymous ratings no longer decode after the v4 migration"
+            );
+            ensure!(
+                Sessions::<T>::iter_values().count() as u32 == sessions,
+                "some sessions no longer decode after the v4 migration"
+            );
+            ensure!(
+                RatingCommits::<T>::iter_values().all(|commit| commit.revealed.is_some() && commit.deposit.is_zero()),
+                "an unrevealed commitment survived the v4 migration"
+            );
+            ensure!(
+                Pallet::<T>::on_chain_storage_version() >= 4,
+                "storage version was not bumped to v4"
+            );
+            Ok(())
+        }
+    }
+}
+
+/// Seeds `ReputationParams` on chains that predate it with `P`, the values the runtime used
+/// to pass in as `MinStakeAmount` and `MaxReviewsPerUser`.
+///
+/// Only writes while the item is unset, so it can stay in `Executive` without touching values
+/// governance has changed since.
+pub struct InitParams<T, P>(PhantomData<(T, P)>);
+
+impl<T: Config, P: Get<ParamsOf<T>>> OnRuntimeUpgrade for InitParams<T, P> {
+    fn on_runtime_upgrade() -> Weight {
+        if ReputationParams::<T>::exists() {
+            return T::DbWeight::get().reads(1);
+        }
+
+        ReputationParams::<T>::put(P::get());
+
+        log::info!(target: LOG_TARGET, "initialised ReputationParams");
+        T::DbWeight::get().reads_writes(1, 1)
+    }
+
+    #[cfg(feature = "try-runtime")]
+    fn post_upgrade(_state: Vec<u8>) -> Result<(), TryRuntimeError> {
+        ensure!(ReputationParams::<T>::exists(), "ReputationParams was not initialised");
+        ensure!(
+            ReputationParams::<T>::get().max_reviews_per_user > 0,
+            "ReputationParams would reject every rating"
+        );
+        Ok(())
+    }
+}
+
+/// Multi-block migration of the `Ratings` map.
+///
+/// A runtime that changes the `Rating` layout sets `Config::RatingsMigration` to a
+/// [`RatingsMigration`] for the new version and lists [`StartRatingsMigration`] in its
+/// `Executive` migrations, next to a single-block migration that bumps the version. From then on
+/// `on_idle` rewrites as many ratings per block as the leftover weight allows, until the map is
+/// exhausted.
+/// Calls that write ratings are rejected while the rewrite is in progress.
+pub mod lazy {
+    use super::*;
+    use frame_support::storage::{unhashed, StoragePrefixedMap};
+
+    /// A rewrite of every stored `Rating` into the current layout.
+    pub trait RatingsMigration<T: Config> {
+        /// Storage version the pallet is at once every rating has been rewritten.
+        const TO_VERSION: u16;
+
+        /// Decode a rating stored in any earlier layout. Returning `None` leaves the raw value
+        /// in place, still counted in its profile's aggregates, for manual repair.
+        fn translate(raw: &[u8]) -> Option<RatingOf<T>>;
+    }
+
+    /// No pending rewrite.
+    impl<T: Config> RatingsMigration<T> for () {
+        const TO_VERSION: u16 = 0;
+
+        fn translate(_raw: &[u8]) -> Option<RatingOf<T>> {
+            None
+        }
+    }
+
+    /// Starts `T::RatingsMigration` if the on-chain version is behind it.
+    ///
+    /// Has to run before the single-block migrations bump the version. A rewrite to an older
+    /// layout that is still running is started over, since the ratings it already passed are
+    /// in that older layout; a rewrite to the current target is left alone.
+    pub struct StartRatingsMigration<T>(PhantomData<T>);
+
+    impl<T: Config> OnRuntimeUpgrade for StartRatingsMigration<T> {
+        fn on_runtime_upgrade() -> Weight {
+            let to = StorageVersion::new(T::RatingsMigration::TO_VERSION);
+            let on_chain = Pallet::<T>::on_chain_storage_version();
+
+            if T::RatingsMigration::TO_VERSION == 0 || on_chain >= to {
+                return T::DbWeight::get().reads(1);
+            }
+
+            let cursor: BoundedVec<u8, ConstU32<MAX_CURSOR_LEN>> = Ratings::<T>::final_prefix()
+                .to_vec()
+                .try_into()
+                .expect("storage prefix is 32 bytes; qed");
+            RatingsMigrationCursor::<T>::put(cursor);
+
+            log::info!(target: LOG_TARGET, "started lazy Ratings migration to {:?}", to);
+            T::DbWeight::get().reads_writes(1, 1)
+        }
+
+        #[cfg(feature = "try-runtime")]
+        fn pre_upgrade() -> Result<Vec<u8>, TryRuntimeError> {
+            Ok(Pallet::<T>::on_chain_storage_version().encode())
+        }
+
+        #[cfg(feature = "try-runtime")]
+        fn post_upgrade(state: Vec<u8>) -> Result<(), TryRuntimeError> {
+            let before = StorageVersion::decode(&mut &state[..])
+                .map_err(|_| TryRuntimeError::Other("invalid pre_upgrade state"))?;
+            let to = StorageVersion::new(T::RatingsMigration::TO_VERSION);
+            ensure!(
+                before >= to || RatingsMigrationCursor::<T>::exists(),
+                "Ratings are behind the target version but no lazy migration was started"
+            );
+            Ok(())
+        }
+    }
+
+    /// Rewrite ratings from the stored cursor until `limit` is used up.
+    ///
+    /// Returns the weight consumed. Finishing the map clears the cursor; the storage version was
+    /// bumped by the upgrade that started the rewrite.
+    pub fn step<T: Config>(limit: Weight) -> Weight {
+        let db = T::DbWeight::get();
+        let mut used = db.reads(1);
+
+        let Some(cursor) = RatingsMigrationCursor::<T>::get() else { return used };
+
+        let per_item = db.reads_writes(2, 2);
+        let mut keys = Ratings::<T>::iter_keys_from(cursor.into_inner());
+        let mut last_key = None;
+
+        loop {
+            if used.saturating_add(per_item).any_gt(limit) {
+                break
+            }
+
+            let Some((to, from)) = keys.next() else {
+                RatingsMigrationCursor::<T>::kill();
+                Pallet::<T>::deposit_event(Event::RatingsMigrated {
+                    version: T::RatingsMigration::TO_VERSION,
+                });
+                log::info!(target: LOG_TARGET, "lazy Ratings migration finished");
+                return used.saturating_add(db.writes(1))
+            };
+
+            let key = Ratings::<T>::hashed_key_for(&to, &from);
+            match unhashed::get_raw(&key).and_then(|raw| T::RatingsMigration::translate(&raw)) {
+                Some(rating) => Ratings::<T>::insert(&to, &from, rating),
+                // Removing it would leave the profile's aggregates counting a rating that is
+                // gone, and nothing could tell what it was.
+                None => log::error!(
+                    target: LOG_TARGET,
+                    "leaving rating at {:?} for manual repair: it decodes in no known layout",
+                    key,
+                ),
+            }
+
+            used = used.saturating_add(per_item);
+            last_key = Some(key);
+        }
+
+        if let Some(key) = last_key {
+            if let Ok(cursor) = key.try_into() {
+                RatingsMigrationCursor::<T>::put::<BoundedVec<u8, ConstU32<MAX_CURSOR_LEN>>>(cursor);
+                used = used.saturating_add(db.writes(1));
+            }
+        }
+
+        used
+    }
+}
@@ -0,0 +1,279 @@
+//! Test runtime for the pallet.
+
+use crate as pallet_reputation;
+use crate::{anonymous, OnRatingSubmitted};
+use frame_support::{
+    construct_runtime, parameter_types,
+    traits::{ConstU128, ConstU32, ConstU64, Hooks, Randomness, UnixTime},
+    PalletId,
+};
+use frame_system::EnsureRoot;
+use sp_core::H256;
+use sp_runtime::{
+    testing::TestXt,
+    traits::{BlakeTwo256, Hash, IdentityLookup},
+    AccountId32, BuildStorage, MultiSignature, MultiSigner, Perbill,
+};
+use std::cell::RefCell;
+
+pub type AccountId = AccountId32;
+pub type Balance = u128;
+type Block = frame_system::mocking::MockBlock<Test>;
+
+construct_runtime!(
+    pub struct Test
+    {
+        System: frame_system,
+        Balances: pallet_balances,
+        Reputation: pallet_reputation,
+    }
+);
+
+impl frame_system::Config for Test {
+    type BaseCallFilter = frame_support::traits::Everything;
+    type BlockWeights = ();
+    type BlockLength = ();
+    type DbWeight = ();
+    type RuntimeOrigin = RuntimeOrigin;
+    type RuntimeCall = RuntimeCall;
+    type Nonce = u64;
+    type Hash = H256;
+    type Hashing = BlakeTwo256;
+    type AccountId = AccountId;
+    type Lookup = IdentityLookup<AccountId>;
+    type Block = Block;
+    type RuntimeEvent = RuntimeEvent;
+    type BlockHashCount = ConstU64<250>;
+    type Version = ();
+    type PalletInfo = PalletInfo;
+    type AccountData = pallet_balances::AccountData<Balance>;
+    type OnNewAccount = ();
+    type OnKilledAccount = ();
+    type SystemWeightInfo = ();
+    type SS58Prefix = ();
+    type OnSetCode = ();
+    type MaxConsumers = ConstU32<16>;
+}
+
+impl pallet_balances::Config for Test {
+    type MaxLocks = ();
+    type MaxReserves = ();
+    type ReserveIdentifier = [u8; 8];
+    type Balance = Balance;
+    type RuntimeEvent = RuntimeEvent;
+    type DustRemoval = ();
+    type ExistentialDeposit = ConstU128<1>;
+    type AccountStore = System;
+    type WeightInfo = ();
+    type FreezeIdentifier = ();
+    type MaxFreezes = ();
+    type RuntimeHoldReason = ();
+    type MaxHolds = ();
+}
+
+impl frame_system::offchain::SigningTypes for Test {
+    type Public = MultiSigner;
+    type Signature = MultiSignature;
+}
+
+impl<C> frame_system::offchain::SendTransactionTypes<C> for Test
+where
+    RuntimeCall: From<C>,
+{
+    type Extrinsic = TestXt<RuntimeCall, ()>;
+    type OverarchingCall = RuntimeCall;
+}
+
+thread_local! {
+    static NOW: RefCell<u128> = RefCell::new(0);
+    static SUBMITTED: RefCell<Vec<(Option<AccountId>, AccountId, u8)>> = RefCell::new(Vec::new());
+}
+
+/// Set the milliseconds `TestClock` reports.
+pub fn set_now(millis: u128) {
+    NOW.with(|now| *now.borrow_mut() = millis);
+}
+
+pub struct TestClock;
+
+impl UnixTime for TestClock {
+    fn now() -> core::time::Duration {
+        core::time::Duration::from_millis(NOW.with(|now| *now.borrow()) as u64)
+    }
+}
+
+/// `(from, to, score)` of every rating `RecordRatings` was told about; `from` is `None` for
+/// anonymous ratings.
+pub fn submitted_ratings() -> Vec<(Option<AccountId>, AccountId, u8)> {
+    SUBMITTED.with(|submitted| submitted.borrow().clone())
+}
+
+pub struct RecordRatings;
+
+impl OnRatingSubmitted<AccountId> for RecordRatings {
+    fn on_rating_submitted(from: &AccountId, to: &AccountId, score: u8) {
+        SUBMITTED.with(|submitted| submitted.borrow_mut().push((Some(from.clone()), to.clone(), score)));
+    }
+
+    fn on_anonymous_rating_submitted(to: &AccountId, score: u8) {
+        SUBMITTED.with(|submitted| submitted.borrow_mut().push((None, to.clone(), score)));
+    }
+}
+
+/// Deterministic per subject and block, which is all juror draws need here.
+pub struct TestRandomness;
+
+impl Randomness<H256, u64> for TestRandomness {
+    fn random(subject: &[u8]) -> (H256, u64) {
+        let block = System::block_number();
+        (BlakeTwo256::hash_of(&(subject, block)), block)
+    }
+}
+
+/// Accepts a proof equal to the signal it is for, so tests can build valid and invalid proofs
+/// without a circuit.
+pub struct TestVerifier;
+
+impl anonymous::MembershipVerifier for TestVerifier {
+    fn hash_pair(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+        anonymous::Groth16Bn254::hash_pair(left, right)
+    }
+
+    fn verify(_verifying_key: &[u8], inputs: &anonymous::PublicInputs, proof: &[u8]) -> bool {
+        proof == &inputs.signal[..]
+    }
+}
+
+parameter_types! {
+    pub const ReputationPalletId: PalletId = PalletId(*b"py/reput");
+    pub const NoShowSlash: Perbill = Perbill::from_percent(10);
+    pub const UnrevealedSlash: Perbill = Perbill::from_percent(50);
+}
+
+impl pallet_reputation::Config for Test {
+    type RuntimeEvent = RuntimeEvent;
+    type ParamsOrigin = EnsureRoot<AccountId>;
+    type ModerationOrigin = EnsureRoot<AccountId>;
+    type OnRatingSubmitted = RecordRatings;
+    type OnProfileChanged = ();
+    type RatingsMigration = ();
+    type Currency = Balances;
+    type SlashOrigin = EnsureRoot<AccountId>;
+    type Slash = ();
+    type PalletId = ReputationPalletId;
+    type MinVouchAmount = ConstU128<10>;
+    type MaxVouchersPerProfile = ConstU32<4>;
+    type MaxUnbondingChunks = ConstU32<2>;
+    type VouchUnbondingPeriod = ConstU64<10>;
+    type MinHelpfulnessVoterReviews = ConstU32<1>;
+    type Randomness = TestRandomness;
+    type FlagDeposit = ConstU128<10>;
+    type FlagThreshold = ConstU32<2>;
+    type JurorBond = ConstU128<100>;
+    type MinJurorScore = ConstU32<4>;
+    type MinJurorReviews = ConstU32<1>;
+    type MaxJurors = ConstU32<10>;
+    type PanelSize = ConstU32<3>;
+    type JuryDrawDelay = ConstU64<2>;
+    type CommitPeriod = ConstU64<5>;
+    type RevealPeriod = ConstU64<5>;
+    type NoShowSlash = NoShowSlash;
+    type MaxSuspensionsPerBlock = ConstU32<2>;
+    type AppealOrigin = EnsureRoot<AccountId>;
+    type AppealBond = ConstU128<50>;
+    type AppealPeriod = ConstU64<10>;
+    type AppealDecisionPeriod = ConstU64<10>;
+    type MaxAppeals = ConstU32<4>;
+    type RatingCommitDeposit = ConstU128<20>;
+    type RatingRevealWindow = ConstU64<5>;
+    type UnrevealedSlash = UnrevealedSlash;
+    type MaxCommitsPerBlock = ConstU32<2>;
+    type SessionOrigin = EnsureRoot<AccountId>;
+    type SessionDuration = ConstU64<10>;
+    type MaxSessionsPerBlock = ConstU32<2>;
+    type SessionDeposit = ConstU128<30>;
+    type AnonymityVerifier = TestVerifier;
+    type EpochLength = ConstU64<10>;
+    type ContentDepositBase = ConstU128<5>;
+    type ContentDepositPerByte = ConstU128<1>;
+    type MaxContentLen = ConstU32<64>;
+    type ContentReporterId = crate::offchain::crypto::ContentReporterId;
+    type MaxContentChecks = ConstU32<2>;
+    type ContentRecheckPeriod = ConstU64<10>;
+    type UnixTime = TestClock;
+}
+
+/// Funds every test account starts with.
+pub const ENDOWMENT: Balance = 1_000;
+
+pub fn account(n: u8) -> AccountId {
+    AccountId32::new([n; 32])
+}
+
+/// Accounts 1 to 8 are endowed with `ENDOWMENT`; accounts 1 to 4 also have profiles.
+#[derive(Default)]
+pub struct ExtBuilder {
+    params: crate::ParamsOf<Test>,
+    ratings: Vec<(AccountId, AccountId, u8, [u8; 4])>,
+}
+
+impl ExtBuilder {
+    pub fn params(mut self, params: crate::ParamsOf<Test>) -> Self {
+        self.params = params;
+        self
+    }
+
+    /// Genesis ratings, as `(from, to, score)` with every category at `score`.
+    pub fn ratings(mut self, ratings: &[(u8, u8, u8)]) -> Self {
+        self.ratings = ratings
+            .iter()
+            .map(|&(from, to, score)| (account(from), account(to), score, [score; 4]))
+            .collect();
+        self
+    }
+
+    pub fn build(self) -> sp_io::TestExternalities {
+        // Genesis ratings are timestamped too.
+        set_now(0);
+        let mut storage = frame_system::GenesisConfig::<Test>::default().build_storage().unwrap();
+
+        pallet_balances::GenesisConfig::<Test> { balances: (1..=8).map(|n| (account(n), ENDOWMENT)).collect() }
+            .assimilate_storage(&mut storage)
+            .unwrap();
+
+        pallet_reputation::GenesisConfig::<Test> {
+            params: self.params,
+            profiles: (1..=4).map(account).collect(),
+            stakes: Vec::new(),
+            ratings: self.ratings,
+            content_reporters: Vec::new(),
+        }
+        .assimilate_storage(&mut storage)
+        .unwrap();
+
+        let mut ext = sp_io::TestExternalities::new(storage);
+        ext.execute_with(|| {
+            SUBMITTED.with(|submitted| submitted.borrow_mut().clear());
+            System::set_block_number(1);
+        });
+        ext
+    }
+
+    /// Build, run `test` and check the storage invariants it left behind.
+    pub fn build_and_execute(self, test: impl FnOnce()) {
+        self.build().execute_with(|| {
+            test();
+            Reputation::do_try_state().unwrap();
+        })
+    }
+}
+
+/// Run the pallet's block hooks up to and including block `n`.
+pub fn run_to_block(n: u64) {
+    while System::block_number() < n {
+        let now = System::block_number();
+        Reputation::on_finalize(now);
+        System::set_block_number(now + 1);
+        Reputation::on_initialize(now + 1);
+    }
+}
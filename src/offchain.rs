@@ -0,0 +1,243 @@
+//! Off-chain checking of review content.
+//!
+//! Nodes with a content reporter key in their keystore fetch the content behind ratings'
+//! `review_hash` from an HTTP gateway, hash it, and report whether it matched through
+//! `report_content`: an unsigned transaction carrying a payload signed by the reporter key,
+//! which must belong to an account governance added with `add_content_reporter`.
+//!
+//! The gateway is read from the node's persistent offchain storage under [`GATEWAY_KEY`], for
+//! example through the `offchain_localStorageSet` RPC; workers do nothing until it is set.
+//! Content is requested as `<gateway><CIDv1 in base32>?format=raw` with an `Accept` of
+//! `application/vnd.ipld.raw`, so IPFS gateways return the block the CID hashes rather than the
+//! file or document decoded from it. A static server laid out the same way answers it too, but
+//! for `raw` and bare multihash references only: a mismatch on any other codec may be a server
+//! that ignored the request and is not reported. Each run checks up to `MaxContentChecks` ratings
+//! that have not been checked within `ContentRecheckPeriod`, resuming where the last run
+//! stopped.
+
+use super::*;
+use crate::content::{ContentRef, RAW};
+use frame_support::pallet_prelude::*;
+use frame_system::{
+    offchain::{SendUnsignedTransaction, SignedPayload, Signer, SigningTypes},
+    pallet_prelude::BlockNumberFor,
+};
+use sp_core::crypto::KeyTypeId;
+use sp_runtime::{
+    offchain::{
+        http,
+        storage::StorageValueRef,
+        storage_lock::{StorageLock, Time},
+        Duration, StorageKind,
+    },
+    traits::{IdentifyAccount, Saturating},
+};
+use sp_std::vec::Vec;
+
+/// Key type of content reporter keys.
+pub const KEY_TYPE: KeyTypeId = KeyTypeId(*b"rcnt");
+
+/// Offchain storage key of the gateway base URL, e.g. `http://127.0.0.1:8080/ipfs/`.
+pub const GATEWAY_KEY: &[u8] = b"reputation::content-gateway";
+
+const CURSOR_KEY: &[u8] = b"reputation::content-cursor";
+const LOCK_KEY: &[u8] = b"reputation::content-lock";
+
+/// Larger bodies are not read past this and count as missing.
+const MAX_FETCH_LEN: usize = 1024 * 1024;
+
+const FETCH_TIMEOUT_MS: u64 = 5_000;
+
+pub mod crypto {
+    use super::KEY_TYPE;
+    use sp_core::sr25519::{Public as Sr25519Public, Signature as Sr25519Signature};
+    use sp_runtime::{
+        app_crypto::{app_crypto, sr25519},
+        MultiSignature, MultiSigner,
+    };
+
+    app_crypto!(sr25519, KEY_TYPE);
+
+    /// Signs content reports with an sr25519 key of type `rcnt`.
+    pub struct ContentReporterId;
+
+    impl frame_system::offchain::AppCrypto<MultiSigner, MultiSignature> for ContentReporterId {
+        type RuntimeAppPublic = Public;
+        type GenericSignature = Sr25519Signature;
+        type GenericPublic = Sr25519Public;
+    }
+}
+
+/// What a reporter found behind a rating's `review_hash`.
+#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo)]
+pub struct ContentReport<Public, AccountId, BlockNumber> {
+    pub to: AccountId,
+    pub from: AccountId,
+    /// The reference checked, so a report cannot outlive the rating it was made for.
+    pub review_hash: ContentRef,
+    pub status: ContentStatus,
+    /// Block the worker ran at.
+    pub at: BlockNumber,
+    pub public: Public,
+}
+
+pub type ContentReportOf<T> =
+    ContentReport<<T as SigningTypes>::Public, <T as frame_system::Config>::AccountId, BlockNumberFor<T>>;
+
+impl<T: SigningTypes> SignedPayload<T> for ContentReport<T::Public, T::AccountId, BlockNumberFor<T>> {
+    fn public(&self) -> T::Public {
+        self.public.clone()
+    }
+}
+
+/// Lowercase RFC 4648 base32 without padding, as multibase `b` uses.
+fn base32(bytes: &[u8]) -> Vec<u8> {
+    const ALPHABET: &[u8; 32] = b"abcdefghijklmnopqrstuvwxyz234567";
+    let mut out = Vec::with_capacity((bytes.len() * 8 + 4) / 5);
+    let mut buffer = 0u16;
+    let mut bits = 0;
+    for &byte in bytes {
+        buffer = (buffer << 8) | byte as u16;
+        bits += 8;
+        while bits >= 5 {
+            bits -= 5;
+            out.push(ALPHABET[((buffer >> bits) & 0x1f) as usize]);
+        }
+    }
+    if bits > 0 {
+        out.push(ALPHABET[((buffer << (5 - bits)) & 0x1f) as usize]);
+    }
+    out
+}
+
+/// `<gateway>b<base32 CIDv1>?format=raw`.
+fn content_url(gateway: &[u8], review_hash: &ContentRef) -> Vec<u8> {
+    let mut url = gateway.to_vec();
+    url.push(b'b');
+    url.extend(base32(&review_hash.to_cid_bytes()));
+    url.extend_from_slice(b"?format=raw");
+    url
+}
+
+/// Fetch `url`. `Ok(None)` means the gateway answered that there is no such content; errors
+/// are transient and not reported.
+fn fetch(url: &str) -> Result<Option<Vec<u8>>, http::Error> {
+    let deadline = sp_io::offchain::timestamp().add(Duration::from_millis(FETCH_TIMEOUT_MS));
+    let pending = http::Request::get(url)
+        .add_header("Accept", "application/vnd.ipld.raw")
+        .deadline(deadline)
+        .send()
+        .map_err(|_| http::Error::IoError)?;
+    let response = pending.try_wait(deadline).map_err(|_| http::Error::DeadlineReached)??;
+
+    match response.code {
+        200 => {
+            let body: Vec<u8> = response.body().take(MAX_FETCH_LEN + 1).collect();
+            Ok((body.len() <= MAX_FETCH_LEN).then_some(body))
+        },
+        404 | 410 => Ok(None),
+        _ => Err(http::Error::Unknown),
+    }
+}
+
+impl<T: Config> Pallet<T> {
+    /// Check the next batch of ratings against the gateway. Run from `offchain_worker`.
+    pub(crate) fn check_content(now: BlockNumberFor<T>) {
+        // Raw bytes, as `offchain_localStorageSet` writes them.
+        let Some(gateway) = sp_io::offchain::local_storage_get(StorageKind::PERSISTENT, GATEWAY_KEY) else {
+            return
+        };
+
+        let signer = Signer::<T, T::ContentReporterId>::any_account();
+        if !signer.can_sign() {
+            return
+        }
+
+        // One worker at a time; a run that overlaps the previous one would report twice.
+        let mut lock = StorageLock::<Time>::with_deadline(
+            LOCK_KEY,
+            Duration::from_millis(FETCH_TIMEOUT_MS * T::MaxContentChecks::get() as u64),
+        );
+        let Ok(_guard) = lock.try_lock() else { return };
+
+        let cursor = StorageValueRef::persistent(CURSOR_KEY);
+        let mut ratings = match cursor.get::<Vec<u8>>() {
+            Ok(Some(last)) => Ratings::<T>::iter_from(last),
+            _ => Ratings::<T>::iter(),
+        };
+
+        let mut checked = 0;
+        while checked < T::MaxContentChecks::get() {
+            let Some((to, from, rating)) = ratings.next() else {
+                // Start over next run.
+                cursor.clear();
+                return
+            };
+            cursor.set(&Ratings::<T>::hashed_key_for(&to, &from));
+
+            let recent = ContentChecks::<T>::get(&to, &from)
+                .map_or(false, |check| check.at.saturating_add(T::ContentRecheckPeriod::get()) > now);
+            if recent {
+                continue
+            }
+            checked += 1;
+
+            let url = content_url(&gateway, &rating.review_hash);
+            let Ok(url) = sp_std::str::from_utf8(&url) else { continue };
+            let status = match fetch(url) {
+                Ok(Some(body)) => match rating.review_hash.matches(&body) {
+                    Some(true) => ContentStatus::ContentVerified,
+                    Some(false) if rating.review_hash.parts().codec.map_or(true, |codec| codec == RAW) => {
+                        ContentStatus::ContentMissing
+                    },
+                    // Possibly decoded content from a server without raw block responses.
+                    Some(false) => continue,
+                    // A hash function the runtime cannot compute.
+                    None => continue,
+                },
+                Ok(None) => ContentStatus::ContentMissing,
+                Err(e) => {
+                    log::debug!(target: migrations::LOG_TARGET, "fetching {} failed: {:?}", url, e);
+                    continue
+                },
+            };
+
+            let result = signer.send_unsigned_transaction(
+                |account| ContentReport {
+                    to: to.clone(),
+                    from: from.clone(),
+                    review_hash: rating.review_hash.clone(),
+                    status: status.clone(),
+                    at: now,
+                    public: account.public.clone(),
+                },
+                |report, signature| Call::report_content { report, signature },
+            );
+            if let Some((_, Err(()))) = result {
+                log::warn!(target: migrations::LOG_TARGET, "failed to submit content report for {:?}", (&to, &from));
+            }
+        }
+    }
+
+    /// Everything about a content report except applying it. Shared by the call and
+    /// `validate_unsigned`.
+    pub(crate) fn check_content_report(report: &ContentReportOf<T>, signature: &T::Signature) -> DispatchResult {
+        ensure!(
+            SignedPayload::<T>::verify::<T::ContentReporterId>(report, signature.clone()),
+            Error::<T>::InvalidReport
+        );
+        let reporter = report.public.clone().into_account();
+        ensure!(ContentReporters::<T>::contains_key(&reporter), Error::<T>::NotContentReporter);
+
+        let rating = Ratings::<T>::get(&report.to, &report.from).ok_or(Error::<T>::RatingNotFound)?;
+        ensure!(rating.review_hash == report.review_hash, Error::<T>::InvalidReport);
+        // A report dated ahead would block every honest one until then.
+        ensure!(report.at <= frame_system::Pallet::<T>::block_number(), Error::<T>::InvalidReport);
+        ensure!(
+            ContentChecks::<T>::get(&report.to, &report.from).map_or(true, |check| check.at < report.at),
+            Error::<T>::InvalidReport
+        );
+
+        Ok(())
+    }
+}
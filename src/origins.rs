@@ -0,0 +1,61 @@
+//! Origins other pallets can use to gate calls on reputation.
+
+use super::*;
+use frame_support::traits::{EnsureOrigin, Get};
+use sp_std::marker::PhantomData;
+
+/// Accepts a signed origin whose profile is `Active`, averages at least `MinScore` stars over
+/// at least `MinReviews` reviews and has `MinStake` staked. Succeeds with the account.
+///
+/// ```ignore
+/// type ProposeOrigin = pallet_reputation::EnsureReputation<Runtime, ConstU32<4>, ConstU32<20>, ConstU128<0>>;
+/// ```
+pub struct EnsureReputation<T, MinScore, MinReviews, MinStake>(
+    PhantomData<(T, MinScore, MinReviews, MinStake)>,
+);
+
+impl<T, MinScore, MinReviews, MinStake> EnsureOrigin<T::RuntimeOrigin>
+    for EnsureReputation<T, MinScore, MinReviews, MinStake>
+where
+    T: Config,
+    MinScore: Get<u32>,
+    MinReviews: Get<u32>,
+    MinStake: Get<u128>,
+{
+    type Success = T::AccountId;
+
+    fn try_origin(o: T::RuntimeOrigin) -> Result<Self::Success, T::RuntimeOrigin> {
+        o.into().and_then(|o| match o {
+            frame_system::RawOrigin::Signed(who)
+                if Pallet::<T>::meets_reputation(&who, MinScore::get(), MinReviews::get(), MinStake::get()) =>
+                Ok(who),
+            r => Err(T::RuntimeOrigin::from(r)),
+        })
+    }
+
+    #[cfg(feature = "runtime-benchmarks")]
+    fn try_successful_origin() -> Result<T::RuntimeOrigin, ()> {
+        let who: T::AccountId = frame_benchmarking::account("reputable", 0, 0);
+        let reviews = MinReviews::get().max(1);
+        let score = MinScore::get().max(1);
+
+        UserReputation::<T>::insert(
+            &who,
+            ReputationProfile {
+                total_score: score.saturating_mul(reviews),
+                review_count: reviews,
+                category_scores: CategoryScores {
+                    communication: score.saturating_mul(reviews),
+                    reliability: score.saturating_mul(reviews),
+                    quality: score.saturating_mul(reviews),
+                    professionalism: score.saturating_mul(reviews),
+                },
+                status: ProfileStatus::Active,
+                staked_amount: MinStake::get(),
+                owner: who.clone(),
+            },
+        );
+
+        Ok(frame_system::RawOrigin::Signed(who).into())
+    }
+}
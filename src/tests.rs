@@ -0,0 +1,1023 @@
+use crate::{
+    anonymous,
+    content::ContentRef,
+    indexing::{IndexEntry, Rater, RatingChange, RatingRecord},
+    mock::*,
+    offchain::ContentReport,
+    *,
+};
+use codec::Encode;
+use frame_support::{
+    assert_noop, assert_ok,
+    storage::{unhashed, StoragePrefixedMap},
+    traits::{ConstU128, ConstU32, Currency, EnsureOrigin, UnfilteredDispatchable},
+    unsigned::ValidateUnsigned,
+    weights::Weight,
+    BoundedVec,
+};
+use sp_core::{sr25519, Pair, H256};
+use sp_runtime::{
+    traits::{BlakeTwo256, Hash, IdentifyAccount},
+    transaction_validity::TransactionSource,
+    DispatchResult, MultiSignature, MultiSigner, Perbill,
+};
+
+fn review(n: u8) -> ContentRef {
+    ContentRef::sha2_256([n; 32])
+}
+
+fn rate(from: u8, to: u8, score: u8) -> DispatchResult {
+    Reputation::submit_rating(
+        RuntimeOrigin::signed(account(from)),
+        account(to),
+        score,
+        score,
+        score,
+        score,
+        score,
+        review(from),
+    )
+}
+
+fn create_profiles(accounts: &[u8]) {
+    for &n in accounts {
+        assert_ok!(Reputation::create_profile(RuntimeOrigin::signed(account(n))));
+    }
+}
+
+fn start_ratings_migration() {
+    let cursor = Ratings::<Test>::final_prefix().to_vec();
+    RatingsMigrationCursor::<Test>::put(BoundedVec::truncate_from(cursor));
+}
+
+#[test]
+fn lazy_migration_keeps_ratings_it_cannot_translate() {
+    ExtBuilder::default().ratings(&[(5, 1, 4)]).build().execute_with(|| {
+        let key = Ratings::<Test>::hashed_key_for(account(1), account(5));
+        unhashed::put_raw(&key, b"no known layout");
+        start_ratings_migration();
+
+        assert_noop!(rate(6, 1, 3), Error::<Test>::MigrationInProgress);
+
+        migrations::lazy::step::<Test>(Weight::MAX);
+
+        // Still there and still counted, for manual repair.
+        assert_eq!(unhashed::get_raw(&key), Some(b"no known layout".to_vec()));
+        assert_eq!(RatingCount::<Test>::get(account(1)), 1);
+        assert_eq!(Reputation::user_reputation(account(1)).unwrap().review_count, 1);
+        assert!(!RatingsMigrationCursor::<Test>::exists());
+        System::assert_last_event(Event::RatingsMigrated { version: 0 }.into());
+
+        assert_ok!(rate(6, 1, 3));
+    });
+}
+
+#[test]
+fn try_state_holds_after_ratings() {
+    ExtBuilder::default().ratings(&[(5, 1, 4), (6, 1, 2)]).build_and_execute(|| {
+        assert_ok!(rate(7, 1, 5));
+        assert_ok!(rate(1, 2, 3));
+    });
+}
+
+#[test]
+fn try_state_detects_drifted_aggregates() {
+    ExtBuilder::default().ratings(&[(5, 1, 4)]).build().execute_with(|| {
+        assert_ok!(Reputation::do_try_state());
+
+        RatingCount::<Test>::insert(account(1), 2);
+        assert!(Reputation::do_try_state().is_err());
+
+        RatingCount::<Test>::insert(account(1), 1);
+        UserReputation::<Test>::mutate(account(1), |profile| profile.as_mut().unwrap().total_score += 1);
+        assert!(Reputation::do_try_state().is_err());
+    });
+}
+
+#[test]
+fn try_state_counts_undecodable_ratings() {
+    ExtBuilder::default().ratings(&[(5, 1, 4), (6, 1, 2)]).build_and_execute(|| {
+        unhashed::put_raw(&Ratings::<Test>::hashed_key_for(account(1), account(5)), b"no known layout");
+    });
+}
+
+#[test]
+fn genesis_builds_profiles_and_free_ratings() {
+    ExtBuilder::default().ratings(&[(5, 1, 4), (6, 1, 2)]).build_and_execute(|| {
+        let profile = Reputation::user_reputation(account(1)).unwrap();
+        assert_eq!((profile.total_score, profile.review_count), (6, 2));
+        assert_eq!(Balances::reserved_balance(account(5)), 0);
+        assert_eq!(Balances::free_balance(Reputation::account_id()), 1);
+
+        // The default parameters accept ratings.
+        assert_ok!(rate(7, 1, 5));
+    });
+}
+
+#[test]
+#[should_panic(expected = "invalid genesis params")]
+fn genesis_rejects_invalid_params() {
+    let params = ReputationParameters { max_reviews_per_user: 0, ..Default::default() };
+    ExtBuilder::default().params(params).build();
+}
+
+#[test]
+#[should_panic(expected = "invalid genesis rating")]
+fn genesis_rejects_invalid_ratings() {
+    ExtBuilder::default().ratings(&[(1, 1, 4)]).build();
+}
+
+#[test]
+fn unvouched_funds_unbond_before_withdrawal() {
+    ExtBuilder::default().build_and_execute(|| {
+        let (voucher, target) = (account(1), account(2));
+        assert_noop!(
+            Reputation::vouch(RuntimeOrigin::signed(voucher.clone()), target.clone(), 9),
+            Error::<Test>::InsufficientVouch
+        );
+        assert_noop!(
+            Reputation::vouch(RuntimeOrigin::signed(voucher.clone()), voucher.clone(), 100),
+            Error::<Test>::CannotVouchSelf
+        );
+
+        assert_ok!(Reputation::vouch(RuntimeOrigin::signed(voucher.clone()), target.clone(), 100));
+        assert_eq!(Balances::reserved_balance(&voucher), 100);
+        assert_eq!(BackedBy::<Test>::get(&target), 100);
+
+        assert_ok!(Reputation::unvouch(RuntimeOrigin::signed(voucher.clone()), target.clone(), 40));
+        assert_eq!(BackedBy::<Test>::get(&target), 60);
+        assert_noop!(
+            Reputation::withdraw_unvouched(RuntimeOrigin::signed(voucher.clone()), target.clone()),
+            Error::<Test>::NothingToWithdraw
+        );
+
+        run_to_block(11);
+        assert_ok!(Reputation::withdraw_unvouched(RuntimeOrigin::signed(voucher.clone()), target.clone()));
+        assert_eq!(Balances::reserved_balance(&voucher), 60);
+        System::assert_last_event(Event::VouchWithdrawn { voucher, target, amount: 40 }.into());
+    });
+}
+
+#[test]
+fn penalising_slashes_vouches_into_the_pot() {
+    ExtBuilder::default().build_and_execute(|| {
+        let (voucher, target) = (account(1), account(2));
+        assert_ok!(Reputation::vouch(RuntimeOrigin::signed(voucher.clone()), target.clone(), 100));
+        assert_ok!(Reputation::unvouch(RuntimeOrigin::signed(voucher.clone()), target.clone(), 40));
+
+        assert_ok!(Reputation::penalise(RuntimeOrigin::root(), target.clone(), Perbill::from_percent(50)));
+
+        // Unbonding chunks are slashed too.
+        assert_eq!(Balances::reserved_balance(&voucher), 50);
+        assert_eq!(BackedBy::<Test>::get(&target), 30);
+        assert_eq!(Balances::free_balance(Reputation::account_id()), 51);
+        System::assert_has_event(Event::VoucherSlashed { voucher, target, amount: 50 }.into());
+    });
+}
+
+#[test]
+fn tips_are_transferred_with_the_rating() {
+    ExtBuilder::default().build_and_execute(|| {
+        assert_ok!(Reputation::submit_rating_with_tip(
+            RuntimeOrigin::signed(account(5)),
+            account(1),
+            5,
+            5,
+            5,
+            5,
+            5,
+            review(5),
+            100,
+        ));
+
+        assert_eq!(Balances::free_balance(account(1)), ENDOWMENT + 100);
+        assert_eq!(TipsReceived::<Test>::get(account(1)), 100);
+        assert_eq!(Reputation::ratings(account(1), account(5)).unwrap().tip, 100);
+        System::assert_last_event(Event::RatingTipped { from: account(5), to: account(1), amount: 100 }.into());
+
+        // The tip cannot reap the rater, and the rating fails with it.
+        assert!(Reputation::submit_rating_with_tip(
+            RuntimeOrigin::signed(account(6)),
+            account(1),
+            5,
+            5,
+            5,
+            5,
+            5,
+            review(6),
+            ENDOWMENT,
+        )
+        .is_err());
+        assert!(Reputation::ratings(account(1), account(6)).is_none());
+    });
+}
+
+#[test]
+fn helpfulness_votes_weight_the_average() {
+    ExtBuilder::default().ratings(&[(5, 1, 5), (6, 1, 1), (7, 2, 4)]).build_and_execute(|| {
+        assert_eq!(Reputation::get_weighted_average_score(&account(1)), Some(300));
+
+        assert_noop!(
+            Reputation::upvote_rating(RuntimeOrigin::signed(account(1)), account(1), account(5)),
+            Error::<Test>::CannotVoteOwnRating
+        );
+        assert_noop!(
+            Reputation::upvote_rating(RuntimeOrigin::signed(account(3)), account(1), account(5)),
+            Error::<Test>::NotEnoughReviews
+        );
+
+        assert_ok!(Reputation::upvote_rating(RuntimeOrigin::signed(account(2)), account(1), account(5)));
+        assert_noop!(
+            Reputation::downvote_rating(RuntimeOrigin::signed(account(2)), account(1), account(5)),
+            Error::<Test>::AlreadyVoted
+        );
+
+        // Weights 11 and 10: (11 * 500 + 10 * 100) / 21.
+        assert_eq!(Reputation::get_weighted_average_score(&account(1)), Some(309));
+        assert_eq!(Reputation::ratings_by_helpfulness(&account(1)), vec![(account(5), 1), (account(6), 0)]);
+    });
+}
+
+/// Jurors 2 to 4 rated 5 stars; flaggers 6 and 7 with profiles of their own.
+fn jury_ext() -> ExtBuilder {
+    ExtBuilder::default().ratings(&[(5, 1, 1), (8, 2, 5), (8, 3, 5), (8, 4, 5)])
+}
+
+fn flag_rating_of_1_by_5() {
+    create_profiles(&[6, 7]);
+    for flagger in [6, 7] {
+        assert_ok!(Reputation::flag_rating(
+            RuntimeOrigin::signed(account(flagger)),
+            account(1),
+            account(5),
+            FlagReason::Spam,
+        ));
+    }
+}
+
+#[test]
+fn jury_hides_a_rating_and_slashes_no_shows() {
+    jury_ext().build_and_execute(|| {
+        for juror in [2, 3, 4] {
+            assert_ok!(Reputation::register_juror(RuntimeOrigin::signed(account(juror))));
+        }
+        flag_rating_of_1_by_5();
+        assert_eq!(OpenCase::<Test>::get(account(1), account(5)), Some(0));
+        assert!(!Cases::<Test>::contains_key(0));
+
+        // Drawn `JuryDrawDelay` blocks after the last flag.
+        run_to_block(3);
+        let mut jurors = Cases::<Test>::get(0).unwrap().jurors.into_inner();
+        jurors.sort();
+        assert_eq!(jurors, vec![account(2), account(3), account(4)]);
+
+        let salt = [7u8; 32];
+        for juror in [2, 3] {
+            let commitment = BlakeTwo256::hash_of(&(Verdict::Hide, salt));
+            assert_ok!(Reputation::commit_vote(RuntimeOrigin::signed(account(juror)), 0, commitment));
+        }
+        assert_noop!(
+            Reputation::reveal_vote(RuntimeOrigin::signed(account(2)), 0, Verdict::Hide, salt),
+            Error::<Test>::NotInRevealPhase
+        );
+
+        run_to_block(9);
+        assert_noop!(
+            Reputation::reveal_vote(RuntimeOrigin::signed(account(2)), 0, Verdict::Keep, salt),
+            Error::<Test>::CommitmentMismatch
+        );
+        for juror in [2, 3] {
+            assert_ok!(Reputation::reveal_vote(RuntimeOrigin::signed(account(juror)), 0, Verdict::Hide, salt));
+        }
+        assert_noop!(
+            Reputation::resolve_case(RuntimeOrigin::signed(account(8)), 0),
+            Error::<Test>::CaseStillOpen
+        );
+
+        run_to_block(14);
+        assert_ok!(Reputation::resolve_case(RuntimeOrigin::signed(account(8)), 0));
+
+        assert!(HiddenRatings::<Test>::contains_key(account(1), account(5)));
+        assert_eq!(Reputation::user_reputation(account(1)).unwrap().review_count, 0);
+        // Flaggers get their deposits back; the no-show's slash is split by the majority.
+        assert_eq!(Balances::reserved_balance(account(6)), 0);
+        assert_eq!(Balances::reserved_balance(account(4)), 0);
+        assert_eq!(Balances::free_balance(account(4)), ENDOWMENT - 10);
+        assert_eq!(Balances::free_balance(account(2)), ENDOWMENT - 100 + 5);
+        assert_eq!(Jurors::<Test>::get().into_inner(), vec![account(2), account(3)]);
+        System::assert_has_event(
+            Event::CaseResolved { case_id: 0, verdict: Verdict::Hide, hide_votes: 2, keep_votes: 0 }.into(),
+        );
+    });
+}
+
+#[test]
+fn cases_without_eligible_jurors_await_governance() {
+    jury_ext().build_and_execute(|| {
+        flag_rating_of_1_by_5();
+        run_to_block(3);
+        assert!(Cases::<Test>::get(0).unwrap().jurors.is_empty());
+
+        assert_noop!(
+            Reputation::resolve_case(RuntimeOrigin::signed(account(8)), 0),
+            Error::<Test>::AwaitingGovernance
+        );
+        assert_ok!(Reputation::decide_case(RuntimeOrigin::root(), 0, Verdict::Keep));
+
+        // Kept: the flag deposits are forfeited.
+        assert!(!HiddenRatings::<Test>::contains_key(account(1), account(5)));
+        assert_eq!(Balances::free_balance(account(6)), ENDOWMENT - 10);
+        assert!(!OpenCase::<Test>::contains_key(account(1), account(5)));
+    });
+}
+
+#[test]
+fn params_default_to_values_that_accept_ratings() {
+    ExtBuilder::default().build_and_execute(|| {
+        ReputationParams::<Test>::kill();
+
+        let params = Reputation::params();
+        assert_eq!((params.min_stake_amount, params.max_reviews_per_user), (1, 10_000));
+        assert_ok!(rate(5, 1, 4));
+        assert_ok!(Reputation::stake_reputation(RuntimeOrigin::signed(account(1)), 1));
+    });
+}
+
+#[test]
+fn set_params_is_gated_and_validated() {
+    ExtBuilder::default().build_and_execute(|| {
+        let params = ReputationParameters { max_reviews_per_user: 1, rating_deposit: 10, ..Default::default() };
+        assert!(Reputation::set_params(RuntimeOrigin::signed(account(1)), params.clone()).is_err());
+        assert_noop!(
+            Reputation::set_params(
+                RuntimeOrigin::root(),
+                ReputationParameters { max_reviews_per_user: 0, ..params.clone() }
+            ),
+            Error::<Test>::InvalidParams
+        );
+
+        assert_ok!(Reputation::set_params(RuntimeOrigin::root(), params.clone()));
+        System::assert_last_event(Event::ParamsUpdated { old: Default::default(), new: params }.into());
+
+        assert_ok!(rate(5, 1, 4));
+        assert_noop!(rate(6, 1, 4), Error::<Test>::TooManyReviews);
+    });
+}
+
+#[test]
+fn rating_deposit_is_slashed_once_a_hidden_rating_stays_hidden() {
+    let params = ReputationParameters { rating_deposit: 25, ..Default::default() };
+    ExtBuilder::default().params(params).build_and_execute(|| {
+        assert_ok!(rate(5, 1, 1));
+        assert_eq!(Balances::reserved_balance(account(5)), 25);
+        assert_eq!(RatingDeposits::<Test>::get(account(1), account(5)), Some(25));
+
+        flag_rating_of_1_by_5();
+        run_to_block(3);
+        assert_ok!(Reputation::decide_case(RuntimeOrigin::root(), 0, Verdict::Hide));
+        assert_eq!(Balances::reserved_balance(account(5)), 25);
+
+        // Closed once the appeal period is over.
+        run_to_block(14);
+        assert_eq!(Balances::reserved_balance(account(5)), 0);
+        assert_eq!(Balances::free_balance(account(5)), ENDOWMENT - 25);
+        assert!(!RatingDeposits::<Test>::contains_key(account(1), account(5)));
+        System::assert_has_event(Event::RatingDepositSlashed { to: account(1), from: account(5), amount: 25 }.into());
+    });
+}
+
+#[test]
+fn rating_cooldown_spaces_out_ratings() {
+    let params = ReputationParameters { rating_cooldown: 5, ..Default::default() };
+    ExtBuilder::default().params(params).build_and_execute(|| {
+        assert_ok!(rate(5, 1, 4));
+        assert_noop!(rate(5, 2, 4), Error::<Test>::RatingCooldown);
+
+        run_to_block(6);
+        assert_ok!(rate(5, 2, 4));
+    });
+}
+
+#[test]
+fn older_ratings_decay() {
+    ExtBuilder::default().build_and_execute(|| {
+        set_now(1);
+        assert_ok!(rate(5, 1, 5));
+        set_now(2_001);
+        assert_ok!(rate(6, 1, 1));
+        assert_eq!(Reputation::get_weighted_average_score(&account(1)), Some(300));
+
+        let params = ReputationParameters { decay_half_life: 1_000, ..Default::default() };
+        assert_ok!(Reputation::set_params(RuntimeOrigin::root(), params));
+
+        // Two half-lives old, so a quarter of the weight: (25 * 500 + 100 * 100) / 125.
+        assert_eq!(Reputation::get_weighted_average_score(&account(1)), Some(180));
+    });
+}
+
+#[test]
+fn owners_pause_and_reactivate_profiles() {
+    ExtBuilder::default().build_and_execute(|| {
+        assert_ok!(Reputation::deactivate_profile(RuntimeOrigin::signed(account(1))));
+        assert_noop!(rate(5, 1, 4), Error::<Test>::ProfileNotActive);
+        assert_noop!(
+            Reputation::set_profile_status(RuntimeOrigin::root(), account(2), ProfileStatus::Paused),
+            Error::<Test>::InvalidStatusTransition
+        );
+
+        assert_ok!(Reputation::reactivate_profile(RuntimeOrigin::signed(account(1))));
+        assert_noop!(
+            Reputation::reactivate_profile(RuntimeOrigin::signed(account(1))),
+            Error::<Test>::InvalidStatusTransition
+        );
+        assert_ok!(rate(5, 1, 4));
+    });
+}
+
+#[test]
+fn suspensions_expire() {
+    ExtBuilder::default().build_and_execute(|| {
+        let suspended = ProfileStatus::Suspended { until: 5, reason: SuspensionReason::Spam };
+        assert_noop!(
+            Reputation::set_profile_status(
+                RuntimeOrigin::root(),
+                account(1),
+                ProfileStatus::Suspended { until: 1, reason: SuspensionReason::Spam }
+            ),
+            Error::<Test>::InvalidStatusTransition
+        );
+        assert_ok!(Reputation::set_profile_status(RuntimeOrigin::root(), account(1), suspended.clone()));
+        assert_eq!(Reputation::user_reputation(account(1)).unwrap().status, suspended);
+        assert_noop!(rate(5, 1, 4), Error::<Test>::ProfileNotActive);
+        // The owner cannot lift it.
+        assert_noop!(
+            Reputation::reactivate_profile(RuntimeOrigin::signed(account(1))),
+            Error::<Test>::InvalidStatusTransition
+        );
+
+        run_to_block(5);
+        assert_eq!(Reputation::user_reputation(account(1)).unwrap().status, ProfileStatus::Active);
+        assert_ok!(rate(5, 1, 4));
+    });
+}
+
+#[test]
+fn banned_profiles_stay_banned() {
+    ExtBuilder::default().build_and_execute(|| {
+        assert_ok!(Reputation::set_profile_status(RuntimeOrigin::root(), account(1), ProfileStatus::Banned));
+        assert!(!<Reputation as ReputationProvider<_>>::is_active(&account(1)));
+        assert_noop!(
+            Reputation::reactivate_profile(RuntimeOrigin::signed(account(1))),
+            Error::<Test>::InvalidStatusTransition
+        );
+
+        run_to_block(20);
+        assert_eq!(Reputation::user_reputation(account(1)).unwrap().status, ProfileStatus::Banned);
+    });
+}
+
+fn hide_rating_of_1_by_5() {
+    flag_rating_of_1_by_5();
+    run_to_block(3);
+    assert_ok!(Reputation::decide_case(RuntimeOrigin::root(), 0, Verdict::Hide));
+    assert_ok!(Reputation::appeal(RuntimeOrigin::signed(account(5)), 0, [1; 32]));
+}
+
+fn refunded() -> bool {
+    System::events()
+        .iter()
+        .any(|record| matches!(record.event, RuntimeEvent::Reputation(Event::VoucherRefunded { .. })))
+}
+
+#[test]
+fn upheld_appeal_unhides_the_rating() {
+    jury_ext().build_and_execute(|| {
+        hide_rating_of_1_by_5();
+        assert_eq!(Balances::reserved_balance(account(5)), 50);
+        assert_noop!(
+            Reputation::appeal(RuntimeOrigin::signed(account(5)), 0, [1; 32]),
+            Error::<Test>::AlreadyAppealed
+        );
+
+        assert_ok!(Reputation::decide_appeal(RuntimeOrigin::root(), 0, true));
+
+        assert!(!HiddenRatings::<Test>::contains_key(account(1), account(5)));
+        assert_eq!(Reputation::user_reputation(account(1)).unwrap().review_count, 1);
+        assert_eq!(Balances::reserved_balance(account(5)), 0);
+        assert!(Reputation::reversals(0).is_some());
+        System::assert_has_event(Event::ModerationReversed { case_id: 0, subject: account(5) }.into());
+    });
+}
+
+#[test]
+fn unhiding_waits_for_the_ratings_migration() {
+    jury_ext().build_and_execute(|| {
+        hide_rating_of_1_by_5();
+
+        start_ratings_migration();
+        assert_noop!(
+            Reputation::decide_appeal(RuntimeOrigin::root(), 0, true),
+            Error::<Test>::MigrationInProgress
+        );
+        RatingsMigrationCursor::<Test>::kill();
+
+        assert_ok!(Reputation::decide_appeal(RuntimeOrigin::root(), 0, true));
+        assert_eq!(Reputation::user_reputation(account(1)).unwrap().review_count, 1);
+    });
+}
+
+#[test]
+fn unhiding_a_missing_rating_is_refused() {
+    jury_ext().build().execute_with(|| {
+        hide_rating_of_1_by_5();
+        Ratings::<Test>::remove(account(1), account(5));
+
+        assert_noop!(
+            Reputation::decide_appeal(RuntimeOrigin::root(), 0, true),
+            Error::<Test>::RatingNotFound
+        );
+        assert!(HiddenRatings::<Test>::contains_key(account(1), account(5)));
+    });
+}
+
+#[test]
+fn rejected_and_lapsed_appeals() {
+    jury_ext().build_and_execute(|| {
+        hide_rating_of_1_by_5();
+        assert_ok!(Reputation::decide_appeal(RuntimeOrigin::root(), 0, false));
+        assert!(HiddenRatings::<Test>::contains_key(account(1), account(5)));
+        assert_eq!(Balances::free_balance(account(5)), ENDOWMENT - 50);
+        System::assert_last_event(Event::AppealRejected { case_id: 0, bond_slashed: 50 }.into());
+
+        assert_ok!(Reputation::set_profile_status(RuntimeOrigin::root(), account(2), ProfileStatus::UnderReview));
+        assert_ok!(Reputation::appeal(RuntimeOrigin::signed(account(2)), 1, [2; 32]));
+        run_to_block(14);
+        assert_eq!(Balances::reserved_balance(account(2)), 0);
+        assert!(!Appeals::<Test>::contains_key(1));
+        System::assert_has_event(Event::AppealLapsed { case_id: 1 }.into());
+        // Closed, so it cannot be appealed again.
+        assert_noop!(
+            Reputation::appeal(RuntimeOrigin::signed(account(2)), 1, [2; 32]),
+            Error::<Test>::ModerationNotFound
+        );
+    });
+}
+
+#[test]
+fn voucher_refunds_come_from_held_slashes() {
+    ExtBuilder::default().build_and_execute(|| {
+        let (voucher, target) = (account(1), account(2));
+        let issuance = Balances::total_issuance();
+        assert_ok!(Reputation::vouch(RuntimeOrigin::signed(voucher.clone()), target.clone(), 100));
+        assert_ok!(Reputation::penalise(RuntimeOrigin::root(), target.clone(), Perbill::from_percent(50)));
+        assert_ok!(Reputation::appeal(RuntimeOrigin::signed(target.clone()), 0, [1; 32]));
+
+        assert_ok!(Reputation::decide_appeal(RuntimeOrigin::root(), 0, true));
+
+        assert_eq!(Balances::free_balance(&voucher), ENDOWMENT - 100 + 50);
+        assert_eq!(Balances::free_balance(Reputation::account_id()), 1);
+        // Nothing minted.
+        assert_eq!(Balances::total_issuance(), issuance);
+        System::assert_has_event(Event::VoucherRefunded { voucher, target, amount: 50 }.into());
+    });
+}
+
+#[test]
+fn unpaid_voucher_refunds_emit_nothing() {
+    ExtBuilder::default().build_and_execute(|| {
+        let (voucher, target) = (account(1), account(2));
+        assert_ok!(Reputation::vouch(RuntimeOrigin::signed(voucher.clone()), target.clone(), 100));
+        assert_ok!(Reputation::penalise(RuntimeOrigin::root(), target.clone(), Perbill::from_percent(50)));
+        assert_ok!(Reputation::appeal(RuntimeOrigin::signed(target.clone()), 0, [1; 32]));
+        Balances::make_free_balance_be(&Reputation::account_id(), 1);
+
+        assert_ok!(Reputation::decide_appeal(RuntimeOrigin::root(), 0, true));
+
+        assert_eq!(Balances::free_balance(&voucher), ENDOWMENT - 100);
+        assert!(!refunded());
+    });
+}
+
+#[test]
+fn ensure_reputation_checks_score_reviews_stake_and_status() {
+    ExtBuilder::default().ratings(&[(5, 1, 3), (5, 2, 5)]).build_and_execute(|| {
+        type FourStars = EnsureReputation<Test, ConstU32<4>, ConstU32<1>, ConstU128<0>>;
+        type Staked = EnsureReputation<Test, ConstU32<4>, ConstU32<1>, ConstU128<10>>;
+
+        assert_eq!(FourStars::try_origin(RuntimeOrigin::signed(account(2))).ok(), Some(account(2)));
+        assert!(FourStars::try_origin(RuntimeOrigin::signed(account(1))).is_err());
+        // No reviews, and no profile.
+        assert!(FourStars::try_origin(RuntimeOrigin::signed(account(3))).is_err());
+        assert!(FourStars::try_origin(RuntimeOrigin::signed(account(5))).is_err());
+        assert!(FourStars::try_origin(RuntimeOrigin::root()).is_err());
+
+        assert!(Staked::try_origin(RuntimeOrigin::signed(account(2))).is_err());
+        assert_ok!(Reputation::stake_reputation(RuntimeOrigin::signed(account(2)), 10));
+        assert!(Staked::try_origin(RuntimeOrigin::signed(account(2))).is_ok());
+
+        assert_ok!(Reputation::deactivate_profile(RuntimeOrigin::signed(account(2))));
+        assert!(FourStars::try_origin(RuntimeOrigin::signed(account(2))).is_err());
+    });
+}
+
+#[test]
+fn reputation_provider_reads_profiles() {
+    ExtBuilder::default().ratings(&[(5, 1, 5), (6, 1, 2)]).build_and_execute(|| {
+        type Provider = Reputation;
+        assert_ok!(Reputation::stake_reputation(RuntimeOrigin::signed(account(1)), 7));
+
+        assert_eq!(<Provider as ReputationProvider<_>>::average_score(&account(1)), Some(3));
+        assert_eq!(<Provider as ReputationProvider<_>>::review_count(&account(1)), 2);
+        assert_eq!(
+            <Provider as ReputationProvider<_>>::category_average(&account(1)),
+            Some(CategoryScores { communication: 3, reliability: 3, quality: 3, professionalism: 3 })
+        );
+        assert_eq!(<Provider as ReputationProvider<_>>::staked_amount(&account(1)), 7);
+        assert!(<Provider as ReputationProvider<_>>::is_active(&account(1)));
+
+        assert_eq!(<Provider as ReputationProvider<_>>::average_score(&account(5)), None);
+        assert_eq!(<Provider as ReputationProvider<_>>::review_count(&account(5)), 0);
+        assert!(!<Provider as ReputationProvider<_>>::is_active(&account(5)));
+    });
+}
+
+#[test]
+fn on_rating_submitted_hears_every_stored_rating() {
+    ExtBuilder::default().build_and_execute(|| {
+        assert_ok!(rate(5, 1, 4));
+        assert_noop!(rate(5, 1, 3), Error::<Test>::AlreadyRated);
+
+        assert_eq!(submitted_ratings(), vec![(Some(account(5)), account(1), 4)]);
+    });
+}
+
+fn commitment(from: u8, score: u8) -> H256 {
+    BlakeTwo256::hash_of(&(score, [score; 4], &review(from), [from; 32]))
+}
+
+fn commit(from: u8, to: u8, score: u8) -> DispatchResult {
+    Reputation::commit_rating(RuntimeOrigin::signed(account(from)), account(to), commitment(from, score))
+}
+
+fn reveal(from: u8, to: u8, score: u8) -> DispatchResult {
+    Reputation::reveal_rating(
+        RuntimeOrigin::signed(account(from)),
+        account(to),
+        score,
+        score,
+        score,
+        score,
+        score,
+        review(from),
+        [from; 32],
+    )
+}
+
+#[test]
+fn mutual_reveals_publish_both_ratings() {
+    ExtBuilder::default().build_and_execute(|| {
+        assert_ok!(commit(1, 2, 5));
+        assert_ok!(commit(2, 1, 2));
+        assert_eq!(Balances::reserved_balance(account(1)), 20);
+        assert_noop!(rate(1, 2, 5), Error::<Test>::AlreadyCommitted);
+
+        assert_noop!(reveal(1, 2, 4), Error::<Test>::CommitmentMismatch);
+        assert_ok!(reveal(1, 2, 5));
+        assert_eq!(Balances::reserved_balance(account(1)), 0);
+        assert!(Reputation::ratings(account(2), account(1)).is_none());
+
+        assert_ok!(reveal(2, 1, 2));
+        assert_eq!(Reputation::ratings(account(2), account(1)).unwrap().score, 5);
+        assert_eq!(Reputation::ratings(account(1), account(2)).unwrap().score, 2);
+        assert!(!RatingCommits::<Test>::contains_key(account(2), account(1)));
+    });
+}
+
+#[test]
+fn reveal_windows_close_by_publishing_or_slashing() {
+    ExtBuilder::default().build_and_execute(|| {
+        assert_ok!(commit(1, 2, 5));
+        assert_ok!(commit(2, 1, 2));
+        assert_ok!(reveal(1, 2, 5));
+
+        run_to_block(6);
+        assert_noop!(reveal(2, 1, 2), Error::<Test>::NoCommitment);
+
+        // The revealed side is published; the other loses half its deposit.
+        assert_eq!(Reputation::ratings(account(2), account(1)).unwrap().score, 5);
+        assert!(Reputation::ratings(account(1), account(2)).is_none());
+        assert_eq!(Balances::reserved_balance(account(2)), 0);
+        assert_eq!(Balances::free_balance(account(2)), ENDOWMENT - 10);
+        System::assert_has_event(Event::RatingCommitExpired { from: account(2), to: account(1), slashed: 10 }.into());
+    });
+}
+
+fn rate_in_session(session_id: SessionId, from: u8, score: u8) -> DispatchResult {
+    Reputation::submit_session_rating(
+        RuntimeOrigin::signed(account(from)),
+        session_id,
+        score,
+        score,
+        score,
+        score,
+        score,
+        review(from),
+    )
+}
+
+#[test]
+fn sessions_publish_once_both_sides_rate() {
+    ExtBuilder::default().build_and_execute(|| {
+        assert_ok!(Reputation::open_session(RuntimeOrigin::signed(account(1)), account(2)));
+        assert_eq!(Balances::reserved_balance(account(1)), 30);
+        assert_noop!(rate(1, 2, 5), Error::<Test>::SessionAlreadyOpen);
+        assert_noop!(rate_in_session(0, 3, 5), Error::<Test>::NotInSession);
+
+        assert_ok!(rate_in_session(0, 1, 5));
+        assert_ok!(rate_in_session(0, 2, 3));
+        assert!(Reputation::ratings(account(2), account(1)).is_none());
+
+        run_to_block(2);
+        assert_eq!(Reputation::ratings(account(2), account(1)).unwrap().score, 5);
+        assert_eq!(Reputation::ratings(account(1), account(2)).unwrap().score, 3);
+        assert_eq!(Balances::reserved_balance(account(1)), 0);
+        System::assert_has_event(Event::SessionPublished { session_id: 0, ratings: 2 }.into());
+    });
+}
+
+#[test]
+fn verified_sessions_take_no_deposit() {
+    ExtBuilder::default().build_and_execute(|| {
+        assert!(Reputation::open_verified_session(RuntimeOrigin::signed(account(1)), account(1), account(2)).is_err());
+        assert_ok!(Reputation::open_verified_session(RuntimeOrigin::root(), account(1), account(2)));
+        assert_eq!(Balances::reserved_balance(account(1)), 0);
+        assert!(!SessionDeposits::<Test>::contains_key(0));
+    });
+}
+
+#[test]
+fn full_publication_blocks_roll_over() {
+    ExtBuilder::default().build_and_execute(|| {
+        for (first, second) in [(1, 2), (3, 4), (1, 3)] {
+            assert_ok!(Reputation::open_session(RuntimeOrigin::signed(account(first)), account(second)));
+        }
+        assert_eq!(Balances::reserved_balance(account(1)), 60);
+        assert_eq!(Sessions::<Test>::get(1).unwrap().deadline, 11);
+        assert_eq!(Sessions::<Test>::get(2).unwrap().deadline, 12);
+        System::assert_last_event(
+            Event::SessionOpened { session_id: 2, first: account(1), second: account(3), verified: false, deadline: 12 }
+                .into(),
+        );
+
+        assert_ok!(rate_in_session(2, 1, 4));
+        run_to_block(12);
+        assert!(Sessions::<Test>::iter().next().is_none());
+        assert_eq!(Reputation::ratings(account(3), account(1)).unwrap().score, 4);
+        assert_eq!(Balances::reserved_balance(account(1)), 0);
+    });
+}
+
+/// An anonymous rating of 2 by the identity account 1 registered, with a proof `TestVerifier`
+/// accepts.
+fn anonymous_rating(score: u8, nullifier: [u8; 32]) -> Call<Test> {
+    let review_hash = review(9);
+    let signal = anonymous::signal_hash(&account(2), score, &[score; 4], &review_hash);
+    Call::submit_anonymous_rating {
+        target: account(2),
+        score,
+        category_ratings: [score; 4],
+        review_hash,
+        root: AnonRoots::<Test>::get().last().copied().unwrap(),
+        nullifier,
+        proof: BoundedVec::truncate_from(signal.to_vec()),
+    }
+}
+
+fn enable_anonymous_ratings() {
+    assert_ok!(Reputation::set_anonymous_verifying_key(RuntimeOrigin::root(), BoundedVec::truncate_from(vec![1])));
+    assert_ok!(Reputation::register_anonymous_identity(RuntimeOrigin::signed(account(1)), [3; 32]));
+}
+
+#[test]
+fn anonymous_ratings_need_a_valid_proof() {
+    ExtBuilder::default().build_and_execute(|| {
+        enable_anonymous_ratings();
+        let call = anonymous_rating(4, [7; 32]);
+        assert_ok!(Reputation::validate_unsigned(TransactionSource::External, &call));
+
+        let Call::submit_anonymous_rating { target, score, category_ratings, review_hash, root, nullifier, .. } = call
+        else {
+            unreachable!()
+        };
+        let forged = Call::submit_anonymous_rating {
+            target,
+            score: 5,
+            category_ratings,
+            review_hash,
+            root,
+            nullifier,
+            proof: BoundedVec::truncate_from(vec![0; 32]),
+        };
+        assert!(Reputation::validate_unsigned(TransactionSource::External, &forged).is_err());
+        // Not a canonical field element.
+        let call = anonymous_rating(4, [0xff; 32]);
+        assert!(Reputation::validate_unsigned(TransactionSource::External, &call).is_err());
+    });
+}
+
+#[test]
+fn anonymous_ratings_reach_the_hook_and_the_index() {
+    ExtBuilder::default().build_and_execute(|| {
+        enable_anonymous_ratings();
+        set_now(1_000);
+        let nullifier = [7; 32];
+        assert_ok!(anonymous_rating(4, nullifier).dispatch_bypass_filter(RuntimeOrigin::none()));
+
+        let profile = Reputation::user_reputation(account(2)).unwrap();
+        assert_eq!((profile.total_score, profile.review_count), (4, 1));
+        assert_eq!(submitted_ratings(), vec![(None, account(2), 4)]);
+
+        let category_ratings = CategoryScores { communication: 4, reliability: 4, quality: 4, professionalism: 4 };
+        let entry = IndexEntry {
+            previous: System::parent_hash(),
+            record: RatingRecord {
+                to: account(2),
+                from: Rater::Anonymous(nullifier),
+                change: RatingChange::Submitted { score: 4, category_ratings },
+                at: 1_000,
+            },
+        };
+        assert_eq!(RatingChangesHead::<Test>::get(), Some(BlakeTwo256::hash_of(&entry)));
+
+        assert_noop!(
+            anonymous_rating(4, nullifier).dispatch_bypass_filter(RuntimeOrigin::none()).map_err(|e| e.error),
+            Error::<Test>::NullifierUsed
+        );
+    });
+}
+
+#[test]
+fn review_content_is_stored_against_a_deposit() {
+    ExtBuilder::default().build_and_execute(|| {
+        let body = b"prompt and friendly".to_vec();
+        let hash = sp_io::hashing::blake2_256(&body);
+        assert_noop!(
+            Reputation::store_review_content(RuntimeOrigin::signed(account(5)), vec![0; 65]),
+            Error::<Test>::ContentTooLong
+        );
+
+        assert_ok!(Reputation::store_review_content(RuntimeOrigin::signed(account(5)), body.clone()));
+        assert_eq!(Balances::reserved_balance(account(5)), 5 + body.len() as Balance);
+        assert_eq!(Reputation::review_content(&hash), Some(body.clone()));
+        assert_noop!(
+            Reputation::store_review_content(RuntimeOrigin::signed(account(6)), body),
+            Error::<Test>::ContentAlreadyStored
+        );
+        assert_noop!(
+            Reputation::remove_review_content(RuntimeOrigin::signed(account(6)), hash),
+            Error::<Test>::NotDepositor
+        );
+
+        assert_ok!(Reputation::remove_review_content(RuntimeOrigin::signed(account(5)), hash));
+        assert_eq!(Balances::reserved_balance(account(5)), 0);
+        assert_eq!(Reputation::review_content(&hash), None);
+    });
+}
+
+#[test]
+fn content_ratings_refer_to_cannot_be_removed() {
+    ExtBuilder::default().build_and_execute(|| {
+        let body = b"prompt and friendly".to_vec();
+        let hash = sp_io::hashing::blake2_256(&body);
+        assert_ok!(Reputation::store_review_content(RuntimeOrigin::signed(account(5)), body));
+        assert_ok!(Reputation::submit_rating(
+            RuntimeOrigin::signed(account(5)),
+            account(1),
+            5,
+            5,
+            5,
+            5,
+            5,
+            ContentRef::blake2_256(hash),
+        ));
+        assert_eq!(ContentReferences::<Test>::get(hash), 1);
+
+        assert_noop!(
+            Reputation::remove_review_content(RuntimeOrigin::signed(account(5)), hash),
+            Error::<Test>::ContentInUse
+        );
+
+        // Governance can still erase it, keeping the hash ratings point at.
+        assert_ok!(Reputation::forget_review_content(RuntimeOrigin::root(), hash));
+        assert_eq!(Balances::reserved_balance(account(5)), 0);
+        assert_eq!(Reputation::review_content(&hash), None);
+        assert!(ReviewContent::<Test>::contains_key(hash));
+    });
+}
+
+/// A report on the genesis rating 5 left for 1, signed by a registered reporter.
+fn content_report(at: u64) -> Call<Test> {
+    let pair = sr25519::Pair::from_seed(&[9; 32]);
+    let public = MultiSigner::from(pair.public());
+    let report = ContentReport {
+        to: account(1),
+        from: account(5),
+        review_hash: ContentRef::sha2_256([0; 32]),
+        status: ContentStatus::ContentVerified,
+        at,
+        public,
+    };
+    let signature = MultiSignature::from(pair.sign(&report.encode()));
+    Call::report_content { report, signature }
+}
+
+fn register_reporter() {
+    let reporter = MultiSigner::from(sr25519::Pair::from_seed(&[9; 32]).public()).into_account();
+    assert_ok!(Reputation::add_content_reporter(RuntimeOrigin::root(), reporter));
+}
+
+#[test]
+fn content_reports_are_checked_before_they_are_accepted() {
+    ExtBuilder::default().ratings(&[(5, 1, 4)]).build_and_execute(|| {
+        assert!(Reputation::validate_unsigned(TransactionSource::External, &content_report(1)).is_err());
+        register_reporter();
+
+        run_to_block(3);
+        assert_ok!(Reputation::validate_unsigned(TransactionSource::External, &content_report(2)));
+        // Dated after the current block.
+        assert!(Reputation::validate_unsigned(TransactionSource::External, &content_report(4)).is_err());
+        assert_noop!(
+            content_report(4).dispatch_bypass_filter(RuntimeOrigin::none()).map_err(|e| e.error),
+            Error::<Test>::InvalidReport
+        );
+
+        assert_ok!(content_report(3).dispatch_bypass_filter(RuntimeOrigin::none()));
+        assert_eq!(
+            ContentChecks::<Test>::get(account(1), account(5)),
+            Some(ContentCheck { status: ContentStatus::ContentVerified, at: 3 })
+        );
+        // Not newer than the last report.
+        assert!(Reputation::validate_unsigned(TransactionSource::External, &content_report(3)).is_err());
+    });
+}
+
+fn submitted(to: u8, from: u8, score: u8, at: u64) -> RatingRecord<AccountId> {
+    RatingRecord {
+        to: account(to),
+        from: Rater::Account(account(from)),
+        change: RatingChange::Submitted {
+            score,
+            category_ratings: CategoryScores {
+                communication: score as u32,
+                reliability: score as u32,
+                quality: score as u32,
+                professionalism: score as u32,
+            },
+        },
+        at,
+    }
+}
+
+#[test]
+fn rating_changes_chain_from_the_parent_hash() {
+    ExtBuilder::default().ratings(&[(5, 1, 4)]).build_and_execute(|| {
+        // Left over from genesis until the next block starts.
+        assert!(RatingChangesHead::<Test>::exists());
+        run_to_block(2);
+        assert!(!RatingChangesHead::<Test>::exists());
+
+        set_now(5_000);
+        assert_ok!(rate(6, 1, 3));
+        assert_ok!(rate(7, 2, 5));
+
+        let first = IndexEntry { previous: System::parent_hash(), record: submitted(1, 6, 3, 5_000) };
+        let first_link = BlakeTwo256::hash_of(&first);
+        let second = IndexEntry { previous: first_link, record: submitted(2, 7, 5, 5_000) };
+        assert_eq!(RatingChangesHead::<Test>::get(), Some(BlakeTwo256::hash_of(&second)));
+    });
+}
+
+#[test]
+fn hiding_and_unhiding_are_indexed() {
+    jury_ext().build_and_execute(|| {
+        hide_rating_of_1_by_5();
+        let hidden = IndexEntry {
+            previous: System::parent_hash(),
+            record: RatingRecord { to: account(1), from: Rater::Account(account(5)), change: RatingChange::Hidden, at: 0 },
+        };
+        let hidden_link = BlakeTwo256::hash_of(&hidden);
+        assert_eq!(RatingChangesHead::<Test>::get(), Some(hidden_link));
+
+        assert_ok!(Reputation::decide_appeal(RuntimeOrigin::root(), 0, true));
+        let unhidden = IndexEntry {
+            previous: hidden_link,
+            record: RatingRecord {
+                to: account(1),
+                from: Rater::Account(account(5)),
+                change: RatingChange::Unhidden,
+                at: 0,
+            },
+        };
+        assert_eq!(RatingChangesHead::<Test>::get(), Some(BlakeTwo256::hash_of(&unhidden)));
+    });
+}
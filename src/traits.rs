@@ -0,0 +1,61 @@
+//! Traits for pallets that build on reputation without depending on `Pallet<T>` directly.
+
+use super::*;
+
+/// Read access to reputation. Implemented by `Pallet<T>`; downstream pallets take it as an
+/// associated type in their own `Config`.
+pub trait ReputationProvider<AccountId> {
+    /// Average star rating, rounded down. `None` without a profile.
+    fn average_score(who: &AccountId) -> Option<u32>;
+
+    /// Visible reviews counted towards the profile.
+    fn review_count(who: &AccountId) -> u32;
+
+    /// Average score per category, rounded down. `None` without a profile.
+    fn category_average(who: &AccountId) -> Option<CategoryScores>;
+
+    /// Reputation staked by the profile's owner.
+    fn staked_amount(who: &AccountId) -> u128;
+
+    /// Whether the profile exists and is `Active`.
+    fn is_active(who: &AccountId) -> bool;
+}
+
+impl<T: Config> ReputationProvider<T::AccountId> for Pallet<T> {
+    fn average_score(who: &T::AccountId) -> Option<u32> {
+        Self::get_average_score(who)
+    }
+
+    fn review_count(who: &T::AccountId) -> u32 {
+        UserReputation::<T>::get(who).map_or(0, |profile| profile.review_count)
+    }
+
+    fn category_average(who: &T::AccountId) -> Option<CategoryScores> {
+        Self::get_category_average(who)
+    }
+
+    fn staked_amount(who: &T::AccountId) -> u128 {
+        UserReputation::<T>::get(who).map_or(0, |profile| profile.staked_amount)
+    }
+
+    fn is_active(who: &T::AccountId) -> bool {
+        UserReputation::<T>::get(who).map_or(false, |profile| profile.status.is_active())
+    }
+}
+
+/// Called after a rating is stored.
+#[impl_trait_for_tuples::impl_for_tuples(10)]
+pub trait OnRatingSubmitted<AccountId> {
+    /// A rating from `from` to `to`.
+    fn on_rating_submitted(from: &AccountId, to: &AccountId, score: u8);
+
+    /// An anonymous rating of `to`, whose rater is not known.
+    fn on_anonymous_rating_submitted(_to: &AccountId, _score: u8) {}
+}
+
+/// Called after `who`'s profile is created or its score, stake or status changes. Read the new
+/// state through [`ReputationProvider`].
+#[impl_trait_for_tuples::impl_for_tuples(10)]
+pub trait OnProfileChanged<AccountId> {
+    fn on_profile_changed(who: &AccountId);
+}